@@ -0,0 +1,551 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CellType {
+    Mine,
+    Number,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CellState {
+    Unrevealed,
+    Flagged,
+    Revealed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Cell {
+    pub index: usize,
+    pub cell_type: CellType,
+    pub state: CellState,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Board {
+    size: usize,
+    data: Vec<Cell>,
+    /// Set when the game on this board was ended before it was cleared or lost,
+    /// e.g. when the player ran out of time
+    #[serde(default)]
+    ended: bool,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    CoordinatesOutOfBound,
+    InvalidMove,
+    /// Every hint allowed in the game has been used
+    HintLimitReached,
+    /// The board would have more cells than can be addressed
+    TooLarge,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "state")]
+pub enum ExternalCell {
+    #[serde(rename = "number")]
+    Number { value: u8 },
+    #[serde(rename = "mine")]
+    Mine,
+    #[serde(rename = "flagged")]
+    Flagged,
+    #[serde(rename = "unrevealed")]
+    Unrevealed,
+}
+
+impl Board {
+    pub fn generate(size: usize) -> Board {
+        Self::generate_with_rng(size, &mut rand::thread_rng())
+    }
+
+    /// Generates the same board every time for the same size and seed, e.g. for
+    /// puzzles everyone should play on the same layout. Unlike `StdRng`, the
+    /// generator used does not change between releases of `rand`.
+    pub fn generate_seeded(size: usize, seed: u64) -> Board {
+        Self::generate_with_rng(size, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn generate_with_rng(size: usize, rng: &mut impl Rng) -> Board {
+        Board {
+            size,
+            data: (0..size * size)
+                .map(|index| Cell {
+                    index,
+                    state: CellState::Unrevealed,
+                    cell_type: if rng.gen_bool(0.15) {
+                        CellType::Mine
+                    } else {
+                        CellType::Number
+                    },
+                })
+                .collect(),
+            ended: false,
+        }
+    }
+
+    /// Builds a board with mines at exactly the given positions, e.g. to replay
+    /// a known layout
+    pub fn from_mines(size: usize, mines: &[usize]) -> Result<Board, Error> {
+        let cells = size.checked_mul(size).ok_or(Error::TooLarge)?;
+        let mut data: Vec<Cell> = (0..cells)
+            .map(|index| Cell {
+                index,
+                state: CellState::Unrevealed,
+                cell_type: CellType::Number,
+            })
+            .collect();
+
+        for &index in mines {
+            data.get_mut(index)
+                .ok_or(Error::CoordinatesOutOfBound)?
+                .cell_type = CellType::Mine;
+        }
+
+        Ok(Board {
+            size,
+            data,
+            ended: false,
+        })
+    }
+
+    pub fn size(self: &Board) -> usize {
+        self.size
+    }
+
+    /// Positions of every mine on the board in ascending order
+    pub fn mine_positions(self: &Board) -> Vec<usize> {
+        self.iter_cells()
+            .filter(|cell| cell.cell_type == CellType::Mine)
+            .map(|cell| cell.index)
+            .collect()
+    }
+
+    pub fn mines_count(self: &Board) -> usize {
+        self.iter_cells()
+            .filter(|cell| cell.cell_type == CellType::Mine)
+            .count()
+    }
+
+    /// Number of safe cells revealed so far
+    pub fn revealed_count(self: &Board) -> usize {
+        self.iter_cells()
+            .filter(|cell| cell.cell_type == CellType::Number && cell.state == CellState::Revealed)
+            .count()
+    }
+
+    /// Whether a mine has been revealed
+    pub fn is_exploded(self: &Board) -> bool {
+        self.iter_cells()
+            .any(|cell| cell.cell_type == CellType::Mine && cell.state == CellState::Revealed)
+    }
+
+    /// Whether every cell without a mine has been revealed
+    pub fn is_cleared(self: &Board) -> bool {
+        self.iter_cells()
+            .filter(|cell| cell.cell_type == CellType::Number)
+            .all(|cell| cell.state == CellState::Revealed)
+    }
+
+    /// Whether no further moves can be made on this board
+    pub fn is_finished(self: &Board) -> bool {
+        self.ended || self.is_exploded() || self.is_cleared()
+    }
+
+    /// Ends the game on this board without it being cleared
+    pub fn end(self: &mut Board) {
+        self.ended = true;
+    }
+
+    pub fn get(self: &Board, index: usize) -> Option<&Cell> {
+        self.data.get(index)
+    }
+
+    pub fn iter_cells(self: &Board) -> impl Iterator<Item = &Cell> {
+        self.data.iter()
+    }
+
+    fn iter_surrounding_positions(self: &Board, index: usize) -> impl Iterator<Item = usize> {
+        let (row, column) = if let Some(pos) = Self::to_coords(self.size, index) {
+            pos
+        } else {
+            panic!("Position out of bound");
+        };
+
+        let surrounding_coordinates = [
+            (row.checked_sub(1), column.checked_sub(1)),
+            (row.checked_sub(1), Some(column)),
+            (row.checked_sub(1), Some(column + 1)),
+            (Some(row), column.checked_sub(1)),
+            (Some(row), Some(column + 1)),
+            (Some(row + 1), column.checked_sub(1)),
+            (Some(row + 1), Some(column)),
+            (Some(row + 1), Some(column + 1)),
+        ];
+
+        let size = self.size();
+
+        surrounding_coordinates
+            .into_iter()
+            .filter_map(|(row, column)| row.and_then(|row| column.map(|column| (row, column))))
+            .filter_map(move |(row, column)| Self::to_index(size, row, column))
+    }
+
+    pub fn iter_surroundings(self: &Board, index: usize) -> impl Iterator<Item = &Cell> {
+        self.iter_surrounding_positions(index)
+            .filter_map(|index| self.get(index))
+    }
+
+    pub fn get_surrounding_mines_count(self: &Board, index: usize) -> u8 {
+        let count = self
+            .iter_surroundings(index)
+            .filter(|cell| cell.cell_type == CellType::Mine)
+            .count();
+
+        count as u8
+    }
+
+    /// Whether the cell is safe with no mines around it, so revealing it also
+    /// reveals its surroundings
+    fn is_empty_cell(self: &Board, index: usize) -> bool {
+        self.data[index].cell_type == CellType::Number
+            && self.get_surrounding_mines_count(index) == 0
+    }
+
+    /// Numbers that are not revealed by any opening and have to be clicked one by one
+    fn is_isolated_number(self: &Board, index: usize) -> bool {
+        self.data[index].cell_type == CellType::Number
+            && !self.is_empty_cell(index)
+            && !self
+                .iter_surrounding_positions(index)
+                .any(|index| self.is_empty_cell(index))
+    }
+
+    /// Counts groups of connected cells matching `predicate`
+    fn count_regions(self: &Board, predicate: impl Fn(usize) -> bool) -> usize {
+        let mut visited = vec![false; self.data.len()];
+        let mut regions = 0;
+
+        for start in 0..self.data.len() {
+            if visited[start] || !predicate(start) {
+                continue;
+            }
+
+            regions += 1;
+            visited[start] = true;
+
+            let mut queue: VecDeque<usize> = VecDeque::from([start]);
+
+            while let Some(index) = queue.pop_front() {
+                for neighbour in self.iter_surrounding_positions(index) {
+                    if !visited[neighbour] && predicate(neighbour) {
+                        visited[neighbour] = true;
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        regions
+    }
+
+    /// Number of connected regions of empty cells, each cleared by a single click
+    pub fn openings(self: &Board) -> usize {
+        self.count_regions(|index| self.is_empty_cell(index))
+    }
+
+    /// Number of connected groups of numbers that do not border any opening
+    pub fn islands(self: &Board) -> usize {
+        self.count_regions(|index| self.is_isolated_number(index))
+    }
+
+    /// The board's 3BV (Bechtel's Board Benchmark Value): the minimum number of
+    /// clicks needed to clear the board without flagging
+    pub fn bbbv(self: &Board) -> usize {
+        let isolated_numbers = (0..self.data.len())
+            .filter(|&index| self.is_isolated_number(index))
+            .count();
+
+        self.openings() + isolated_numbers
+    }
+
+    pub fn toggle_flag(self: &mut Board, index: usize) -> Result<(), Error> {
+        let cell = self
+            .data
+            .get_mut(index)
+            .ok_or(Error::CoordinatesOutOfBound)?;
+
+        match cell.state {
+            CellState::Unrevealed => cell.state = CellState::Flagged,
+            CellState::Flagged => cell.state = CellState::Unrevealed,
+            CellState::Revealed => return Err(Error::InvalidMove),
+        };
+
+        Ok(())
+    }
+
+    pub fn reveal(self: &mut Board, index: usize) -> Result<(), Error> {
+        let cell = self
+            .data
+            .get_mut(index)
+            .ok_or(Error::CoordinatesOutOfBound)?;
+
+        cell.state = CellState::Revealed;
+
+        let is_empty = self.get_surrounding_mines_count(index) == 0;
+        if !is_empty {
+            return Ok(());
+        }
+
+        // An empty cell was revealed
+        // Perform a BFS to find and reveal all the surrounding empty cells as well
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        queue.push_back(index);
+
+        while !queue.is_empty() {
+            let index = queue.pop_front().unwrap();
+
+            let cell = self.data.get_mut(index).unwrap();
+            cell.state = CellState::Revealed;
+
+            let is_empty =
+                cell.cell_type == CellType::Number && self.get_surrounding_mines_count(index) == 0;
+
+            if !is_empty {
+                continue;
+            }
+
+            for cell in self.iter_surroundings(index) {
+                if cell.state == CellState::Unrevealed {
+                    queue.push_back(cell.index);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reveals every unflagged neighbour of a revealed number once as many
+    /// flags as the number surround it
+    pub fn chord(self: &mut Board, index: usize) -> Result<(), Error> {
+        let cell = self.get(index).ok_or(Error::CoordinatesOutOfBound)?;
+
+        if cell.state != CellState::Revealed || cell.cell_type != CellType::Number {
+            return Err(Error::InvalidMove);
+        }
+
+        let flags = self
+            .iter_surroundings(index)
+            .filter(|cell| cell.state == CellState::Flagged)
+            .count();
+        if flags != self.get_surrounding_mines_count(index) as usize {
+            return Err(Error::InvalidMove);
+        }
+
+        let unrevealed = self
+            .iter_surroundings(index)
+            .filter(|cell| cell.state == CellState::Unrevealed)
+            .map(|cell| cell.index)
+            .collect::<Vec<usize>>();
+
+        for index in unrevealed {
+            self.reveal(index)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Returns the state of the game suitable for consumption on client side
+     */
+    pub fn get_external_state(self: &Board) -> Vec<ExternalCell> {
+        self.iter_cells()
+            .enumerate()
+            .map(|(index, cell)| match cell.state {
+                CellState::Unrevealed => ExternalCell::Unrevealed,
+                CellState::Flagged => ExternalCell::Flagged,
+
+                CellState::Revealed => match cell.cell_type {
+                    CellType::Mine => ExternalCell::Mine,
+                    CellType::Number => ExternalCell::Number {
+                        value: self.get_surrounding_mines_count(index),
+                    },
+                },
+            })
+            .collect::<Vec<ExternalCell>>()
+    }
+
+    fn to_index(size: usize, row: usize, column: usize) -> Option<usize> {
+        if row >= size || column >= size {
+            return None;
+        }
+
+        Some(size * row + column)
+    }
+
+    fn to_coords(size: usize, index: usize) -> Option<(usize, usize)> {
+        if size * size <= index {
+            None
+        } else {
+            Some((index / size, index % size))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generate_seeded() {
+        let board = Board::generate_seeded(16, 42);
+
+        assert_eq!(
+            board.mine_positions(),
+            Board::generate_seeded(16, 42).mine_positions()
+        );
+        assert_ne!(
+            board.mine_positions(),
+            Board::generate_seeded(16, 43).mine_positions()
+        );
+
+        // Seeded boards must not change when dependencies are updated
+        assert_eq!(
+            Board::generate_seeded(6, 42).mine_positions(),
+            [5, 19, 23, 24, 31]
+        );
+    }
+
+    #[test]
+    fn test_from_mines_rejects_unaddressable_size() {
+        assert!(matches!(
+            Board::from_mines(usize::MAX, &[]),
+            Err(Error::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_flag() {
+        let mut board = Board::generate(5);
+
+        let _ = board.toggle_flag(11);
+
+        assert!(board.get(11).unwrap().state == CellState::Flagged);
+    }
+
+    #[test]
+    fn test_reveal() {
+        let mut board = Board::generate(5);
+
+        let _ = board.reveal(21);
+
+        assert!(board.get(21).unwrap().state == CellState::Revealed);
+    }
+
+    #[test]
+    fn test_chord() {
+        // * 2 *
+        // 1 2 1
+        // . . .
+        let mut board = Board::from_mines(3, &[0, 2]).unwrap();
+        board.reveal(4).unwrap();
+
+        assert!(board.chord(4).is_err());
+        assert!(board.chord(0).is_err());
+
+        board.toggle_flag(0).unwrap();
+        board.toggle_flag(2).unwrap();
+        board.chord(4).unwrap();
+
+        assert!(board.is_cleared());
+        assert!(!board.is_exploded());
+    }
+
+    #[test]
+    fn test_chord_on_wrong_flag_explodes() {
+        let mut board = Board::from_mines(3, &[0, 2]).unwrap();
+        board.reveal(1).unwrap();
+        board.toggle_flag(0).unwrap();
+        board.toggle_flag(3).unwrap();
+        board.chord(1).unwrap();
+
+        assert!(board.is_exploded());
+    }
+
+    #[test]
+    fn test_get_invalid() {
+        let board = Board::generate(5);
+        assert!(board.get(51).is_none());
+        assert!(board.get(25).is_none());
+    }
+
+    #[test]
+    fn test_corner_surroundings() {
+        let board = Board::generate(8);
+
+        let mut actual = board.iter_surrounding_positions(0).collect::<Vec<usize>>();
+        actual.sort();
+
+        assert_eq!(
+            board.iter_surrounding_positions(0).collect::<Vec<usize>>(),
+            vec![1, 8, 9]
+        );
+    }
+
+    #[test]
+    fn test_surrounding_mines_count() {
+        let mut data: Vec<Cell> = (0..16)
+            .map(|index| Cell {
+                index,
+                cell_type: CellType::Number,
+                state: CellState::Unrevealed,
+            })
+            .collect();
+
+        data[0] = Cell {
+            index: 0,
+            cell_type: CellType::Mine,
+            state: CellState::Unrevealed,
+        };
+
+        let board = Board {
+            data,
+            size: 4,
+            ended: false,
+        };
+
+        assert_eq!(board.get_surrounding_mines_count(1), 1);
+        assert_eq!(board.get_surrounding_mines_count(4), 1);
+        assert_eq!(board.get_surrounding_mines_count(2), 0);
+        assert_eq!(board.get_surrounding_mines_count(0), 0);
+    }
+
+    #[test]
+    fn test_bbbv() {
+        // 0 1 * 1
+        // 0 1 1 1
+        // 1 1 0 0
+        // * 1 0 0
+        let board = Board::from_mines(4, &[2, 12]).unwrap();
+
+        assert_eq!(board.openings(), 2);
+        assert_eq!(board.islands(), 1);
+        assert_eq!(board.bbbv(), 3);
+    }
+
+    #[test]
+    fn test_bbbv_counts_isolated_numbers() {
+        // * 2 *
+        // 1 3 2
+        // 0 1 *
+        let board = Board::from_mines(3, &[0, 2, 8]).unwrap();
+
+        assert_eq!(board.openings(), 1);
+        assert_eq!(board.islands(), 1);
+        assert_eq!(board.bbbv(), 3);
+    }
+}
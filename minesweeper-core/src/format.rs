@@ -0,0 +1,246 @@
+//! Conversions between board layouts and the common minesweeper board file formats.
+//!
+//! Two formats are supported:
+//!
+//! * MBF, a binary format: one byte each for width and height, a big endian
+//!   16 bit mine count and then one `(column, row)` byte pair for every mine.
+//! * RAWVF, a line based text format made of `Key: value` headers followed by a
+//!   `Board:` section where `*` marks a mine and `0` a safe cell. Only the
+//!   headers needed to rebuild the layout are read; any `Events:` section is
+//!   ignored.
+//!
+//! Boards in this game are always square so layouts with differing width and
+//! height are rejected on import.
+
+use super::{Board, CellType};
+
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FormatError {
+    /// Input ended before the whole layout could be read
+    UnexpectedEnd,
+    /// Width and height of the layout differ
+    NotSquare,
+    /// The board is too large to be represented in the requested format
+    TooLarge,
+    /// A mine lies outside the board
+    MineOutOfBound,
+    /// The declared mine count does not match the layout
+    MineCountMismatch,
+    /// The input is not in the expected format
+    Malformed,
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FormatError::UnexpectedEnd => "board file is truncated",
+            FormatError::NotSquare => "only square boards are supported",
+            FormatError::TooLarge => "board is too large for the format",
+            FormatError::MineOutOfBound => "mine out of bounds",
+            FormatError::MineCountMismatch => "mine count does not match the board",
+            FormatError::Malformed => "malformed board file",
+        })
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+const RAWVF_VERSION: &str = "Rev5";
+
+impl Board {
+    pub fn to_mbf(self: &Board) -> Result<Vec<u8>, FormatError> {
+        let size = u8::try_from(self.size()).map_err(|_| FormatError::TooLarge)?;
+        let mines = self.mine_positions();
+        let mines_count = u16::try_from(mines.len()).map_err(|_| FormatError::TooLarge)?;
+
+        let mut bytes = Vec::with_capacity(4 + mines.len() * 2);
+        bytes.push(size);
+        bytes.push(size);
+        bytes.extend_from_slice(&mines_count.to_be_bytes());
+
+        for index in mines {
+            let (row, column) = (index / self.size(), index % self.size());
+            bytes.push(column as u8);
+            bytes.push(row as u8);
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn from_mbf(bytes: &[u8]) -> Result<Board, FormatError> {
+        let [width, height, count_high, count_low, mines @ ..] = bytes else {
+            return Err(FormatError::UnexpectedEnd);
+        };
+
+        if width != height {
+            return Err(FormatError::NotSquare);
+        }
+
+        let size = *width as usize;
+        let mines_count = u16::from_be_bytes([*count_high, *count_low]) as usize;
+
+        if mines.len() < mines_count * 2 {
+            return Err(FormatError::UnexpectedEnd);
+        }
+
+        let positions = mines[..mines_count * 2]
+            .chunks_exact(2)
+            .map(|pair| {
+                let (column, row) = (pair[0] as usize, pair[1] as usize);
+
+                if column >= size || row >= size {
+                    Err(FormatError::MineOutOfBound)
+                } else {
+                    Ok(row * size + column)
+                }
+            })
+            .collect::<Result<Vec<usize>, FormatError>>()?;
+
+        Board::from_mines(size, &positions).map_err(|_| FormatError::MineOutOfBound)
+    }
+
+    pub fn to_rawvf(self: &Board) -> String {
+        let mut out = format!(
+            "RawVF_Version: {}\nWidth: {}\nHeight: {}\nMines: {}\nBoard:\n",
+            RAWVF_VERSION,
+            self.size(),
+            self.size(),
+            self.mines_count()
+        );
+
+        for row in self
+            .iter_cells()
+            .collect::<Vec<_>>()
+            .chunks(self.size().max(1))
+        {
+            for cell in row {
+                out.push(if cell.cell_type == CellType::Mine {
+                    '*'
+                } else {
+                    '0'
+                });
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+
+    pub fn from_rawvf(text: &str) -> Result<Board, FormatError> {
+        let mut width: Option<usize> = None;
+        let mut height: Option<usize> = None;
+        let mut declared_mines: Option<usize> = None;
+
+        let mut lines = text.lines();
+
+        for line in lines.by_ref() {
+            let line = line.trim();
+
+            if line == "Board:" {
+                break;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+
+            let parse = |value: &str| {
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| FormatError::Malformed)
+            };
+
+            match key.trim() {
+                "Width" => width = Some(parse(value)?),
+                "Height" => height = Some(parse(value)?),
+                "Mines" => declared_mines = Some(parse(value)?),
+                _ => {}
+            }
+        }
+
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            _ => return Err(FormatError::Malformed),
+        };
+
+        if width != height {
+            return Err(FormatError::NotSquare);
+        }
+
+        let mut mines = Vec::new();
+
+        for row in 0..height {
+            let line = lines.next().ok_or(FormatError::UnexpectedEnd)?.trim();
+
+            if line.chars().count() != width {
+                return Err(FormatError::Malformed);
+            }
+
+            for (column, char) in line.chars().enumerate() {
+                match char {
+                    '*' => mines.push(row * width + column),
+                    '0' => {}
+                    _ => return Err(FormatError::Malformed),
+                }
+            }
+        }
+
+        if declared_mines.is_some_and(|count| count != mines.len()) {
+            return Err(FormatError::MineCountMismatch);
+        }
+
+        Board::from_mines(width, &mines).map_err(|_| FormatError::MineOutOfBound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mbf_round_trip() {
+        let board = Board::from_mines(4, &[0, 5, 15]).unwrap();
+
+        let bytes = board.to_mbf().unwrap();
+        assert_eq!(bytes, vec![4, 4, 0, 3, 0, 0, 1, 1, 3, 3]);
+
+        let imported = Board::from_mbf(&bytes).unwrap();
+        assert_eq!(imported.mine_positions(), vec![0, 5, 15]);
+    }
+
+    #[test]
+    fn test_mbf_rejects_truncated() {
+        assert_eq!(
+            Board::from_mbf(&[4, 4, 0, 2, 0, 0]).unwrap_err(),
+            FormatError::UnexpectedEnd
+        );
+        assert_eq!(
+            Board::from_mbf(&[4, 3, 0, 0]).unwrap_err(),
+            FormatError::NotSquare
+        );
+    }
+
+    #[test]
+    fn test_rawvf_round_trip() {
+        let board = Board::from_mines(3, &[1, 8]).unwrap();
+
+        let text = board.to_rawvf();
+        assert!(text.ends_with("Board:\n0*0\n000\n00*\n"));
+
+        let imported = Board::from_rawvf(&text).unwrap();
+        assert_eq!(imported.size(), 3);
+        assert_eq!(imported.mine_positions(), vec![1, 8]);
+    }
+
+    #[test]
+    fn test_rawvf_mine_count_mismatch() {
+        let text = "Width: 2\nHeight: 2\nMines: 3\nBoard:\n*0\n00\n";
+
+        assert_eq!(
+            Board::from_rawvf(text).unwrap_err(),
+            FormatError::MineCountMismatch
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! The rules of minesweeper as played on the server, free of any server.
+//!
+//! The [`Board`] and everything about it, the moves players make and the
+//! deductions the solver finds live at the root. [`protocol`] holds the
+//! messages the server and its clients exchange, so a client built on this
+//! crate speaks the same protocol as the server it talks to.
+//!
+//! The crate follows semantic versioning but has not reached 1.0, so any minor
+//! release may break the Rust API while patch releases do not. Most enums are
+//! exhaustive and most structs have public fields, so adding a variant or a
+//! field is one such break. The public API is what is exported here and from
+//! [`protocol`]; the other modules are private so they can be reorganised
+//! freely. Changing a type that goes over the wire in a way older peers cannot
+//! read is a breaking change too. Only the errors are `#[non_exhaustive]`, so
+//! new reasons for refusing a move or a file do not break callers.
+
+mod board;
+mod format;
+mod play;
+mod probability;
+pub mod protocol;
+mod solo;
+mod solver;
+mod view;
+
+pub use board::{Board, Cell, CellState, CellType, Error, ExternalCell};
+pub use format::FormatError;
+pub use play::{Guess, Hint, PlayerAction};
+pub use solo::{PersonalBests, SoloGame};
+pub use solver::Deduction;
+pub use view::{OpponentCell, OpponentView, PlayerView, RevealView, RevealedCell, SpectatorView};
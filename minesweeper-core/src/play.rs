@@ -0,0 +1,103 @@
+//! Moves a player can make and the rules for applying them, shared by games
+//! on the server and games played locally.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Board, Deduction, Error};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action")]
+pub enum PlayerAction {
+    #[serde(rename = "flag")]
+    Flag { index: usize },
+    #[serde(rename = "reveal")]
+    Reveal { index: usize },
+    /// Reveals the neighbours of a revealed number whose mines are all flagged
+    #[serde(rename = "chord")]
+    Chord { index: usize },
+    /// Asks for a cell that can be deduced from the revealed numbers. With
+    /// `allow_guess` the least risky cell is offered when nothing can be deduced.
+    #[serde(rename = "hint")]
+    Hint {
+        #[serde(default)]
+        allow_guess: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Hint {
+    Safe {
+        index: usize,
+    },
+    Mine {
+        index: usize,
+    },
+    /// Nothing can be deduced; `guess` is the least risky cell when one was asked for
+    NoDeduction {
+        guess: Option<Guess>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Guess {
+    pub index: usize,
+    /// Probability of the cell holding a mine given what the player can see
+    pub probability: f64,
+}
+
+impl Hint {
+    /// Whether the hint told the player anything, as only those count
+    /// against their hints
+    pub fn is_useful(&self) -> bool {
+        *self != Hint::NoDeduction { guess: None }
+    }
+}
+
+impl Board {
+    /// Applies a move to the board. Hint requests leave it unchanged, their
+    /// answer comes from [`Board::hint`].
+    pub fn play(self: &mut Board, action: &PlayerAction) -> Result<(), Error> {
+        match *action {
+            PlayerAction::Flag { index } => self.toggle_flag(index),
+            PlayerAction::Reveal { index } => self.reveal(index),
+            PlayerAction::Chord { index } => self.chord(index),
+            PlayerAction::Hint { .. } => Ok(()),
+        }
+    }
+
+    /// Answers a hint request from what the player can see
+    pub fn hint(self: &Board, allow_guess: bool) -> Hint {
+        match self.find_deduction() {
+            Some(Deduction::Safe { index }) => Hint::Safe { index },
+            Some(Deduction::Mine { index }) => Hint::Mine { index },
+            None => Hint::NoDeduction {
+                guess: allow_guess
+                    .then(|| self.lowest_risk_cell())
+                    .flatten()
+                    .map(|(index, probability)| Guess { index, probability }),
+            },
+        }
+    }
+
+    /// Answers a hint request from a player who has taken `hints_used` of the
+    /// `hint_limit` hints allowed, counting the answer against them when it is
+    /// useful. Being told that nothing can be deduced is free.
+    pub fn take_hint(
+        self: &Board,
+        allow_guess: bool,
+        hint_limit: usize,
+        hints_used: &mut usize,
+    ) -> Result<Hint, Error> {
+        if *hints_used >= hint_limit {
+            return Err(Error::HintLimitReached);
+        }
+
+        let hint = self.hint(allow_guess);
+        if hint.is_useful() {
+            *hints_used += 1;
+        }
+
+        Ok(hint)
+    }
+}
@@ -0,0 +1,455 @@
+//! Exact mine probabilities for the cells a player cannot see.
+//!
+//! Unknown cells next to a revealed number form the frontier. Every way of
+//! placing mines on the frontier that agrees with the numbers is enumerated,
+//! weighted by the number of ways the remaining mines can be spread over the
+//! cells away from the frontier. Frontier cells that share no number are split
+//! into independent components which are enumerated separately and combined
+//! by their mine counts, keeping the search small on large boards.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{Board, ExternalCell};
+
+/// Most partial assignments tried across every component before giving up.
+/// The search is exponential in the worst case and runs on the server for
+/// every reveal, so positions that need more are left unknown rather than
+/// stalling the game handler.
+const SEARCH_BUDGET: usize = 200_000;
+
+/// Assignments of one frontier component grouped by the number of mines used
+#[derive(Default)]
+struct ComponentSolutions {
+    /// Number of valid assignments for every mine count
+    counts: HashMap<usize, f64>,
+    /// For every mine count, how many of those assignments put a mine on each cell
+    cell_counts: HashMap<usize, Vec<f64>>,
+}
+
+struct Component {
+    cells: Vec<usize>,
+    /// Numbers touching the component as (mines still needed, positions in `cells`)
+    constraints: Vec<(usize, Vec<usize>)>,
+}
+
+/// Where the enumeration of a component has got to
+struct Search<'a> {
+    component: &'a Component,
+    /// Constraints each cell takes part in, so that a partial assignment can
+    /// be checked without scanning everything
+    cell_constraints: Vec<Vec<usize>>,
+    assignment: Vec<bool>,
+    /// Mines placed so far on the cells of every constraint
+    placed: Vec<usize>,
+    /// Cells of every constraint not assigned yet
+    unassigned: Vec<usize>,
+    solutions: ComponentSolutions,
+    budget: &'a mut usize,
+}
+
+impl Component {
+    /// Enumerates the component, or gives up once `budget` runs out
+    fn solve(&self, budget: &mut usize) -> Option<ComponentSolutions> {
+        let mut cell_constraints = vec![Vec::new(); self.cells.len()];
+        for (constraint_index, (_, cells)) in self.constraints.iter().enumerate() {
+            for &cell in cells {
+                cell_constraints[cell].push(constraint_index);
+            }
+        }
+
+        let mut search = Search {
+            component: self,
+            cell_constraints,
+            assignment: vec![false; self.cells.len()],
+            placed: vec![0; self.constraints.len()],
+            unassigned: self
+                .constraints
+                .iter()
+                .map(|(_, cells)| cells.len())
+                .collect(),
+            solutions: ComponentSolutions::default(),
+            budget,
+        };
+
+        search.search(0).then_some(search.solutions)
+    }
+}
+
+impl Search<'_> {
+    /// Tries both values for the cell at `position` and everything after it,
+    /// returning false if the budget ran out on the way
+    fn search(&mut self, position: usize) -> bool {
+        let Some(remaining) = self.budget.checked_sub(1) else {
+            return false;
+        };
+        *self.budget = remaining;
+
+        if position == self.component.cells.len() {
+            let mines = self.assignment.iter().filter(|&&mine| mine).count();
+
+            *self.solutions.counts.entry(mines).or_default() += 1.0;
+
+            let cell_counts = self
+                .solutions
+                .cell_counts
+                .entry(mines)
+                .or_insert_with(|| vec![0.0; self.component.cells.len()]);
+
+            for (cell, &mine) in self.assignment.iter().enumerate() {
+                if mine {
+                    cell_counts[cell] += 1.0;
+                }
+            }
+
+            return true;
+        }
+
+        for mine in [false, true] {
+            self.assignment[position] = mine;
+
+            let mut consistent = true;
+
+            for &constraint in &self.cell_constraints[position] {
+                self.unassigned[constraint] -= 1;
+                self.placed[constraint] += mine as usize;
+
+                let needed = self.component.constraints[constraint].0;
+                if self.placed[constraint] > needed
+                    || self.placed[constraint] + self.unassigned[constraint] < needed
+                {
+                    consistent = false;
+                }
+            }
+
+            let finished = !consistent || self.search(position + 1);
+
+            for &constraint in &self.cell_constraints[position] {
+                self.unassigned[constraint] += 1;
+                self.placed[constraint] -= mine as usize;
+            }
+
+            if !finished {
+                return false;
+            }
+        }
+
+        self.assignment[position] = false;
+        true
+    }
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+
+    let k = k.min(n - k);
+    (0..k)
+        .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+        .sum()
+}
+
+/// Convolves mine count distributions, `a[i] * b[j]` contributing to `i + j`
+fn convolve(a: &HashMap<usize, f64>, b: &HashMap<usize, f64>) -> HashMap<usize, f64> {
+    let mut result = HashMap::new();
+
+    for (&i, &x) in a {
+        for (&j, &y) in b {
+            *result.entry(i + j).or_default() += x * y;
+        }
+    }
+
+    result
+}
+
+/// Union-find lookup of the representative cell of a component
+fn find_root(parent: &mut HashMap<usize, usize>, cell: usize) -> usize {
+    let next = *parent.entry(cell).or_insert(cell);
+    if next == cell {
+        return cell;
+    }
+
+    let root = find_root(parent, next);
+    parent.insert(cell, root);
+    root
+}
+
+fn neighbours(size: usize, index: usize) -> impl Iterator<Item = usize> {
+    let (row, column) = ((index / size) as isize, (index % size) as isize);
+
+    (-1..=1)
+        .flat_map(move |dr| (-1..=1).map(move |dc| (row + dr, column + dc)))
+        .filter(move |&(r, c)| {
+            (r, c) != (row, column) && r >= 0 && c >= 0 && r < size as isize && c < size as isize
+        })
+        .map(move |(r, c)| r as usize * size + c as usize)
+}
+
+/// Computes the probability of every cell holding a mine from the player
+/// visible `state` of a `size` by `size` board with `total_mines` mines.
+///
+/// Revealed cells get `None`. Flags are treated as unknown cells since they
+/// may be wrong. Returns `None` for every cell when the state is inconsistent
+/// or the frontier takes too long to enumerate.
+pub fn mine_probabilities(
+    size: usize,
+    state: &[ExternalCell],
+    total_mines: usize,
+) -> Vec<Option<f64>> {
+    mine_probabilities_within(size, state, total_mines, SEARCH_BUDGET)
+}
+
+fn mine_probabilities_within(
+    size: usize,
+    state: &[ExternalCell],
+    total_mines: usize,
+    mut budget: usize,
+) -> Vec<Option<f64>> {
+    let is_unknown = |index: usize| {
+        matches!(
+            state[index],
+            ExternalCell::Unrevealed | ExternalCell::Flagged
+        )
+    };
+
+    let visible_mines = state
+        .iter()
+        .filter(|cell| matches!(cell, ExternalCell::Mine))
+        .count();
+    let hidden_mines = total_mines.saturating_sub(visible_mines);
+
+    // Numbers with unknown neighbours, as (mines still needed, unknown neighbours)
+    let mut constraints: Vec<(usize, BTreeSet<usize>)> = Vec::new();
+
+    for (index, cell) in state.iter().enumerate() {
+        let ExternalCell::Number { value } = cell else {
+            continue;
+        };
+
+        let mut unknown = BTreeSet::new();
+        let mut known_mines = 0;
+
+        for neighbour in neighbours(size, index) {
+            if matches!(state[neighbour], ExternalCell::Mine) {
+                known_mines += 1;
+            } else if is_unknown(neighbour) {
+                unknown.insert(neighbour);
+            }
+        }
+
+        if !unknown.is_empty() {
+            let Some(needed) = (*value as usize).checked_sub(known_mines) else {
+                return vec![None; state.len()];
+            };
+            constraints.push((needed, unknown));
+        }
+    }
+
+    // Group frontier cells into components of cells linked through a shared number
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+
+    for (_, cells) in &constraints {
+        let first = *cells.first().expect("constraints have unknown cells");
+
+        for &cell in cells {
+            let (a, b) = (find_root(&mut parent, first), find_root(&mut parent, cell));
+            parent.insert(a, b);
+        }
+    }
+
+    let mut component_of: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<BTreeSet<usize>> = Vec::new();
+    let mut roots: HashMap<usize, usize> = HashMap::new();
+
+    for cell in parent.keys().copied().collect::<Vec<usize>>() {
+        let root = find_root(&mut parent, cell);
+        let component = *roots.entry(root).or_insert_with(|| {
+            components.push(BTreeSet::new());
+            components.len() - 1
+        });
+
+        components[component].insert(cell);
+        component_of.insert(cell, component);
+    }
+
+    let components = components
+        .into_iter()
+        .map(|cells| {
+            let cells = cells.into_iter().collect::<Vec<usize>>();
+            let position = |cell: &usize| cells.binary_search(cell).ok();
+
+            let constraints = constraints
+                .iter()
+                .filter(|(_, unknown)| unknown.iter().any(|cell| position(cell).is_some()))
+                .map(|(needed, unknown)| (*needed, unknown.iter().filter_map(position).collect()))
+                .collect();
+
+            Component { cells, constraints }
+        })
+        .collect::<Vec<Component>>();
+
+    let Some(solutions) = components
+        .iter()
+        .map(|component| component.solve(&mut budget))
+        .collect::<Option<Vec<ComponentSolutions>>>()
+    else {
+        return vec![None; state.len()];
+    };
+
+    let outside = (0..state.len())
+        .filter(|&index| is_unknown(index) && !component_of.contains_key(&index))
+        .count();
+
+    // Scale weights relative to the largest one to stay within f64 range
+    let ln_weights = (0..=hidden_mines)
+        .map(|frontier_mines| ln_binomial(outside, hidden_mines - frontier_mines))
+        .collect::<Vec<f64>>();
+    let max_ln_weight = ln_weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weight = |frontier_mines: usize| {
+        ln_weights
+            .get(frontier_mines)
+            .map_or(0.0, |ln| (ln - max_ln_weight).exp())
+    };
+
+    let all = solutions
+        .iter()
+        .fold(HashMap::from([(0, 1.0)]), |acc, solutions| {
+            convolve(&acc, &solutions.counts)
+        });
+
+    let total: f64 = all
+        .iter()
+        .map(|(&mines, &count)| count * weight(mines))
+        .sum();
+
+    if total <= 0.0 || !total.is_finite() {
+        return vec![None; state.len()];
+    }
+
+    let mut probabilities = vec![None; state.len()];
+
+    for (index, component) in components.iter().enumerate() {
+        let others = solutions
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .fold(HashMap::from([(0, 1.0)]), |acc, (_, solutions)| {
+                convolve(&acc, &solutions.counts)
+            });
+
+        for (position, &cell) in component.cells.iter().enumerate() {
+            let mut mine_weight = 0.0;
+
+            for (&mines, cell_counts) in &solutions[index].cell_counts {
+                for (&other_mines, &count) in &others {
+                    mine_weight += cell_counts[position] * count * weight(mines + other_mines);
+                }
+            }
+
+            probabilities[cell] = Some(mine_weight / total);
+        }
+    }
+
+    if outside > 0 {
+        let outside_mines: f64 = all
+            .iter()
+            .filter(|&(&mines, _)| mines <= hidden_mines)
+            .map(|(&mines, &count)| count * weight(mines) * (hidden_mines - mines) as f64)
+            .sum();
+
+        let probability = outside_mines / total / outside as f64;
+
+        for (index, slot) in probabilities.iter_mut().enumerate() {
+            if is_unknown(index) && !component_of.contains_key(&index) {
+                *slot = Some(probability);
+            }
+        }
+    }
+
+    probabilities
+}
+
+impl Board {
+    /// Probability of each cell holding a mine as far as the player can tell.
+    /// See [`mine_probabilities`].
+    pub fn mine_probabilities(self: &Board) -> Vec<Option<f64>> {
+        mine_probabilities(self.size(), &self.get_external_state(), self.mines_count())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("cell has a probability");
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {} got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_single_constraint() {
+        let mut board = Board::from_mines(2, &[1]).unwrap();
+        board.reveal(0).unwrap();
+
+        let probabilities = board.mine_probabilities();
+
+        assert_eq!(probabilities[0], None);
+        for probability in &probabilities[1..] {
+            assert_close(*probability, 1.0 / 3.0);
+        }
+    }
+
+    #[test]
+    fn test_weights_by_remaining_mines() {
+        // Revealed 1 in the corner of a 3x3 board with two mines: the mine next
+        // to the 1 is one of three cells, the other one of the five far cells
+        let mut board = Board::from_mines(3, &[1, 8]).unwrap();
+        board.reveal(0).unwrap();
+
+        let probabilities = board.mine_probabilities();
+
+        for index in [1, 3, 4] {
+            assert_close(probabilities[index], 1.0 / 3.0);
+        }
+        for index in [2, 5, 6, 7, 8] {
+            assert_close(probabilities[index], 1.0 / 5.0);
+        }
+    }
+
+    #[test]
+    fn test_gives_up_when_budget_runs_out() {
+        let mut board = Board::from_mines(3, &[1, 8]).unwrap();
+        board.reveal(0).unwrap();
+        let state = board.get_external_state();
+
+        assert!(mine_probabilities_within(3, &state, 2, 4)
+            .iter()
+            .all(Option::is_none));
+        assert!(mine_probabilities_within(3, &state, 2, SEARCH_BUDGET)
+            .iter()
+            .any(Option::is_some));
+    }
+
+    #[test]
+    fn test_certain_cells() {
+        // 1 * .
+        // 1 1 .
+        // . . .
+        let mut board = Board::from_mines(3, &[1]).unwrap();
+        board.reveal(0).unwrap();
+        board.reveal(3).unwrap();
+        board.reveal(4).unwrap();
+
+        let probabilities = board.mine_probabilities();
+
+        assert_close(probabilities[1], 1.0);
+        for index in [2, 5, 6, 7, 8] {
+            assert_close(probabilities[index], 0.0);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Clock, Visibility};
+use crate::PlayerAction;
+
+/// Anything a player can send over the socket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ClientMessage {
+    Move(PlayerAction),
+    Game(GameAction),
+    Host(HostAction),
+}
+
+/// Requests about the game as a whole rather than the player's board
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GameAction {
+    Rematch {
+        #[serde(default)]
+        same_board: bool,
+    },
+}
+
+/// Commands only the host of a game may give
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HostAction {
+    Kick {
+        player_name: String,
+    },
+    Lock,
+    Unlock,
+    /// Changes the settings of a game that has not started yet, leaving out
+    /// any that are not given
+    Configure {
+        board_size: Option<usize>,
+        player_limit: Option<usize>,
+        hint_limit: Option<usize>,
+        hint_penalty_secs: Option<u64>,
+        clock: Option<Clock>,
+        visibility: Option<Visibility>,
+    },
+    Start,
+    TransferHost {
+        player_name: String,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_client_messages_are_told_apart() {
+        let parse = |json: &str| serde_json::from_str::<ClientMessage>(json).unwrap();
+
+        assert_eq!(
+            parse(r#"{"action":"reveal","index":3}"#),
+            ClientMessage::Move(PlayerAction::Reveal { index: 3 })
+        );
+        assert_eq!(
+            parse(r#"{"action":"rematch"}"#),
+            ClientMessage::Game(GameAction::Rematch { same_board: false })
+        );
+        assert_eq!(
+            parse(r#"{"action":"start"}"#),
+            ClientMessage::Host(HostAction::Start)
+        );
+    }
+}
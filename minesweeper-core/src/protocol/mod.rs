@@ -0,0 +1,22 @@
+//! Messages exchanged between the server and its clients, along with the
+//! settings they carry.
+//!
+//! Clients send a [`ClientMessage`] over their socket and receive a
+//! [`GameStateUpdate`] after every change to the game. The HTTP endpoints that
+//! create and join games take and return the types in this module too.
+
+mod message;
+mod request;
+mod settings;
+mod state;
+
+pub use message::{ClientMessage, GameAction, HostAction};
+pub use request::{CreateGameRequest, JoinGameQuery, NewGameResponse};
+pub use settings::{
+    default_hint_limit, default_hint_penalty_secs, Clock, TournamentFormat, Visibility,
+    DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+pub use state::{
+    GameStateUpdate, GameStats, MatchState, OpponentState, RematchState, Standing,
+    TournamentStanding, TournamentState,
+};
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use super::settings::{default_hint_limit, default_hint_penalty_secs};
+use super::{Clock, Visibility};
+
+/// Body of `create-game`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CreateGameRequest {
+    pub board_size: usize,
+    pub player_limit: usize,
+    #[serde(default = "default_hint_limit")]
+    pub hint_limit: usize,
+    #[serde(default = "default_hint_penalty_secs")]
+    pub hint_penalty_secs: u64,
+    #[serde(default)]
+    pub clock: Clock,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Password players have to give to join
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Hold the game in a lobby until the host starts it
+    #[serde(default)]
+    pub lobby: bool,
+}
+
+impl CreateGameRequest {
+    /// A private game with the default hints and no clock
+    pub fn new(board_size: usize, player_limit: usize) -> CreateGameRequest {
+        CreateGameRequest {
+            board_size,
+            player_limit,
+            hint_limit: default_hint_limit(),
+            hint_penalty_secs: default_hint_penalty_secs(),
+            clock: Clock::default(),
+            visibility: Visibility::default(),
+            password: None,
+            lobby: false,
+        }
+    }
+
+    pub fn with_password(mut self, password: String) -> CreateGameRequest {
+        self.password = Some(password);
+        self
+    }
+
+    /// Nobody can move until the host sends `HostAction::Start`
+    pub fn with_lobby(mut self) -> CreateGameRequest {
+        self.lobby = true;
+        self
+    }
+}
+
+/// Response of `create-game`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewGameResponse {
+    pub code: String,
+    /// Passed to `join-game` by the creator to become the host
+    pub host_token: String,
+}
+
+/// Query string of `join-game`, which upgrades to the game's socket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JoinGameQuery {
+    pub code: String,
+    pub player_name: String,
+    /// Token returned by `create-game`, for the host to join with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_token: Option<String>,
+    /// Required for games created with a password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Token from `identity`, to record results against the player's identity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_token: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_create_game_defaults() {
+        let request: CreateGameRequest =
+            serde_json::from_str(r#"{"board_size":16,"player_limit":2}"#).unwrap();
+
+        assert_eq!(request, CreateGameRequest::new(16, 2));
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Hints each player may ask for, unless the game says otherwise
+pub const DEFAULT_HINT_LIMIT: usize = 3;
+/// Seconds added to a player's time for every hint, unless the game says otherwise
+pub const DEFAULT_HINT_PENALTY_SECS: u64 = 10;
+
+/// Serde default for a missing hint limit
+pub fn default_hint_limit() -> usize {
+    DEFAULT_HINT_LIMIT
+}
+
+/// Serde default for a missing hint penalty
+pub fn default_hint_penalty_secs() -> u64 {
+    DEFAULT_HINT_PENALTY_SECS
+}
+
+/// How long players have to clear their board
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Clock {
+    #[default]
+    Unlimited,
+    /// Every player gets `limit_secs` from the moment they join
+    PerPlayer { limit_secs: u64 },
+    /// Everyone races against a single deadline, `limit_secs` after the first
+    /// player joins
+    Blitz { limit_secs: u64 },
+}
+
+/// Whether a game is listed for anyone to join or only reachable by its code
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Public,
+    #[default]
+    Private,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TournamentFormat {
+    /// Every entrant plays each round on the same board. The first to win a
+    /// majority of `best_of` rounds takes the series.
+    Series { best_of: usize },
+    /// Single elimination in head to head matches, with byes for odd numbers
+    Bracket,
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::TournamentFormat;
+use crate::{Hint, OpponentView, PlayerView, RevealView};
+
+/// Everything a player is shown, sent over their socket after every change
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameStateUpdate {
+    pub board_state: PlayerView,
+    pub board_size: usize,
+    /// Full layout of the board, only sent once the player's game has ended
+    pub revealed_board: Option<RevealView>,
+    pub opponents: Vec<OpponentState>,
+    pub start_time: Option<u64>,
+    pub finished_time: Option<u64>,
+    pub stats: Option<GameStats>,
+    pub hint: Option<Hint>,
+    pub hints_remaining: usize,
+    /// When the player's clock runs out, in seconds since the epoch
+    pub deadline: Option<u64>,
+    pub standings: Vec<Standing>,
+    pub rematch: Option<RematchState>,
+    /// Progress of the tournament this game is a match of
+    pub tournament: Option<TournamentState>,
+    /// Name of the player who controls the game, if anyone does
+    pub host: Option<String>,
+    pub is_host: bool,
+    pub locked: bool,
+    /// Whether the host has started the game, always true for games without one
+    pub started: bool,
+}
+
+/// Progress of a proposed rematch
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RematchState {
+    pub same_board: bool,
+    /// Names of the players who agreed so far
+    pub accepted: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OpponentState {
+    pub name: String,
+    /// Rating at the difficulty of the game, for players with an identity
+    pub rating: Option<f64>,
+    pub board: OpponentView,
+}
+
+/// A player's position in a game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    /// Safe cells revealed so far
+    pub revealed: usize,
+    pub won: bool,
+    pub finished: bool,
+    /// Time taken, once the player has finished
+    pub time_ms: Option<u64>,
+}
+
+/// Performance metrics of a finished board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameStats {
+    pub bbbv: usize,
+    pub openings: usize,
+    pub islands: usize,
+    pub clicks: usize,
+    /// 3BV divided by the number of clicks; above 1 only when chording
+    pub efficiency: f64,
+    pub bbbv_per_second: f64,
+}
+
+impl GameStats {
+    pub fn new(
+        bbbv: usize,
+        openings: usize,
+        islands: usize,
+        clicks: usize,
+        duration: Duration,
+    ) -> GameStats {
+        GameStats {
+            bbbv,
+            openings,
+            islands,
+            clicks,
+            efficiency: if clicks == 0 {
+                0.0
+            } else {
+                bbbv as f64 / clicks as f64
+            },
+            bbbv_per_second: if duration.is_zero() {
+                0.0
+            } else {
+                bbbv as f64 / duration.as_secs_f64()
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TournamentState {
+    pub format: TournamentFormat,
+    pub round: usize,
+    pub champion: Option<String>,
+    pub standings: Vec<TournamentStanding>,
+    pub matches: Vec<MatchState>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TournamentStanding {
+    pub rank: usize,
+    pub name: String,
+    pub points: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MatchState {
+    pub round: usize,
+    /// Code of the game to join, missing for a bye
+    pub game_code: Option<String>,
+    pub players: Vec<String>,
+    pub winner: Option<String>,
+}
@@ -0,0 +1,165 @@
+//! Single-player games driven entirely by the caller, without a server.
+//!
+//! Moves follow the same rules as games on the server. Nothing here reads the
+//! clock, so callers time games themselves, which keeps it usable where there
+//! is no clock to read such as in WebAssembly.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Board, Error, Hint, PlayerAction};
+
+#[derive(Debug, Clone)]
+pub struct SoloGame {
+    board: Board,
+    hint_limit: usize,
+    hint_penalty_secs: u64,
+    hints_used: usize,
+    /// Answer to the latest hint request, cleared by the next move
+    last_hint: Option<Hint>,
+    /// Reveals, flags and chords made so far
+    clicks: usize,
+}
+
+impl SoloGame {
+    pub fn new(board: Board, hint_limit: usize, hint_penalty_secs: u64) -> SoloGame {
+        SoloGame {
+            board,
+            hint_limit,
+            hint_penalty_secs,
+            hints_used: 0,
+            last_hint: None,
+            clicks: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn hints_remaining(&self) -> usize {
+        self.hint_limit.saturating_sub(self.hints_used)
+    }
+
+    pub fn last_hint(&self) -> Option<&Hint> {
+        self.last_hint.as_ref()
+    }
+
+    pub fn clicks(&self) -> usize {
+        self.clicks
+    }
+
+    /// Time added to the player's for the hints they took
+    pub fn penalty(&self) -> Duration {
+        Duration::from_secs(
+            self.hint_penalty_secs
+                .saturating_mul(self.hints_used as u64),
+        )
+    }
+
+    /// Makes a move, refusing any once the board is finished
+    pub fn play(&mut self, action: &PlayerAction) -> Result<(), Error> {
+        if self.board.is_finished() {
+            return Err(Error::InvalidMove);
+        }
+
+        match *action {
+            PlayerAction::Hint { allow_guess } => {
+                let hint =
+                    self.board
+                        .take_hint(allow_guess, self.hint_limit, &mut self.hints_used)?;
+                self.last_hint = Some(hint);
+            }
+            _ => {
+                self.board.play(action)?;
+                self.clicks += 1;
+                self.last_hint = None;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fastest clear of each board size, in milliseconds
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct PersonalBests {
+    best_times: BTreeMap<usize, u64>,
+}
+
+impl PersonalBests {
+    pub fn get(&self, board_size: usize) -> Option<u64> {
+        self.best_times.get(&board_size).copied()
+    }
+
+    /// Records a clear, returning whether it beat the previous best
+    pub fn record(&mut self, board_size: usize, time_ms: u64) -> bool {
+        match self.best_times.get(&board_size) {
+            Some(&best) if best <= time_ms => false,
+            _ => {
+                self.best_times.insert(board_size, time_ms);
+                true
+            }
+        }
+    }
+
+    /// Board sizes and their best times, smallest board first
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.best_times
+            .iter()
+            .map(|(&board_size, &time_ms)| (board_size, time_ms))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solo_game() {
+        // * 2 *
+        // 1 2 1
+        // . . .
+        let mut game = SoloGame::new(Board::from_mines(3, &[0, 2]).unwrap(), 1, 10);
+
+        game.play(&PlayerAction::Reveal { index: 6 }).unwrap();
+        game.play(&PlayerAction::Hint { allow_guess: false })
+            .unwrap();
+        assert_eq!(game.last_hint(), Some(&Hint::Safe { index: 1 }));
+        assert!(matches!(
+            game.play(&PlayerAction::Hint { allow_guess: false }),
+            Err(Error::HintLimitReached)
+        ));
+
+        game.play(&PlayerAction::Reveal { index: 1 }).unwrap();
+        assert_eq!(game.last_hint(), None);
+        assert!(game.board().is_cleared());
+        assert_eq!(game.clicks(), 2);
+        assert_eq!(game.penalty(), Duration::from_secs(10));
+
+        assert!(game.play(&PlayerAction::Flag { index: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_personal_bests() {
+        let mut bests = PersonalBests::default();
+
+        assert!(bests.record(16, 30_000));
+        assert!(!bests.record(16, 31_000));
+        assert!(bests.record(16, 29_000));
+        assert!(bests.record(8, 5_000));
+
+        assert_eq!(bests.get(16), Some(29_000));
+        assert_eq!(
+            serde_json::to_string(&bests).unwrap(),
+            r#"{"8":5000,"16":29000}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<PersonalBests>(r#"{"8":5000,"16":29000}"#).unwrap(),
+            bests
+        );
+    }
+}
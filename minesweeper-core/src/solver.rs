@@ -0,0 +1,220 @@
+//! Deductions on a board made only from what the player can see.
+//!
+//! The solver never looks at hidden cell types: it works on the output of
+//! [`Board::get_external_state`] so that anything it reports could have been
+//! worked out by the player. Flags are treated as unknown cells because the
+//! player may have placed them wrongly.
+
+use std::collections::{BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use super::{Board, ExternalCell};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Deduction {
+    /// The cell is certainly not a mine
+    Safe { index: usize },
+    /// The cell is certainly a mine
+    Mine { index: usize },
+}
+
+/// Mines left among a set of unknown cells around a revealed number
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    cells: BTreeSet<usize>,
+    mines: usize,
+}
+
+/// Player visible state of a board along with the deductions made so far
+struct Solver<'a> {
+    board: &'a Board,
+    state: Vec<ExternalCell>,
+    mines: HashSet<usize>,
+    safe: HashSet<usize>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(board: &'a Board) -> Solver<'a> {
+        Solver {
+            board,
+            state: board.get_external_state(),
+            mines: HashSet::new(),
+            safe: HashSet::new(),
+        }
+    }
+
+    fn is_unknown(&self, index: usize) -> bool {
+        matches!(
+            self.state[index],
+            ExternalCell::Unrevealed | ExternalCell::Flagged
+        ) && !self.mines.contains(&index)
+            && !self.safe.contains(&index)
+    }
+
+    fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+
+        for (index, cell) in self.state.iter().enumerate() {
+            let ExternalCell::Number { value } = cell else {
+                continue;
+            };
+
+            let mut cells = BTreeSet::new();
+            let mut known_mines = 0;
+
+            for neighbour in self.board.iter_surroundings(index).map(|cell| cell.index) {
+                if self.mines.contains(&neighbour)
+                    || matches!(self.state[neighbour], ExternalCell::Mine)
+                {
+                    known_mines += 1;
+                } else if self.is_unknown(neighbour) {
+                    cells.insert(neighbour);
+                }
+            }
+
+            if !cells.is_empty() {
+                constraints.push(Constraint {
+                    cells,
+                    mines: (*value as usize).saturating_sub(known_mines),
+                });
+            }
+        }
+
+        constraints
+    }
+
+    /// Applies the single cell and subset rules until no new cell can be decided
+    fn solve(&mut self) {
+        loop {
+            let constraints = self.constraints();
+            let mut progressed = false;
+
+            let mut decide = |cells: &mut dyn Iterator<Item = &usize>, mine: bool| {
+                for &cell in cells {
+                    let target = if mine {
+                        &mut self.mines
+                    } else {
+                        &mut self.safe
+                    };
+                    progressed |= target.insert(cell);
+                }
+            };
+
+            for constraint in &constraints {
+                if constraint.mines == 0 {
+                    decide(&mut constraint.cells.iter(), false);
+                } else if constraint.mines == constraint.cells.len() {
+                    decide(&mut constraint.cells.iter(), true);
+                }
+            }
+
+            for smaller in &constraints {
+                for larger in &constraints {
+                    if smaller == larger || !smaller.cells.is_subset(&larger.cells) {
+                        continue;
+                    }
+
+                    let Some(mines) = larger.mines.checked_sub(smaller.mines) else {
+                        continue;
+                    };
+
+                    let rest = larger.cells.difference(&smaller.cells).collect::<Vec<_>>();
+
+                    if mines == 0 {
+                        decide(&mut rest.into_iter(), false);
+                    } else if mines == rest.len() {
+                        decide(&mut rest.into_iter(), true);
+                    }
+                }
+            }
+
+            if !progressed {
+                break;
+            }
+        }
+    }
+}
+
+impl Board {
+    /// Finds a cell the player can prove to be safe or a mine from the revealed
+    /// numbers. Safe cells are preferred, and mines the player has already
+    /// flagged are not reported.
+    pub fn find_deduction(self: &Board) -> Option<Deduction> {
+        let mut solver = Solver::new(self);
+        solver.solve();
+
+        let safe = solver
+            .safe
+            .iter()
+            .filter(|&&index| matches!(solver.state[index], ExternalCell::Unrevealed))
+            .min()
+            .map(|&index| Deduction::Safe { index });
+
+        safe.or_else(|| {
+            solver
+                .mines
+                .iter()
+                .filter(|&&index| matches!(solver.state[index], ExternalCell::Unrevealed))
+                .min()
+                .map(|&index| Deduction::Mine { index })
+        })
+    }
+
+    /// The unrevealed cell least likely to hold a mine along with its probability
+    pub fn lowest_risk_cell(self: &Board) -> Option<(usize, f64)> {
+        let state = self.get_external_state();
+
+        self.mine_probabilities()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| matches!(state[*index], ExternalCell::Unrevealed))
+            .filter_map(|(index, probability)| probability.map(|probability| (index, probability)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_deduces_safe_cell() {
+        // 1 * .
+        // 1 1 .
+        // . . .
+        let mut board = Board::from_mines(3, &[1]).unwrap();
+        board.reveal(0).unwrap();
+        board.reveal(3).unwrap();
+        board.reveal(4).unwrap();
+
+        // 0 has a single unknown neighbour left, so 1 must be the mine and
+        // everything else around 4 is safe
+        assert_eq!(board.find_deduction(), Some(Deduction::Safe { index: 2 }));
+    }
+
+    #[test]
+    fn test_deduces_mine_when_no_safe_cell() {
+        // 1 *
+        // 1 1
+        let mut board = Board::from_mines(2, &[1]).unwrap();
+        board.reveal(0).unwrap();
+
+        assert_eq!(board.find_deduction(), None);
+
+        board.reveal(2).unwrap();
+        board.reveal(3).unwrap();
+
+        assert_eq!(board.find_deduction(), Some(Deduction::Mine { index: 1 }));
+    }
+
+    #[test]
+    fn test_lowest_risk_cell() {
+        let mut board = Board::from_mines(2, &[1]).unwrap();
+        board.reveal(0).unwrap();
+
+        let (_, probability) = board.lowest_risk_cell().unwrap();
+        assert!((probability - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
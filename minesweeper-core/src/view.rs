@@ -0,0 +1,283 @@
+//! Projections of a board for every audience that can be sent one.
+//!
+//! These are the only types that should ever leave the server describing a
+//! board. None of them can be built in a way that shows where an unrevealed
+//! mine is, except for [`RevealView`] which only exists once the game on that
+//! board has ended. Clients read them back with the same types.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Board, CellState, CellType, ExternalCell};
+
+/// A player's own board, exactly as they are allowed to see it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct PlayerView {
+    cells: Vec<ExternalCell>,
+}
+
+/// Another player's board as seen by their opponents: which cells are open,
+/// but neither the numbers nor the flags
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpponentView {
+    /// Left out while anyone may still be playing the same layout, as the
+    /// cells a player has yet to open once they are done are the mines
+    cells: Option<Vec<OpponentCell>>,
+    revealed: usize,
+    safe_cells: usize,
+    exploded: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpponentCell {
+    Revealed,
+    Unrevealed,
+}
+
+/// A player's board as shown to someone watching the game: what the player
+/// sees, apart from the mine they hit. Every player races on the same layout,
+/// so spectators are never shown where a mine is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SpectatorView {
+    cells: Vec<ExternalCell>,
+}
+
+/// A board whose game has ended, showing every mine and how the player's
+/// flags compare to them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct RevealView {
+    cells: Vec<RevealedCell>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum RevealedCell {
+    /// A safe cell the player revealed
+    Number { value: u8 },
+    /// A safe cell the player never revealed
+    Unrevealed,
+    /// The mine the player revealed, ending their game
+    Exploded,
+    /// A mine the player did not flag
+    Mine,
+    /// A mine the player flagged
+    CorrectFlag,
+    /// A flag the player placed on a safe cell
+    WrongFlag,
+}
+
+impl PlayerView {
+    pub fn cells(&self) -> &[ExternalCell] {
+        &self.cells
+    }
+}
+
+impl OpponentView {
+    pub fn cells(&self) -> Option<&[OpponentCell]> {
+        self.cells.as_deref()
+    }
+
+    /// Safe cells revealed so far
+    pub fn revealed(&self) -> usize {
+        self.revealed
+    }
+
+    /// Safe cells on the board, all of which are revealed once it is cleared
+    pub fn safe_cells(&self) -> usize {
+        self.safe_cells
+    }
+
+    pub fn exploded(&self) -> bool {
+        self.exploded
+    }
+}
+
+impl SpectatorView {
+    pub fn cells(&self) -> &[ExternalCell] {
+        &self.cells
+    }
+}
+
+impl RevealView {
+    pub fn cells(&self) -> &[RevealedCell] {
+        &self.cells
+    }
+}
+
+impl Board {
+    pub fn player_view(self: &Board) -> PlayerView {
+        PlayerView {
+            cells: self.get_external_state(),
+        }
+    }
+
+    pub fn opponent_view(self: &Board) -> OpponentView {
+        let cells = self
+            .iter_cells()
+            .map(|cell| match cell.state {
+                CellState::Revealed => OpponentCell::Revealed,
+                CellState::Unrevealed | CellState::Flagged => OpponentCell::Unrevealed,
+            })
+            .collect::<Vec<OpponentCell>>();
+
+        OpponentView {
+            cells: Some(cells),
+            ..self.opponent_progress()
+        }
+    }
+
+    /// How far along the player is, without which cells they opened
+    pub fn opponent_progress(self: &Board) -> OpponentView {
+        OpponentView {
+            cells: None,
+            revealed: self.revealed_count(),
+            safe_cells: self.size() * self.size() - self.mines_count(),
+            exploded: self.is_exploded(),
+        }
+    }
+
+    pub fn spectator_view(self: &Board) -> SpectatorView {
+        SpectatorView {
+            cells: self
+                .get_external_state()
+                .into_iter()
+                .map(|cell| match cell {
+                    ExternalCell::Mine => ExternalCell::Unrevealed,
+                    cell => cell,
+                })
+                .collect(),
+        }
+    }
+
+    /// The full layout, only available once no more moves can be made
+    pub fn reveal_view(self: &Board) -> Option<RevealView> {
+        if !self.is_finished() {
+            return None;
+        }
+
+        Some(RevealView {
+            cells: self
+                .iter_cells()
+                .map(|cell| match (&cell.cell_type, &cell.state) {
+                    (CellType::Mine, CellState::Revealed) => RevealedCell::Exploded,
+                    (CellType::Mine, CellState::Flagged) => RevealedCell::CorrectFlag,
+                    (CellType::Mine, CellState::Unrevealed) => RevealedCell::Mine,
+                    (CellType::Number, CellState::Revealed) => RevealedCell::Number {
+                        value: self.get_surrounding_mines_count(cell.index),
+                    },
+                    (CellType::Number, CellState::Flagged) => RevealedCell::WrongFlag,
+                    (CellType::Number, CellState::Unrevealed) => RevealedCell::Unrevealed,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    use super::*;
+
+    /// Plays random moves that do not end the game
+    fn random_unfinished_board() -> Board {
+        let mut rng = rand::thread_rng();
+        let mut board = Board::generate(rng.gen_range(3..12));
+
+        for _ in 0..rng.gen_range(0..8) {
+            let mut candidates = board
+                .iter_cells()
+                .filter(|cell| cell.state != CellState::Revealed)
+                .map(|cell| cell.index)
+                .collect::<Vec<usize>>();
+            candidates.shuffle(&mut rng);
+
+            let Some(&index) = candidates.first() else {
+                break;
+            };
+
+            let mut next = board.clone();
+            if rng.gen_bool(0.3) {
+                let _ = next.toggle_flag(index);
+            } else {
+                let _ = next.reveal(index);
+            }
+
+            if !next.is_finished() {
+                board = next;
+            }
+        }
+
+        board
+    }
+
+    #[test]
+    fn test_views_never_show_unrevealed_mines() {
+        for _ in 0..200 {
+            let board = random_unfinished_board();
+
+            let player = serde_json::to_value(board.player_view()).unwrap();
+            let spectator = serde_json::to_value(board.spectator_view()).unwrap();
+            let opponent = serde_json::to_value(board.opponent_view()).unwrap();
+
+            for cell in board.iter_cells() {
+                if cell.state == CellState::Revealed {
+                    continue;
+                }
+
+                let expected = if cell.state == CellState::Flagged {
+                    "flagged"
+                } else {
+                    "unrevealed"
+                };
+
+                assert_eq!(player[cell.index]["state"], expected);
+                assert_eq!(spectator[cell.index]["state"], expected);
+                assert_eq!(opponent["cells"][cell.index], "unrevealed");
+            }
+
+            assert!(board.reveal_view().is_none());
+        }
+    }
+
+    #[test]
+    fn test_reveal_view_after_loss() {
+        // * 2 *
+        // 1 2 1
+        // . . .
+        let mut board = Board::from_mines(3, &[0, 2]).unwrap();
+        board.reveal(1).unwrap();
+        board.toggle_flag(2).unwrap();
+        board.toggle_flag(5).unwrap();
+        board.reveal(0).unwrap();
+
+        assert_eq!(
+            board.reveal_view().unwrap().cells[..6],
+            [
+                RevealedCell::Exploded,
+                RevealedCell::Number { value: 2 },
+                RevealedCell::CorrectFlag,
+                RevealedCell::Unrevealed,
+                RevealedCell::Unrevealed,
+                RevealedCell::WrongFlag,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reveal_view_shows_unflagged_mines() {
+        let mut board = Board::from_mines(2, &[0]).unwrap();
+        for index in 1..4 {
+            board.reveal(index).unwrap();
+        }
+
+        let reveal = serde_json::to_value(board.reveal_view().unwrap()).unwrap();
+
+        assert_eq!(reveal[0]["state"], "mine");
+        assert_eq!(reveal[3]["value"], 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! What the client knows about the game and how the player's input changes it.
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+use minesweeper_core::protocol::{ClientMessage, GameAction, GameStateUpdate, HostAction};
+use minesweeper_core::{ExternalCell, PlayerAction};
+
+use crate::ui;
+
+pub enum Command {
+    Send(ClientMessage),
+    Quit,
+}
+
+pub struct App {
+    /// Shown above the board, e.g. the code other players join with
+    pub title: String,
+    /// Latest state of the game, missing until the server sends the first one
+    pub state: Option<GameStateUpdate>,
+    /// Row and column of the selected cell
+    pub cursor: (usize, usize),
+    /// Shown beside the board, e.g. the player's best time
+    pub notice: Option<String>,
+}
+
+impl App {
+    pub fn new(title: String) -> App {
+        App {
+            title,
+            state: None,
+            cursor: (0, 0),
+            notice: None,
+        }
+    }
+
+    pub fn update(&mut self, state: GameStateUpdate) {
+        let last = state.board_size.saturating_sub(1);
+        self.cursor = (self.cursor.0.min(last), self.cursor.1.min(last));
+        self.state = Some(state);
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Option<Command> {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => None,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Command> {
+        let index = self.selected_index();
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Command::Quit)
+            }
+            KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+
+            KeyCode::Char(' ') | KeyCode::Enter => self.open(index?),
+            KeyCode::Char('f') => send_move(PlayerAction::Flag { index: index? }),
+            KeyCode::Char('c') => send_move(PlayerAction::Chord { index: index? }),
+            KeyCode::Char('?') => send_move(PlayerAction::Hint { allow_guess: true }),
+
+            KeyCode::Char('s') => Some(Command::Send(ClientMessage::Host(HostAction::Start))),
+            KeyCode::Char('r') => Some(Command::Send(ClientMessage::Game(GameAction::Rematch {
+                same_board: false,
+            }))),
+            KeyCode::Char('R') => Some(Command::Send(ClientMessage::Game(GameAction::Rematch {
+                same_board: true,
+            }))),
+
+            _ => None,
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Option<Command> {
+        let size = self.state.as_ref()?.board_size;
+        let (row, column) = ui::cell_at(mouse.column, mouse.row, size)?;
+        let index = row * size + column;
+
+        match mouse.kind {
+            MouseEventKind::Down(button) => {
+                self.cursor = (row, column);
+
+                match button {
+                    MouseButton::Left => self.open(index),
+                    MouseButton::Right => send_move(PlayerAction::Flag { index }),
+                    MouseButton::Middle => send_move(PlayerAction::Chord { index }),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the cursor without leaving the board
+    fn move_cursor(&mut self, rows: isize, columns: isize) -> Option<Command> {
+        let last = self.state.as_ref()?.board_size.saturating_sub(1);
+        let (row, column) = self.cursor;
+
+        self.cursor = (
+            row.saturating_add_signed(rows).min(last),
+            column.saturating_add_signed(columns).min(last),
+        );
+
+        None
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let size = self.state.as_ref()?.board_size;
+
+        Some(self.cursor.0 * size + self.cursor.1)
+    }
+
+    fn open(&self, index: usize) -> Option<Command> {
+        let cells = self.state.as_ref()?.board_state.cells();
+
+        send_move(open_action(cells, index)?)
+    }
+}
+
+fn send_move(action: PlayerAction) -> Option<Command> {
+    Some(Command::Send(ClientMessage::Move(action)))
+}
+
+/// Reveals an unrevealed cell, or chords around a revealed number
+fn open_action(cells: &[ExternalCell], index: usize) -> Option<PlayerAction> {
+    match cells.get(index)? {
+        ExternalCell::Unrevealed => Some(PlayerAction::Reveal { index }),
+        ExternalCell::Number { value } if *value > 0 => Some(PlayerAction::Chord { index }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::KeyEventState;
+    use serde_json::json;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new("Game 1A2B".into());
+        app.update(
+            serde_json::from_value(json!({
+                "board_state": [
+                    { "state": "unrevealed" },
+                    { "state": "number", "value": 1 },
+                    { "state": "flagged" },
+                    { "state": "number", "value": 0 },
+                ],
+                "board_size": 2,
+                "opponents": [],
+                "hints_remaining": 0,
+                "standings": [],
+                "is_host": false,
+                "locked": false,
+                "started": true,
+            }))
+            .unwrap(),
+        );
+
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<ClientMessage> {
+        let event = Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+
+        match app.handle_event(event)? {
+            Command::Send(message) => Some(message),
+            Command::Quit => None,
+        }
+    }
+
+    #[test]
+    fn test_open_action() {
+        let cells = app().state.unwrap().board_state;
+        let cells = cells.cells();
+
+        assert_eq!(
+            open_action(cells, 0),
+            Some(PlayerAction::Reveal { index: 0 })
+        );
+        assert_eq!(
+            open_action(cells, 1),
+            Some(PlayerAction::Chord { index: 1 })
+        );
+        assert_eq!(open_action(cells, 2), None);
+        assert_eq!(open_action(cells, 3), None);
+        assert_eq!(open_action(cells, 4), None);
+    }
+
+    #[test]
+    fn test_cursor_stays_on_board() {
+        let mut app = app();
+
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.cursor, (0, 1));
+
+        assert_eq!(
+            press(&mut app, KeyCode::Char('f')),
+            Some(ClientMessage::Move(PlayerAction::Flag { index: 1 }))
+        );
+        assert_eq!(
+            press(&mut app, KeyCode::Char(' ')),
+            Some(ClientMessage::Move(PlayerAction::Chord { index: 1 }))
+        );
+    }
+
+    #[test]
+    fn test_mouse_selects_clicked_cell() {
+        let mut app = app();
+        let (column, row) = ui::cell_position(1, 0);
+
+        let command = app.handle_event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Right),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }));
+
+        assert_eq!(app.cursor, (1, 0));
+        assert!(matches!(
+            command,
+            Some(Command::Send(ClientMessage::Move(PlayerAction::Flag {
+                index: 2
+            })))
+        ));
+    }
+}
@@ -0,0 +1,316 @@
+//! Terminal client for games hosted by a minesweeper server, or played
+//! offline on the same rules.
+//!
+//! Messages are built from the protocol types in `minesweeper-core`, which the
+//! server uses too, so the client cannot fall out of step with it.
+
+mod app;
+mod solo;
+mod ui;
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use awc::ws::{Frame, Message};
+use clap::{Parser, Subcommand};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture, EventStream};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, terminal};
+use futures_util::{SinkExt, StreamExt};
+
+use minesweeper_core::protocol::{
+    ClientMessage, CreateGameRequest, GameAction, GameStateUpdate, JoinGameQuery, NewGameResponse,
+};
+use minesweeper_core::Board;
+
+use app::{App, Command};
+use solo::LocalGame;
+
+/// Largest board played offline, the same as the server allows by default
+const MAX_BOARD_SIZE: usize = 50;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Play minesweeper from the terminal, online or offline"
+)]
+struct Cli {
+    /// Address of the server
+    #[arg(
+        long,
+        env = "MINESWEEPER_SERVER",
+        default_value = "http://localhost:8080"
+    )]
+    server: String,
+    /// Name shown to the other players, required to play online
+    #[arg(long, short)]
+    name: Option<String>,
+    /// Token from `/api/identity`, to record results against an identity
+    #[arg(long, env = "MINESWEEPER_IDENTITY")]
+    identity_token: Option<String>,
+    #[command(subcommand)]
+    game: GameArgs,
+}
+
+#[derive(Subcommand)]
+enum GameArgs {
+    /// Create a game and join it as its host
+    Create {
+        #[arg(long, default_value_t = 16)]
+        size: usize,
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        /// Password other players have to give to join
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Join a game by its code
+    Join {
+        code: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Play on your own without a server
+    Solo {
+        #[arg(long, default_value_t = 16)]
+        size: usize,
+        /// Plays the same board every time for the same size and seed
+        #[arg(long)]
+        seed: Option<u64>,
+        /// File personal bests are kept in, `~/.minesweeper-bests.json` by default
+        #[arg(long, env = "MINESWEEPER_BESTS")]
+        bests: Option<PathBuf>,
+    },
+}
+
+/// Puts the terminal back the way it was found, even when the client fails
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide
+        )?;
+
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            cursor::Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[actix_rt::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(Some(reason)) => println!("{}", reason),
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Plays until the player quits or the server closes the socket, returning
+/// the server's reason for closing it
+async fn run(cli: Cli) -> Result<Option<String>, Box<dyn Error>> {
+    let server = cli.server.trim_end_matches('/');
+    let client = awc::Client::default();
+    let player_name = cli.name.ok_or("a --name is required to play online");
+
+    let query = match cli.game {
+        GameArgs::Solo { size, seed, bests } => return run_solo(size, seed, bests).await,
+        GameArgs::Create {
+            size,
+            players,
+            password,
+        } => {
+            let player_name = player_name?;
+            let mut create_game = CreateGameRequest::new(size, players).with_lobby();
+            if let Some(password) = password.clone() {
+                create_game = create_game.with_password(password);
+            }
+
+            let mut response = client
+                .post(format!("{}/api/create-game", server))
+                .send_json(&create_game)
+                .await?;
+            if !response.status().is_success() {
+                let body = response.body().await?;
+                return Err(String::from_utf8_lossy(&body).into());
+            }
+            let created = response.json::<NewGameResponse>().await?;
+
+            JoinGameQuery {
+                code: created.code,
+                player_name,
+                host_token: Some(created.host_token),
+                password,
+                identity_token: cli.identity_token,
+            }
+        }
+        GameArgs::Join { code, password } => JoinGameQuery {
+            code: code.to_uppercase(),
+            player_name: player_name?,
+            host_token: None,
+            password,
+            identity_token: cli.identity_token,
+        },
+    };
+
+    let url = format!(
+        "{}/api/join-game?{}",
+        server,
+        serde_urlencoded::to_string(&query)?
+    );
+    let (_, mut socket) = client
+        .ws(url)
+        .connect()
+        .await
+        .map_err(|err| format!("cannot join game {}: {}", query.code, err))?;
+
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+    let mut events = EventStream::new();
+    // Redraws the clocks even when nothing else happens
+    let mut ticks = actix_rt::time::interval(Duration::from_secs(1));
+    let mut app = App::new(format!("Game {}", query.code));
+
+    loop {
+        ui::draw(&mut stdout, &app)?;
+        stdout.flush()?;
+
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    return Ok(None);
+                };
+
+                match app.handle_event(event?) {
+                    Some(Command::Send(message)) => {
+                        let json = serde_json::to_string(&message)?;
+                        socket.send(Message::Text(json.into())).await?;
+                    }
+                    Some(Command::Quit) => {
+                        let _ = socket.send(Message::Close(None)).await;
+                        return Ok(None);
+                    }
+                    None => {}
+                }
+            }
+            frame = socket.next() => match frame {
+                Some(Ok(Frame::Text(text))) => {
+                    app.update(serde_json::from_slice::<GameStateUpdate>(&text)?);
+                }
+                Some(Ok(Frame::Ping(payload))) => socket.send(Message::Pong(payload)).await?,
+                Some(Ok(Frame::Close(reason))) => {
+                    return Ok(reason.and_then(|reason| reason.description));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err.into()),
+                None => return Ok(Some("Connection to the server was lost".into())),
+            },
+            _ = ticks.tick() => {}
+        }
+    }
+}
+
+/// Plays offline until the player quits, keeping their personal bests
+async fn run_solo(
+    size: usize,
+    seed: Option<u64>,
+    bests_path: Option<PathBuf>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    if !(2..=MAX_BOARD_SIZE).contains(&size) {
+        return Err(format!("board size must be between 2 and {}", MAX_BOARD_SIZE).into());
+    }
+
+    let bests_path = bests_path.or_else(|| {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".minesweeper-bests.json"))
+    });
+    let mut bests = match &bests_path {
+        Some(path) => solo::load_bests(path)?,
+        None => Default::default(),
+    };
+    let best_notice =
+        |best: Option<u64>| best.map(|best| format!("Best: {}", solo::format_time(best)));
+
+    let layout = match seed {
+        Some(seed) => Board::generate_seeded(size, seed),
+        None => Board::generate(size),
+    };
+    let mut game = LocalGame::new(layout);
+
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+    let mut events = EventStream::new();
+    let mut ticks = actix_rt::time::interval(Duration::from_secs(1));
+    let mut app = App::new(format!("Solo {}x{}", size, size));
+    app.update(game.state());
+    app.notice = best_notice(bests.get(size));
+
+    loop {
+        ui::draw(&mut stdout, &app)?;
+        stdout.flush()?;
+
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    return Ok(None);
+                };
+
+                match app.handle_event(event?) {
+                    Some(Command::Send(ClientMessage::Move(action))) => {
+                        if !game.play(&action) {
+                            continue;
+                        }
+
+                        if let Some(time) = game.winning_time() {
+                            let time_ms = time.as_millis() as u64;
+
+                            if bests.record(size, time_ms) {
+                                let time = solo::format_time(time_ms);
+                                app.notice = Some(format!("New personal best: {}", time));
+                                if let Some(path) = &bests_path {
+                                    solo::save_bests(path, &bests)?;
+                                }
+                            }
+                        }
+
+                        app.update(game.state());
+                    }
+                    Some(Command::Send(ClientMessage::Game(action))) => {
+                        let GameAction::Rematch { same_board } = action;
+                        game = if same_board {
+                            game.restart()
+                        } else {
+                            LocalGame::new(Board::generate(size))
+                        };
+                        app.update(game.state());
+                        app.notice = best_notice(bests.get(size));
+                    }
+                    Some(Command::Send(ClientMessage::Host(_))) | None => {}
+                    Some(Command::Quit) => return Ok(None),
+                }
+            }
+            _ = ticks.tick() => {}
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Games played without a server, following the same rules through the
+//! library and shown through the same state the server would send.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use minesweeper_core::protocol::{
+    GameStateUpdate, GameStats, DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+use minesweeper_core::{Board, PersonalBests, PlayerAction, SoloGame};
+
+pub struct LocalGame {
+    /// Layout the game started from, for playing it again
+    layout: Board,
+    game: SoloGame,
+    start_time: SystemTime,
+    finished_time: Option<SystemTime>,
+}
+
+impl LocalGame {
+    pub fn new(layout: Board) -> LocalGame {
+        LocalGame {
+            game: SoloGame::new(
+                layout.clone(),
+                DEFAULT_HINT_LIMIT,
+                DEFAULT_HINT_PENALTY_SECS,
+            ),
+            layout,
+            start_time: SystemTime::now(),
+            finished_time: None,
+        }
+    }
+
+    /// A new game on the same layout
+    pub fn restart(&self) -> LocalGame {
+        LocalGame::new(self.layout.clone())
+    }
+
+    /// Makes a move, returning whether it was allowed
+    pub fn play(&mut self, action: &PlayerAction) -> bool {
+        if self.game.play(action).is_err() {
+            return false;
+        }
+
+        if self.game.board().is_finished() {
+            self.finished_time = Some(SystemTime::now());
+        }
+
+        true
+    }
+
+    /// Time the player took, hint penalties included, once they cleared the board
+    pub fn winning_time(&self) -> Option<Duration> {
+        let finished_time = self.finished_time?;
+
+        self.game.board().is_cleared().then(|| {
+            finished_time
+                .duration_since(self.start_time)
+                .unwrap_or_default()
+                + self.game.penalty()
+        })
+    }
+
+    pub fn state(&self) -> GameStateUpdate {
+        let board = self.game.board();
+        // Like the server, penalties move the start back rather than the end forward
+        let start_time = self.start_time - self.game.penalty();
+        let seconds = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        };
+
+        GameStateUpdate {
+            board_state: board.player_view(),
+            board_size: board.size(),
+            revealed_board: board.reveal_view(),
+            opponents: Vec::new(),
+            start_time: Some(seconds(start_time)),
+            finished_time: self.finished_time.map(seconds),
+            stats: self.finished_time.map(|finished_time| {
+                GameStats::new(
+                    board.bbbv(),
+                    board.openings(),
+                    board.islands(),
+                    self.game.clicks(),
+                    finished_time.duration_since(start_time).unwrap_or_default(),
+                )
+            }),
+            hint: self.game.last_hint().cloned(),
+            hints_remaining: self.game.hints_remaining(),
+            deadline: None,
+            standings: Vec::new(),
+            rematch: None,
+            tournament: None,
+            host: None,
+            is_host: false,
+            locked: false,
+            started: true,
+        }
+    }
+}
+
+/// Reads the personal bests kept in a file, which only exists once there is one
+pub fn load_bests(path: &Path) -> Result<PersonalBests, Box<dyn std::error::Error>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(PersonalBests::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn save_bests(path: &Path, bests: &PersonalBests) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(bests)?)
+}
+
+pub fn format_time(time_ms: u64) -> String {
+    format!("{:.1}s", time_ms as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_local_game() {
+        // * 2 *
+        // 1 2 1
+        // . . .
+        let mut game = LocalGame::new(Board::from_mines(3, &[0, 2]).unwrap());
+
+        assert!(!game.play(&PlayerAction::Chord { index: 6 }));
+        assert!(game.play(&PlayerAction::Reveal { index: 6 }));
+        assert!(game.winning_time().is_none());
+        assert!(game.play(&PlayerAction::Reveal { index: 1 }));
+
+        let state = game.state();
+        assert!(game.winning_time().is_some());
+        assert!(state.revealed_board.is_some());
+        assert_eq!(state.stats.unwrap().clicks, 2);
+
+        assert!(game.restart().state().revealed_board.is_none());
+    }
+}
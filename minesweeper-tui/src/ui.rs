@@ -0,0 +1,283 @@
+//! Drawing the game onto the terminal.
+
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, Stylize};
+use crossterm::terminal::{Clear, ClearType};
+
+use minesweeper_core::protocol::GameStateUpdate;
+use minesweeper_core::{ExternalCell, Hint, RevealedCell};
+
+use crate::app::App;
+
+const BOARD_TOP: u16 = 2;
+const BOARD_LEFT: u16 = 1;
+/// Every cell takes a symbol and a space so the board looks square
+const CELL_WIDTH: u16 = 2;
+const PROGRESS_WIDTH: usize = 20;
+
+const HELP: &str =
+    "arrows/hjkl move  space reveal  f flag  c chord  ? hint  s start  r/R rematch  q quit";
+
+/// Terminal column and row a cell is drawn at
+pub fn cell_position(row: usize, column: usize) -> (u16, u16) {
+    (
+        BOARD_LEFT + column as u16 * CELL_WIDTH,
+        BOARD_TOP + row as u16,
+    )
+}
+
+/// Row and column of the cell drawn at a terminal position, e.g. one clicked
+pub fn cell_at(x: u16, y: u16, board_size: usize) -> Option<(usize, usize)> {
+    let row = y.checked_sub(BOARD_TOP)? as usize;
+    let column = (x.checked_sub(BOARD_LEFT)? / CELL_WIDTH) as usize;
+
+    (row < board_size && column < board_size).then_some((row, column))
+}
+
+pub fn draw(out: &mut impl Write, app: &App) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    let Some(state) = &app.state else {
+        return queue!(out, Print(format!("Joining {}...", app.title)));
+    };
+
+    queue!(
+        out,
+        Print(app.title.as_str().bold()),
+        Print("  "),
+        Print(status(state))
+    )?;
+
+    draw_board(out, app, state)?;
+    let panel_height = draw_panel(out, app, state)?;
+
+    let bottom = BOARD_TOP + (state.board_size as u16).max(panel_height) + 1;
+    queue!(out, MoveTo(0, bottom), Print(HELP.dark_grey()))
+}
+
+fn draw_board(out: &mut impl Write, app: &App, state: &GameStateUpdate) -> io::Result<()> {
+    let hinted = match &state.hint {
+        Some(Hint::Safe { index }) => Some((*index, Color::DarkGreen)),
+        Some(Hint::Mine { index }) => Some((*index, Color::DarkRed)),
+        Some(Hint::NoDeduction { guess: Some(guess) }) => Some((guess.index, Color::DarkYellow)),
+        Some(Hint::NoDeduction { guess: None }) | None => None,
+    };
+
+    for (index, cell) in state.board_state.cells().iter().enumerate() {
+        let (row, column) = (index / state.board_size, index % state.board_size);
+        let (x, y) = cell_position(row, column);
+
+        let (symbol, color) = match &state.revealed_board {
+            Some(revealed) => revealed_symbol(&revealed.cells()[index]),
+            None => cell_symbol(cell),
+        };
+
+        queue!(out, MoveTo(x, y))?;
+        if app.cursor == (row, column) {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        if let Some((_, background)) = hinted.filter(|(hinted, _)| *hinted == index) {
+            queue!(out, SetBackgroundColor(background))?;
+        }
+        queue!(
+            out,
+            Print(symbol.with(color)),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draws the opponents and standings beside the board, returning the number
+/// of lines taken
+fn draw_panel(out: &mut impl Write, app: &App, state: &GameStateUpdate) -> io::Result<u16> {
+    let mut lines = Vec::new();
+
+    if !state.opponents.is_empty() {
+        lines.push("Opponents".bold().to_string());
+    }
+    for opponent in &state.opponents {
+        let board = &opponent.board;
+        let rating = opponent
+            .rating
+            .map(|rating| format!(" ({:.0})", rating))
+            .unwrap_or_default();
+        let outcome = if board.exploded() {
+            " hit a mine".red().to_string()
+        } else if board.revealed() == board.safe_cells() {
+            " cleared".green().to_string()
+        } else {
+            String::new()
+        };
+
+        lines.push(format!("{}{}", opponent.name, rating));
+        lines.push(format!(
+            "  {} {}/{}{}",
+            progress_bar(board.revealed(), board.safe_cells()),
+            board.revealed(),
+            board.safe_cells(),
+            outcome
+        ));
+    }
+
+    if state.standings.iter().any(|standing| standing.finished) {
+        lines.push(String::new());
+        lines.push("Standings".bold().to_string());
+        for standing in &state.standings {
+            let result = match standing.time_ms {
+                Some(time_ms) if standing.won => format!("{:.1}s", time_ms as f64 / 1000.0),
+                _ if standing.finished => "out".to_string(),
+                _ => format!("{} cells", standing.revealed),
+            };
+            lines.push(format!("{}. {} {}", standing.rank, standing.name, result));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(format!("Hints left: {}", state.hints_remaining));
+    if let Some(Hint::NoDeduction { guess: None }) = state.hint {
+        lines.push("Nothing can be deduced".yellow().to_string());
+    }
+    if let Some(rematch) = &state.rematch {
+        let board = if rematch.same_board {
+            "same board"
+        } else {
+            "new board"
+        };
+        lines.push(format!(
+            "Rematch on a {}: {}",
+            board,
+            rematch.accepted.join(", ")
+        ));
+    }
+    if let Some(tournament) = &state.tournament {
+        lines.push(format!("Tournament round {}", tournament.round));
+    }
+    if let Some(notice) = &app.notice {
+        lines.push(notice.clone());
+    }
+
+    let x = BOARD_LEFT + state.board_size as u16 * CELL_WIDTH + 2;
+    for (line, text) in lines.iter().enumerate() {
+        queue!(out, MoveTo(x, BOARD_TOP + line as u16), Print(text))?;
+    }
+
+    Ok(lines.len() as u16)
+}
+
+fn status(state: &GameStateUpdate) -> String {
+    let now = SystemTime::now();
+    let seconds = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+
+    if !state.started {
+        return match (&state.host, state.is_host) {
+            (_, true) => "Press s to start".to_string(),
+            (Some(host), false) => format!("Waiting for {} to start", host),
+            (None, false) => "Waiting to start".to_string(),
+        };
+    }
+
+    let elapsed = state
+        .start_time
+        .map(|start_time| {
+            let end = state.finished_time.map(seconds).unwrap_or(now);
+            end.duration_since(seconds(start_time))
+                .unwrap_or_default()
+                .as_secs()
+        })
+        .unwrap_or_default();
+
+    match &state.revealed_board {
+        Some(revealed) if revealed.cells().contains(&RevealedCell::Exploded) => {
+            format!("Hit a mine after {}s", elapsed).red().to_string()
+        }
+        Some(revealed) if !revealed.cells().contains(&RevealedCell::Unrevealed) => {
+            format!("Cleared in {}s", elapsed).green().to_string()
+        }
+        Some(_) => "Out of time".yellow().to_string(),
+        None => match state.deadline {
+            Some(deadline) => {
+                let left = seconds(deadline)
+                    .duration_since(now)
+                    .unwrap_or_default()
+                    .as_secs();
+                format!("{}s, {}s left", elapsed, left)
+            }
+            None => format!("{}s", elapsed),
+        },
+    }
+}
+
+fn progress_bar(done: usize, total: usize) -> String {
+    let filled = (done * PROGRESS_WIDTH).checked_div(total).unwrap_or(0);
+
+    format!(
+        "{}{}",
+        "#".repeat(filled).green(),
+        "-".repeat(PROGRESS_WIDTH - filled.min(PROGRESS_WIDTH))
+            .dark_grey()
+    )
+}
+
+fn number_symbol(value: u8) -> (char, Color) {
+    let color = match value {
+        0 => Color::DarkGrey,
+        1 => Color::Blue,
+        2 => Color::Green,
+        3 => Color::Red,
+        4 => Color::DarkBlue,
+        5 => Color::DarkRed,
+        6 => Color::Cyan,
+        7 => Color::Magenta,
+        _ => Color::Grey,
+    };
+
+    match value {
+        0 => ('.', color),
+        value => (char::from(b'0' + value), color),
+    }
+}
+
+fn cell_symbol(cell: &ExternalCell) -> (char, Color) {
+    match cell {
+        ExternalCell::Number { value } => number_symbol(*value),
+        ExternalCell::Mine => ('X', Color::Red),
+        ExternalCell::Flagged => ('F', Color::Yellow),
+        ExternalCell::Unrevealed => ('#', Color::Grey),
+    }
+}
+
+fn revealed_symbol(cell: &RevealedCell) -> (char, Color) {
+    match cell {
+        RevealedCell::Number { value } => number_symbol(*value),
+        RevealedCell::Unrevealed => ('#', Color::Grey),
+        RevealedCell::Exploded => ('X', Color::Red),
+        RevealedCell::Mine => ('*', Color::White),
+        RevealedCell::CorrectFlag => ('F', Color::Green),
+        RevealedCell::WrongFlag => ('F', Color::Red),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cell_at() {
+        for (row, column) in [(0, 0), (3, 7), (9, 9)] {
+            let (x, y) = cell_position(row, column);
+
+            assert_eq!(cell_at(x, y, 10), Some((row, column)));
+            assert_eq!(cell_at(x + 1, y, 10), Some((row, column)));
+        }
+
+        assert_eq!(cell_at(0, BOARD_TOP, 10), None);
+        assert_eq!(cell_at(BOARD_LEFT, 0, 10), None);
+        assert_eq!(cell_at(BOARD_LEFT + 10 * CELL_WIDTH, BOARD_TOP, 10), None);
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use minesweeper_core::Board;
+
+use super::solve::play_through;
+use super::CommandError;
+
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// Width and height of the boards
+    #[arg(long, short, default_value_t = 16)]
+    size: usize,
+    /// Number of boards to generate and solve
+    #[arg(long, short, default_value_t = 100)]
+    boards: u64,
+    /// Seed of the first board, the others follow on from it
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    count as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+pub fn bench(args: &BenchArgs) -> Result<(), CommandError> {
+    if args.size < 2 || args.boards == 0 {
+        return Err("needs at least one board at least 2 cells wide".into());
+    }
+
+    let end_seed = args
+        .seed
+        .checked_add(args.boards)
+        .ok_or("seed is too large for that many boards")?;
+
+    let start = Instant::now();
+    let boards = (args.seed..end_seed)
+        .map(|seed| Board::generate_seeded(args.size, seed))
+        .collect::<Vec<Board>>();
+    let generation = start.elapsed();
+
+    let start = Instant::now();
+    let mut cleared = 0;
+    let mut guesses = 0;
+    for board in boards {
+        let playthrough = play_through(board, true, |_, _| {});
+
+        cleared += u64::from(playthrough.board.is_cleared());
+        guesses += playthrough.guesses;
+    }
+    let solving = start.elapsed();
+
+    println!(
+        "{} boards of {}x{} from seed {}",
+        args.boards, args.size, args.size, args.seed
+    );
+    println!(
+        "generate: {:>10.1} boards/s ({:?} total)",
+        per_second(args.boards, generation),
+        generation
+    );
+    println!(
+        "solve:    {:>10.1} boards/s ({:?} total)",
+        per_second(args.boards, solving),
+        solving
+    );
+    println!(
+        "solver cleared {:.1}% of boards, guessing {:.2} times per board",
+        cleared as f64 * 100.0 / args.boards as f64,
+        guesses as f64 / args.boards as f64
+    );
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use rand::Rng;
+
+use crate::game_handler::{Difficulty, GameRecord};
+use minesweeper_core::Board;
+
+use super::{parse_difficulty, render, BoardFormat, CommandError};
+
+#[derive(clap::Args, Debug)]
+pub struct GenerateArgs {
+    /// Width and height of the board
+    #[arg(long, short, default_value_t = 16)]
+    size: usize,
+    /// Uses the board size matchmaking plays a difficulty on instead of --size
+    #[arg(long, short, value_parser = parse_difficulty, conflicts_with = "size")]
+    difficulty: Option<Difficulty>,
+    /// Seed to generate the board from, a random one is picked when left out
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Writes the layout in a file format instead of drawing it
+    #[arg(long, short)]
+    format: Option<BoardFormat>,
+    /// File to write to instead of standard output
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+pub fn generate(args: &GenerateArgs) -> Result<(), CommandError> {
+    let size = args.difficulty.map_or(args.size, Difficulty::board_size);
+    if size < 2 {
+        return Err("boards must be at least 2 cells wide".into());
+    }
+
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let board = Board::generate_seeded(size, seed);
+
+    let bytes = match args.format {
+        None => format!(
+            "Seed: {}\nSize: {}x{}\nMines: {}\n3BV: {}\n\n{}",
+            seed,
+            size,
+            size,
+            board.mines_count(),
+            board.bbbv(),
+            render(&board, true)
+        )
+        .into_bytes(),
+        Some(BoardFormat::Json) => {
+            let mut json = serde_json::to_vec_pretty(&GameRecord::from_board(&board))?;
+            json.push(b'\n');
+            json
+        }
+        Some(BoardFormat::Mbf) => board.to_mbf()?,
+        Some(BoardFormat::Rawvf) => board.to_rawvf().into_bytes(),
+    };
+
+    match &args.output {
+        Some(path) => std::fs::write(path, bytes)?,
+        None => std::io::stdout().write_all(&bytes)?,
+    }
+
+    Ok(())
+}
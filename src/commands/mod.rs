@@ -0,0 +1,132 @@
+//! Subcommands of the binary other than running the server.
+
+pub mod bench;
+pub mod generate;
+pub mod replay;
+pub mod serve;
+pub mod solve;
+
+use std::fmt;
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::game_handler::{Difficulty, GameRecord};
+use minesweeper_core::{Board, CellState, CellType, FormatError};
+
+/// Failure of a command, reported before exiting
+#[derive(Debug)]
+pub struct CommandError(String);
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError(message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError(err.to_string())
+    }
+}
+
+impl From<FormatError> for CommandError {
+    fn from(err: FormatError) -> Self {
+        CommandError(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(err: serde_json::Error) -> Self {
+        CommandError(format!("invalid game record: {}", err))
+    }
+}
+
+/// File formats boards are read from and written in
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardFormat {
+    /// A game record as exported by the server
+    Json,
+    Mbf,
+    Rawvf,
+}
+
+impl BoardFormat {
+    /// Guesses the format of a file from its extension
+    fn of_path(path: &Path) -> Option<BoardFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(BoardFormat::Json),
+            "mbf" => Some(BoardFormat::Mbf),
+            "rawvf" | "txt" => Some(BoardFormat::Rawvf),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_difficulty(name: &str) -> Result<Difficulty, String> {
+    serde_json::from_value(serde_json::Value::String(name.to_lowercase()))
+        .map_err(|_| "expected beginner, intermediate or expert".to_string())
+}
+
+/// Reads a game record, in the format given or the one implied by the extension
+pub fn read_record(path: &Path, format: Option<BoardFormat>) -> Result<GameRecord, CommandError> {
+    let format = format
+        .or_else(|| BoardFormat::of_path(path))
+        .ok_or("cannot tell the format from the file name, pass --format")?;
+
+    let bytes = std::fs::read(path)
+        .map_err(|err| CommandError(format!("cannot read {}: {}", path.display(), err)))?;
+
+    let board = match format {
+        BoardFormat::Json => return Ok(serde_json::from_slice(&bytes)?),
+        BoardFormat::Mbf => Board::from_mbf(&bytes)?,
+        BoardFormat::Rawvf => Board::from_rawvf(
+            std::str::from_utf8(&bytes).map_err(|_| "RAWVF board must be valid UTF-8")?,
+        )?,
+    };
+
+    Ok(GameRecord::from_board(&board))
+}
+
+/// Draws a board one row per line, as the player sees it unless `reveal` is set
+pub fn render(board: &Board, reveal: bool) -> String {
+    let mut text = String::with_capacity(board.size() * (board.size() * 2 + 1));
+
+    for cell in board.iter_cells() {
+        let is_mine = cell.cell_type == CellType::Mine;
+
+        let symbol = match cell.state {
+            CellState::Flagged => 'F',
+            CellState::Revealed if is_mine => 'X',
+            CellState::Unrevealed if !reveal => '#',
+            _ if is_mine => '*',
+            _ => match board.get_surrounding_mines_count(cell.index) {
+                0 => '.',
+                count => char::from(b'0' + count),
+            },
+        };
+
+        text.push(symbol);
+        if (cell.index + 1) % board.size() == 0 {
+            text.push('\n');
+        } else {
+            text.push(' ');
+        }
+    }
+
+    text
+}
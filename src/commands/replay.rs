@@ -0,0 +1,105 @@
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::game_handler::PlayerAction;
+
+use super::{read_record, render, BoardFormat, CommandError};
+
+/// Longest pause between two moves, so that replays skip over idle players
+const MAX_PAUSE: Duration = Duration::from_secs(3);
+
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+    /// Game record exported from the server
+    file: PathBuf,
+    /// Player whose moves to replay, the first one in the record when left out
+    #[arg(long, short)]
+    player: Option<String>,
+    /// Waits for Enter before every move instead of replaying at the recorded pace
+    #[arg(long, short)]
+    step: bool,
+    /// Replays moves this many times faster than they were made
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+}
+
+pub fn replay(args: &ReplayArgs) -> Result<(), CommandError> {
+    if args.speed.is_nan() || args.speed <= 0.0 {
+        return Err("--speed must be greater than zero".into());
+    }
+
+    let record = read_record(&args.file, Some(BoardFormat::Json))?;
+
+    let player = match &args.player {
+        Some(name) => record
+            .players
+            .iter()
+            .find(|player| player.name == *name)
+            .ok_or_else(|| format!("nobody called {:?} played this game", name))?,
+        None => record.players.first().ok_or("the record has no moves")?,
+    };
+
+    let mut board = record.board().map_err(|_| "mine out of bounds")?;
+    let interactive = std::io::stdout().is_terminal();
+    let mut stdin = std::io::stdin().lock();
+    let mut previous_time = 0;
+
+    let frame = |header: String, board: &str| {
+        // Redraws in place rather than scrolling when shown in a terminal
+        if interactive {
+            print!("\x1b[2J\x1b[H");
+        }
+        println!("{}\n\n{}", header, board);
+    };
+
+    frame(
+        format!("{}: {} moves", player.name, player.moves.len()),
+        &render(&board, false),
+    );
+
+    for (number, recorded) in player.moves.iter().enumerate() {
+        if args.step {
+            let mut line = String::new();
+            stdin.read_line(&mut line)?;
+        } else {
+            let pause = Duration::from_millis(recorded.time.saturating_sub(previous_time));
+            // A tiny speed stretches the pause past what a Duration holds
+            let pause =
+                Duration::try_from_secs_f64(pause.as_secs_f64() / args.speed).unwrap_or(MAX_PAUSE);
+            std::thread::sleep(pause.min(MAX_PAUSE));
+        }
+        previous_time = recorded.time;
+
+        let _ = board.play(&recorded.action);
+        let description = match recorded.action {
+            PlayerAction::Reveal { index } => format!("reveal {}", index),
+            PlayerAction::Flag { index } => format!("flag {}", index),
+            PlayerAction::Chord { index } => format!("chord {}", index),
+            PlayerAction::Hint { .. } => "hint".to_string(),
+        };
+
+        frame(
+            format!(
+                "{}: move {}/{} at {:.1}s, {}",
+                player.name,
+                number + 1,
+                player.moves.len(),
+                recorded.time as f64 / 1000.0,
+                description
+            ),
+            &render(&board, false),
+        );
+    }
+
+    let outcome = if board.is_cleared() {
+        "Cleared"
+    } else if board.is_exploded() {
+        "Hit a mine"
+    } else {
+        "Did not finish"
+    };
+    println!("{}\n\n{}", outcome, render(&board, true));
+
+    Ok(())
+}
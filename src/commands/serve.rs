@@ -0,0 +1,72 @@
+use actix::Actor;
+use actix_cors::Cors;
+use actix_web::{web, App, HttpServer};
+
+use crate::config::{Config, ConfigArgs};
+use crate::{game_handler, routes, storage};
+
+/// Allows requests from the configured origins, or from anywhere given `*`
+fn cors(origins: &[String]) -> Cors {
+    if origins.iter().any(|origin| origin == "*") {
+        return Cors::permissive();
+    }
+
+    origins.iter().fold(
+        Cors::default()
+            .allow_any_method()
+            .allow_any_header()
+            .supports_credentials(),
+        |cors, origin| cors.allowed_origin(origin),
+    )
+}
+
+/// Runs the game server until it is shut down
+pub async fn serve(args: &ConfigArgs) -> std::io::Result<()> {
+    let config = match Config::load(args) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(2);
+        }
+    };
+
+    env_logger::Builder::new()
+        .filter_level(config.log_level())
+        .init();
+
+    println!("Effective configuration:\n\n{}", config.to_toml());
+
+    if !config.static_dir.is_dir() {
+        log::warn!(
+            "Static directory {} does not exist, only the API will be served",
+            config.static_dir.display()
+        );
+    }
+
+    let storage = storage::SqliteStorage::open(&config.database).map_err(std::io::Error::other)?;
+    let mut game_handler = game_handler::GameHandler::new(Box::new(storage))
+        .map_err(std::io::Error::other)?
+        .with_limits(config.limits);
+    if let Some(secret) = &config.daily_secret {
+        game_handler = game_handler.with_daily_secret(secret);
+    }
+    let game_handler_addr = web::Data::new(game_handler.start());
+    let limits = web::Data::new(config.limits);
+
+    let server_config = config.clone();
+
+    HttpServer::new(move || {
+        App::new()
+            .wrap(cors(&server_config.cors_origins))
+            // The default format logs query strings, which carry join secrets
+            .wrap(actix_web::middleware::Logger::new(
+                r#"%a "%U" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
+            ))
+            .service(routes::routes(&server_config.static_dir))
+            .app_data(game_handler_addr.clone())
+            .app_data(limits.clone())
+    })
+    .bind((config.bind_address, config.port))?
+    .run()
+    .await
+}
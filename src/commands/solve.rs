@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use minesweeper_core::{Board, Deduction};
+
+use super::{read_record, render, BoardFormat, CommandError};
+
+#[derive(clap::Args, Debug)]
+pub struct SolveArgs {
+    /// Board to solve: a JSON game record, an MBF or a RAWVF file
+    file: PathBuf,
+    /// Format of the file, guessed from its extension when left out
+    #[arg(long, short)]
+    format: Option<BoardFormat>,
+    /// Stops where a guess is needed instead of revealing the least risky cell
+    #[arg(long)]
+    no_guess: bool,
+    /// Draws the board after every move
+    #[arg(long, short)]
+    verbose: bool,
+}
+
+/// A move made while playing through a board
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    Deduced(Deduction),
+    /// Nothing could be proven, so the cell least likely to be a mine was revealed
+    Guessed {
+        index: usize,
+        risk: f64,
+    },
+}
+
+/// Outcome of playing through a board
+pub struct Playthrough {
+    pub board: Board,
+    pub deductions: usize,
+    pub guesses: usize,
+}
+
+/// Plays a board using only what a player could see. Cells proven safe are
+/// revealed and proven mines flagged. When nothing can be proven the least
+/// risky cell is revealed if `guess` is set, otherwise play stops.
+pub fn play_through(
+    mut board: Board,
+    guess: bool,
+    mut on_step: impl FnMut(&Board, Step),
+) -> Playthrough {
+    let mut deductions = 0;
+    let mut guesses = 0;
+
+    while !board.is_finished() {
+        let step = match board.find_deduction() {
+            Some(deduction) => Step::Deduced(deduction),
+            None if guess => match board.lowest_risk_cell() {
+                Some((index, risk)) => Step::Guessed { index, risk },
+                None => break,
+            },
+            None => break,
+        };
+
+        let applied = match step {
+            Step::Deduced(Deduction::Safe { index }) | Step::Guessed { index, .. } => {
+                board.reveal(index)
+            }
+            Step::Deduced(Deduction::Mine { index }) => board.toggle_flag(index),
+        };
+
+        // The solver only offers unrevealed cells, so this would otherwise loop forever
+        if applied.is_err() {
+            break;
+        }
+
+        match step {
+            Step::Deduced(_) => deductions += 1,
+            Step::Guessed { .. } => guesses += 1,
+        }
+
+        on_step(&board, step);
+    }
+
+    Playthrough {
+        board,
+        deductions,
+        guesses,
+    }
+}
+
+pub fn solve(args: &SolveArgs) -> Result<(), CommandError> {
+    let board = read_record(&args.file, args.format)?
+        .board()
+        .map_err(|_| "mine out of bounds")?;
+    let bbbv = board.bbbv();
+
+    let playthrough = play_through(board, !args.no_guess, |board, step| {
+        if args.verbose {
+            let description = match step {
+                Step::Deduced(Deduction::Safe { index }) => format!("Reveal {} (safe)", index),
+                Step::Deduced(Deduction::Mine { index }) => format!("Flag {} (mine)", index),
+                Step::Guessed { index, risk } => {
+                    format!("Reveal {} (guess, {:.0}% risk)", index, risk * 100.0)
+                }
+            };
+
+            println!("{}\n{}", description, render(board, false));
+        }
+    });
+
+    let board = &playthrough.board;
+    let outcome = if board.is_cleared() {
+        "Cleared"
+    } else if board.is_exploded() {
+        "Hit a mine"
+    } else {
+        "Stuck, a guess is needed"
+    };
+
+    println!("{}", render(board, false));
+    println!("{}", outcome);
+    println!(
+        "3BV: {}, deductions: {}, guesses: {}",
+        bbbv, playthrough.deductions, playthrough.guesses
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_play_through_without_guessing() {
+        // 1 * 1
+        // 1 1 1
+        // . . .
+        let board = Board::from_mines(3, &[1]).unwrap();
+
+        // Nothing can be proven on an untouched board
+        let stuck = play_through(board.clone(), false, |_, _| {});
+        assert_eq!(stuck.deductions + stuck.guesses, 0);
+
+        let mut opened = board;
+        for index in [0, 3, 4] {
+            opened.reveal(index).unwrap();
+        }
+
+        let playthrough = play_through(opened, false, |_, _| {});
+        assert!(playthrough.board.is_cleared());
+        assert!(playthrough.deductions > 0);
+        assert_eq!(playthrough.guesses, 0);
+    }
+}
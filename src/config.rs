@@ -0,0 +1,409 @@
+//! Server settings.
+//!
+//! Settings are layered: built-in defaults are overridden by a TOML file, then
+//! by `MINESWEEPER_*` environment variables and finally by command-line
+//! arguments.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+use crate::game_handler::GameLimits;
+
+/// File read when no other configuration file is given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "minesweeper.toml";
+
+const ENV_PREFIX: &str = "MINESWEEPER_";
+
+/// Variables with the prefix that are not settings: the configuration file,
+/// which clap picks along with the arguments, and those read by the terminal client
+const OTHER_ENV_VARS: &[&str] = &["CONFIG", "SERVER", "IDENTITY", "BESTS"];
+
+/// Shortest daily secret accepted, short ones being easy to guess
+const MIN_DAILY_SECRET_LENGTH: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    /// One of `off`, `error`, `warn`, `info`, `debug` or `trace`
+    pub log_level: String,
+    /// Origins allowed to make cross-origin requests, `*` allows any
+    pub cors_origins: Vec<String>,
+    /// Directory the frontend is served from
+    pub static_dir: PathBuf,
+    /// SQLite database games and results are kept in
+    pub database: PathBuf,
+    pub limits: GameLimits,
+    /// Key the daily challenge boards are derived from. Anyone who knows it can
+    /// work out every future board, so it is never printed. Without one a
+    /// random key is generated and kept in the database.
+    #[serde(skip_serializing)]
+    pub daily_secret: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 8080,
+            log_level: "debug".to_string(),
+            cors_origins: vec!["*".to_string()],
+            static_dir: PathBuf::from("./static/"),
+            database: PathBuf::from("minesweeper.db"),
+            limits: GameLimits::default(),
+            daily_secret: None,
+        }
+    }
+}
+
+/// Settings given on the command line, overriding every other source
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct ConfigArgs {
+    /// Configuration file to read, `minesweeper.toml` is used if it exists
+    #[arg(long, short, env = "MINESWEEPER_CONFIG")]
+    pub config: Option<PathBuf>,
+    #[arg(long)]
+    pub bind_address: Option<IpAddr>,
+    #[arg(long, short)]
+    pub port: Option<u16>,
+    #[arg(long)]
+    pub log_level: Option<String>,
+    /// Origin allowed to make cross-origin requests, may be given more than once
+    #[arg(long = "cors-origin")]
+    pub cors_origins: Vec<String>,
+    #[arg(long)]
+    pub static_dir: Option<PathBuf>,
+    #[arg(long)]
+    pub database: Option<PathBuf>,
+    #[arg(long)]
+    pub max_board_size: Option<usize>,
+    #[arg(long)]
+    pub max_player_limit: Option<usize>,
+    #[arg(long)]
+    pub max_hint_limit: Option<usize>,
+    #[arg(long)]
+    pub max_hint_penalty_secs: Option<u64>,
+    #[arg(long)]
+    pub max_clock_secs: Option<u64>,
+    #[arg(long)]
+    pub matchmaking_interval_secs: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// An environment variable could not be parsed
+    Env(String, String),
+    /// An environment variable with the prefix matches no setting
+    UnknownEnv(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "invalid {}: {}", path.display(), err),
+            ConfigError::Env(name, value) => write!(f, "invalid value {:?} for {}", value, name),
+            ConfigError::UnknownEnv(name) => write!(f, "unknown setting {}", name),
+            ConfigError::Invalid(reason) => write!(f, "invalid configuration: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads the configuration from every source and checks it
+    pub fn load(args: &ConfigArgs) -> Result<Config, ConfigError> {
+        // Arguments parsed without the serve subcommand miss clap's fallback to the environment
+        let file = match args
+            .config
+            .clone()
+            .or_else(|| std::env::var_os("MINESWEEPER_CONFIG").map(PathBuf::from))
+        {
+            Some(path) => Some(path),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_PATH)).filter(|path| path.exists()),
+        };
+
+        let mut config = match file {
+            Some(path) => Self::from_file(&path)?,
+            None => Config::default(),
+        };
+
+        config.apply_env(std::env::vars())?;
+        config.apply_args(args);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| ConfigError::Read(path.into(), err))?;
+
+        toml::from_str(&text).map_err(|err| ConfigError::Parse(path.into(), err))
+    }
+
+    fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), ConfigError> {
+        let vars = vars
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix(ENV_PREFIX)?.to_string(), value)))
+            .collect::<HashMap<String, String>>();
+
+        fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, ConfigError> {
+            value
+                .parse()
+                .map_err(|_| ConfigError::Env(format!("{}{}", ENV_PREFIX, name), value.into()))
+        }
+
+        for (name, value) in &vars {
+            match name.as_str() {
+                "BIND_ADDRESS" => self.bind_address = parse(name, value)?,
+                "PORT" => self.port = parse(name, value)?,
+                "LOG_LEVEL" => self.log_level = value.clone(),
+                "CORS_ORIGINS" => {
+                    self.cors_origins = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|origin| !origin.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "STATIC_DIR" => self.static_dir = value.into(),
+                "DATABASE" => self.database = value.into(),
+                "DAILY_SECRET" => self.daily_secret = Some(value.clone()),
+                "MAX_BOARD_SIZE" => self.limits.max_board_size = parse(name, value)?,
+                "MAX_PLAYER_LIMIT" => self.limits.max_player_limit = parse(name, value)?,
+                "MAX_HINT_LIMIT" => self.limits.max_hint_limit = parse(name, value)?,
+                "MAX_HINT_PENALTY_SECS" => self.limits.max_hint_penalty_secs = parse(name, value)?,
+                "MAX_CLOCK_SECS" => self.limits.max_clock_secs = parse(name, value)?,
+                "MATCHMAKING_INTERVAL_SECS" => {
+                    self.limits.matchmaking_interval_secs = parse(name, value)?
+                }
+                _ if OTHER_ENV_VARS.contains(&name.as_str()) => {}
+                _ => return Err(ConfigError::UnknownEnv(format!("{}{}", ENV_PREFIX, name))),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &ConfigArgs) {
+        if let Some(bind_address) = args.bind_address {
+            self.bind_address = bind_address;
+        }
+        if let Some(port) = args.port {
+            self.port = port;
+        }
+        if let Some(log_level) = &args.log_level {
+            self.log_level = log_level.clone();
+        }
+        if !args.cors_origins.is_empty() {
+            self.cors_origins = args.cors_origins.clone();
+        }
+        if let Some(static_dir) = &args.static_dir {
+            self.static_dir = static_dir.clone();
+        }
+        if let Some(database) = &args.database {
+            self.database = database.clone();
+        }
+
+        let limits = &mut self.limits;
+        if let Some(max_board_size) = args.max_board_size {
+            limits.max_board_size = max_board_size;
+        }
+        if let Some(max_player_limit) = args.max_player_limit {
+            limits.max_player_limit = max_player_limit;
+        }
+        if let Some(max_hint_limit) = args.max_hint_limit {
+            limits.max_hint_limit = max_hint_limit;
+        }
+        if let Some(max_hint_penalty_secs) = args.max_hint_penalty_secs {
+            limits.max_hint_penalty_secs = max_hint_penalty_secs;
+        }
+        if let Some(max_clock_secs) = args.max_clock_secs {
+            limits.max_clock_secs = max_clock_secs;
+        }
+        if let Some(matchmaking_interval_secs) = args.matchmaking_interval_secs {
+            limits.matchmaking_interval_secs = matchmaking_interval_secs;
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| Err(ConfigError::Invalid(reason));
+
+        if LevelFilter::from_str(&self.log_level).is_err() {
+            return invalid(format!("unknown log level {:?}", self.log_level));
+        }
+
+        if self.cors_origins.is_empty() {
+            return invalid("at least one CORS origin is required, use \"*\" for any".into());
+        }
+        for origin in &self.cors_origins {
+            let is_url = origin.starts_with("http://") || origin.starts_with("https://");
+            if origin != "*" && (!is_url || origin.ends_with('/')) {
+                return invalid(format!(
+                    "CORS origin {:?} should look like https://example.com",
+                    origin
+                ));
+            }
+        }
+
+        if self
+            .daily_secret
+            .as_ref()
+            .is_some_and(|secret| secret.len() < MIN_DAILY_SECRET_LENGTH)
+        {
+            return invalid(format!(
+                "daily_secret must be at least {} characters long",
+                MIN_DAILY_SECRET_LENGTH
+            ));
+        }
+
+        let limits = &self.limits;
+        if limits.max_board_size < 2 {
+            return invalid("max_board_size must be at least 2".into());
+        }
+        if limits.max_player_limit < 1 {
+            return invalid("max_player_limit must be at least 1".into());
+        }
+        if limits.max_clock_secs < 1 {
+            return invalid("max_clock_secs must be at least 1".into());
+        }
+        if limits.matchmaking_interval_secs < 1 {
+            return invalid("matchmaking_interval_secs must be at least 1".into());
+        }
+
+        Ok(())
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        LevelFilter::from_str(&self.log_level).unwrap_or(LevelFilter::Info)
+    }
+
+    /// The configuration in the same format it is read from
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("serializes Config")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_round_trips() {
+        let config = Config::default();
+
+        assert!(config.validate().is_ok());
+        assert_eq!(toml::from_str::<Config>(&config.to_toml()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_daily_secret_is_never_printed() {
+        let config = Config {
+            daily_secret: Some("a very secret secret".into()),
+            ..Config::default()
+        };
+
+        assert!(config.validate().is_ok());
+        assert!(!config.to_toml().contains("a very secret secret"));
+    }
+
+    #[test]
+    fn test_sources_override_each_other() {
+        let mut config: Config = toml::from_str(
+            r#"
+            port = 9000
+            log_level = "warn"
+
+            [limits]
+            max_board_size = 30
+            "#,
+        )
+        .unwrap();
+
+        config
+            .apply_env(env(&[
+                ("MINESWEEPER_PORT", "9001"),
+                (
+                    "MINESWEEPER_CORS_ORIGINS",
+                    "https://a.example, https://b.example",
+                ),
+                ("MINESWEEPER_MAX_PLAYER_LIMIT", "4"),
+                ("MINESWEEPER_MAX_CLOCK_SECS", "600"),
+                ("MINESWEEPER_SERVER", "http://localhost:8080"),
+                ("PORT", "1"),
+            ]))
+            .unwrap();
+        config.apply_args(&ConfigArgs {
+            port: Some(9002),
+            max_clock_secs: Some(900),
+            ..ConfigArgs::default()
+        });
+
+        assert_eq!(config.port, 9002);
+        assert_eq!(config.log_level(), LevelFilter::Warn);
+        assert_eq!(
+            config.cors_origins,
+            ["https://a.example", "https://b.example"]
+        );
+        assert_eq!(config.limits.max_board_size, 30);
+        assert_eq!(config.limits.max_player_limit, 4);
+        assert_eq!(config.limits.max_clock_secs, 900);
+        assert_eq!(config.database, Config::default().database);
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings() {
+        assert!(toml::from_str::<Config>("prot = 9000").is_err());
+        assert!(Config::default()
+            .apply_env(env(&[("MINESWEEPER_PORT", "http")]))
+            .is_err());
+        assert!(Config::default()
+            .apply_env(env(&[("MINESWEEPER_MAX_BORAD_SIZE", "30")]))
+            .is_err());
+
+        for config in [
+            Config {
+                log_level: "loud".into(),
+                ..Config::default()
+            },
+            Config {
+                cors_origins: vec!["example.com".into()],
+                ..Config::default()
+            },
+            Config {
+                daily_secret: Some("hunter2".into()),
+                ..Config::default()
+            },
+            Config {
+                limits: GameLimits {
+                    matchmaking_interval_secs: 0,
+                    ..GameLimits::default()
+                },
+                ..Config::default()
+            },
+        ] {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
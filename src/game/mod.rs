@@ -1,3 +0,0 @@
-mod board;
-
-pub use board::*;
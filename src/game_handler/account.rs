@@ -0,0 +1,240 @@
+use std::time::{Duration, SystemTime};
+
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use crate::storage::StorageError;
+
+use super::{password, player_name, GameHandler, NameError, PlayerId};
+
+/// Shortest password accepted when registering
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// How long a login lasts before the player has to log in again
+pub const SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// A registered player. Results of games joined while logged in are recorded
+/// against the account's player id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub username: String,
+    pub password_hash: String,
+    pub player_id: PlayerId,
+    pub created_time: SystemTime,
+}
+
+/// Proof of a login, handed to the client as a bearer token and cookie
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// Only the client gets the token itself. The server keeps sessions
+    /// under the token's [`hash_token`](super::hash_token)
+    pub token: String,
+    pub username: String,
+    pub player_id: PlayerId,
+    #[serde(skip)]
+    pub created_time: SystemTime,
+}
+
+/// Creates an account with a password hashed by [`hash_new_password`]
+pub struct Register {
+    pub username: String,
+    pub password_hash: String,
+}
+
+/// The stored password hash of an account, for the route to verify the
+/// login's password against
+pub struct GetAccountPasswordHash {
+    pub username: String,
+}
+
+/// Starts a session once the login's password has been verified against the
+/// hash from [`GetAccountPasswordHash`]
+pub struct Login {
+    pub username: String,
+    pub password_hash: String,
+}
+
+pub struct Logout {
+    pub token: String,
+}
+
+#[derive(Debug)]
+pub enum AccountError {
+    InvalidName(NameError),
+    UsernameTaken,
+    WeakPassword,
+    /// The username does not exist or the password is wrong
+    InvalidCredentials,
+    Storage(StorageError),
+}
+
+impl From<StorageError> for AccountError {
+    fn from(err: StorageError) -> Self {
+        AccountError::Storage(err)
+    }
+}
+
+impl Message for Register {
+    type Result = Result<Session, AccountError>;
+}
+
+impl Message for GetAccountPasswordHash {
+    type Result = Option<String>;
+}
+
+impl Message for Login {
+    type Result = Result<Session, AccountError>;
+}
+
+impl Message for Logout {
+    type Result = Result<(), StorageError>;
+}
+
+/// Hashes the password of a new account, rejecting those too short to be safe
+pub fn hash_new_password(password: &str) -> Result<String, AccountError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AccountError::WeakPassword);
+    }
+
+    Ok(password::hash_password(password))
+}
+
+impl Session {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        now.duration_since(self.created_time)
+            .is_ok_and(|age| age >= SESSION_TTL)
+    }
+}
+
+impl GameHandler {
+    /// The account a session token was handed out for, unless it has expired
+    pub(super) fn session_account(&self, token: &str) -> Option<&Account> {
+        self.sessions
+            .get(&password::hash_token(token))
+            .filter(|session| !session.is_expired(SystemTime::now()))
+            .and_then(|session| self.accounts.get(&session.username.to_lowercase()))
+    }
+
+    /// Normalises the name of a player who is not logged in, who may not use
+    /// an account's username in any case
+    pub(super) fn anonymous_name(&self, raw: &str) -> Result<String, NameError> {
+        let name = player_name::normalise_name(raw)?;
+
+        if self.accounts.contains_key(&name.to_lowercase()) {
+            return Err(NameError::Registered);
+        }
+
+        Ok(name)
+    }
+
+    /// Forgets sessions that have expired
+    pub(super) fn prune_sessions(&mut self) -> Result<(), StorageError> {
+        let now = SystemTime::now();
+        let expired = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.is_expired(now))
+            .map(|(token_hash, _)| token_hash.clone())
+            .collect::<Vec<String>>();
+
+        for token_hash in expired {
+            self.storage.delete_session(&token_hash)?;
+            self.sessions.remove(&token_hash);
+        }
+
+        Ok(())
+    }
+
+    fn start_session(&mut self, account: &Account) -> Result<Session, StorageError> {
+        self.prune_sessions()?;
+
+        let session = Session {
+            token: format!("{:032x}", rand::random::<u128>()),
+            username: account.username.clone(),
+            player_id: account.player_id.clone(),
+            created_time: SystemTime::now(),
+        };
+        let stored = Session {
+            token: password::hash_token(&session.token),
+            ..session.clone()
+        };
+
+        self.storage.save_session(&stored)?;
+        self.sessions.insert(stored.token.clone(), stored);
+
+        Ok(session)
+    }
+}
+
+impl Handler<Register> for GameHandler {
+    type Result = Result<Session, AccountError>;
+
+    fn handle(&mut self, msg: Register, _ctx: &mut Self::Context) -> Self::Result {
+        let username =
+            player_name::normalise_name(&msg.username).map_err(AccountError::InvalidName)?;
+
+        // Usernames are unique regardless of case
+        if self.accounts.contains_key(&username.to_lowercase()) {
+            return Err(AccountError::UsernameTaken);
+        }
+
+        let account = Account {
+            username,
+            password_hash: msg.password_hash,
+            player_id: PlayerId::new(format!("{:016x}", rand::random::<u64>())),
+            created_time: SystemTime::now(),
+        };
+
+        self.storage.save_account(&account)?;
+        self.accounts
+            .insert(account.username.to_lowercase(), account.clone());
+
+        Ok(self.start_session(&account)?)
+    }
+}
+
+impl GameHandler {
+    fn account_by_name(&self, username: &str) -> Option<&Account> {
+        player_name::normalise_name(username)
+            .ok()
+            .and_then(|username| self.accounts.get(&username.to_lowercase()))
+    }
+}
+
+impl Handler<GetAccountPasswordHash> for GameHandler {
+    type Result = Option<String>;
+
+    fn handle(&mut self, msg: GetAccountPasswordHash, _ctx: &mut Self::Context) -> Self::Result {
+        self.account_by_name(&msg.username)
+            .map(|account| account.password_hash.clone())
+    }
+}
+
+impl Handler<Login> for GameHandler {
+    type Result = Result<Session, AccountError>;
+
+    fn handle(&mut self, msg: Login, _ctx: &mut Self::Context) -> Self::Result {
+        // Only the hash the route verified the password against will do
+        let account = self
+            .account_by_name(&msg.username)
+            .filter(|account| account.password_hash == msg.password_hash)
+            .cloned()
+            .ok_or(AccountError::InvalidCredentials)?;
+
+        Ok(self.start_session(&account)?)
+    }
+}
+
+impl Handler<Logout> for GameHandler {
+    type Result = Result<(), StorageError>;
+
+    fn handle(&mut self, msg: Logout, _ctx: &mut Self::Context) -> Self::Result {
+        let token_hash = password::hash_token(&msg.token);
+
+        if self.sessions.remove(&token_hash).is_some() {
+            self.storage.delete_session(&token_hash)?;
+        }
+
+        Ok(())
+    }
+}
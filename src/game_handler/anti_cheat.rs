@@ -0,0 +1,130 @@
+//! Heuristics run over a finished game's server-side move log to flag results
+//! that were unlikely to be achieved by a human.
+//!
+//! Flagged results are still recorded, but leaderboards leave them out unless
+//! asked to include them.
+
+use minesweeper_core::Board;
+use serde::{Deserialize, Serialize};
+
+use super::{GameStats, PlayerAction, RecordedMove};
+
+/// Number of consecutive moves looked at when measuring the move rate
+const MOVE_RATE_WINDOW: usize = 10;
+/// Shortest time in milliseconds a human needs for `MOVE_RATE_WINDOW` moves
+const MIN_WINDOW_MS: u64 = 500;
+
+/// Board clearing speed above which a win is implausible. Human records sit
+/// well below this even on small boards.
+const MAX_BBBV_PER_SECOND: f64 = 12.0;
+
+/// Reveals of cells whose mine probability was in this range count as coin flips
+const COIN_FLIP_RISK: (f64, f64) = (0.4, 0.6);
+/// Number of coin flips survived in a single game that is considered suspicious
+const MAX_COIN_FLIPS_SURVIVED: usize = 6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Suspicion {
+    /// Moves arrived faster than a person can click
+    InhumanMoveRate,
+    /// The board was cleared implausibly fast for its 3BV
+    ImplausibleSpeed,
+    /// The player survived too many forced 50/50 guesses
+    PerfectGuessing,
+}
+
+/// Replays the moves from the starting board, noting the risk the player took
+/// with every reveal. Working this out is costly, so it is left until a win
+/// needs reviewing rather than done as moves come in.
+pub fn record_risks(start: &Board, moves: &mut [RecordedMove]) {
+    let mut board = start.clone();
+
+    for recorded in moves {
+        if let PlayerAction::Reveal { index } = recorded.action {
+            recorded.risk = board.mine_probabilities().get(index).copied().flatten();
+        }
+
+        // Only moves that were applied are recorded, so they apply again
+        let _ = board.play(&recorded.action);
+    }
+}
+
+/// Reviews a finished game. `won` results are also checked for speed and
+/// guessing since only wins reach the leaderboards.
+pub fn review(moves: &[RecordedMove], stats: &GameStats, won: bool) -> Vec<Suspicion> {
+    let mut suspicions = Vec::new();
+
+    let clicks = moves
+        .iter()
+        .filter(|recorded| !matches!(recorded.action, PlayerAction::Hint { .. }))
+        .map(|recorded| recorded.time)
+        .collect::<Vec<u64>>();
+
+    if clicks
+        .windows(MOVE_RATE_WINDOW)
+        .any(|window| window[MOVE_RATE_WINDOW - 1].saturating_sub(window[0]) < MIN_WINDOW_MS)
+    {
+        suspicions.push(Suspicion::InhumanMoveRate);
+    }
+
+    if won && stats.bbbv_per_second > MAX_BBBV_PER_SECOND {
+        suspicions.push(Suspicion::ImplausibleSpeed);
+    }
+
+    let coin_flips = moves
+        .iter()
+        .filter_map(|recorded| recorded.risk)
+        .filter(|risk| (COIN_FLIP_RISK.0..=COIN_FLIP_RISK.1).contains(risk))
+        .count();
+
+    if won && coin_flips >= MAX_COIN_FLIPS_SURVIVED {
+        suspicions.push(Suspicion::PerfectGuessing);
+    }
+
+    suspicions
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn reveal(time: u64, risk: Option<f64>) -> RecordedMove {
+        RecordedMove {
+            action: PlayerAction::Reveal { index: 0 },
+            time,
+            risk,
+        }
+    }
+
+    fn stats(seconds: u64) -> GameStats {
+        GameStats::new(30, 4, 2, 30, Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn test_human_game_is_not_flagged() {
+        let moves = (0..30)
+            .map(|index| reveal(index * 400, Some(0.1)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(review(&moves, &stats(12), true), vec![]);
+    }
+
+    #[test]
+    fn test_flags_scripted_game() {
+        let moves = (0..30)
+            .map(|index| reveal(index * 10, Some(0.5)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            review(&moves, &stats(1), true),
+            vec![
+                Suspicion::InhumanMoveRate,
+                Suspicion::ImplausibleSpeed,
+                Suspicion::PerfectGuessing
+            ]
+        );
+    }
+}
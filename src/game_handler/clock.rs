@@ -0,0 +1,104 @@
+use std::time::{Duration, SystemTime};
+
+use actix::{AsyncContext, Context};
+
+use super::{Clock, GameHandler, WsGame};
+
+impl WsGame {
+    /// When the given player's clock runs out, if the game is timed
+    pub fn player_deadline(&self, player_code: u16) -> Option<SystemTime> {
+        if self.waiting {
+            return None;
+        }
+
+        match self.config.clock {
+            Clock::Unlimited => None,
+            Clock::PerPlayer { limit_secs } => self
+                .players
+                .get(&player_code)
+                .map(|player_game| player_game.start_time + Duration::from_secs(limit_secs)),
+            Clock::Blitz { .. } => self.deadline,
+        }
+    }
+
+    /// Sets the shared deadline of a blitz game if it has not been started yet
+    pub(super) fn start_blitz_clock(&mut self, now: SystemTime) {
+        if self.waiting {
+            return;
+        }
+
+        if let Clock::Blitz { limit_secs } = self.config.clock {
+            self.deadline
+                .get_or_insert(now + Duration::from_secs(limit_secs));
+        }
+    }
+}
+
+impl GameHandler {
+    /// Arranges for the player's game to be ended once their clock runs out
+    pub(super) fn schedule_expiry(
+        &self,
+        ctx: &mut Context<Self>,
+        game_code: u16,
+        player_code: u16,
+    ) {
+        let Some(deadline) = self
+            .games
+            .get(&game_code)
+            .and_then(|game| game.player_deadline(player_code))
+        else {
+            return;
+        };
+
+        let delay = deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+
+        ctx.run_later(delay, move |act, _ctx| {
+            act.expire_player(game_code, player_code);
+        });
+    }
+
+    /// Restarts the timers of games restored from storage
+    pub(super) fn schedule_all_expiries(&self, ctx: &mut Context<Self>) {
+        for (&game_code, game) in &self.games {
+            for (&player_code, player_game) in &game.players {
+                if !player_game.board.is_finished() {
+                    self.schedule_expiry(ctx, game_code, player_code);
+                }
+            }
+        }
+    }
+
+    /// Ends the player's game if their clock has run out.
+    ///
+    /// Returns whether the player's game is over, either now or before.
+    pub(super) fn expire_player(&mut self, game_code: u16, player_code: u16) -> bool {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return false;
+        };
+
+        let deadline = game.player_deadline(player_code);
+
+        let Some(player_game) = game.players.get_mut(&player_code) else {
+            return false;
+        };
+
+        if player_game.board.is_finished() {
+            return true;
+        }
+
+        match deadline {
+            Some(deadline) if deadline <= SystemTime::now() => {
+                player_game.board.end();
+
+                self.finish_player(game_code, player_code, deadline);
+                self.persist_game(game_code);
+                self.notify_game(game_code, None);
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,36 +1,93 @@
 use actix::{Handler, Message};
-use rand::Rng;
-use serde::Deserialize;
+use minesweeper_core::protocol::CreateGameRequest;
 
-use super::{GameConfig, GameHandler, WsGame};
+use super::{Clock, GameConfig, GameHandler, Host, NoFreeCode, Visibility, WsGame};
 
-#[derive(Deserialize)]
 pub struct CreateGame {
     pub board_size: usize,
     pub player_limit: usize,
+    pub hint_limit: usize,
+    pub hint_penalty_secs: u64,
+    pub clock: Clock,
+    pub visibility: Visibility,
+    /// Hash of the password players have to give to join, made with
+    /// [`hash_password`](super::hash_password)
+    pub password_hash: Option<String>,
+    /// Whether the game waits in a lobby for the host to start it
+    pub lobby: bool,
+}
+
+#[derive(Debug)]
+pub enum CreateGameError {
+    /// The settings are outside the server's limits
+    OutsideLimits,
+    NoFreeCode,
+}
+
+impl From<NoFreeCode> for CreateGameError {
+    fn from(_: NoFreeCode) -> Self {
+        CreateGameError::NoFreeCode
+    }
+}
+
+/// A new game, along with the token that makes whoever joins with it the host
+pub struct CreatedGame {
+    pub code: u16,
+    pub host_token: String,
+}
+
+impl CreateGame {
+    /// The request's password is replaced by its hash, which is slow to make
+    /// and so is made before the game handler sees the request
+    pub fn new(request: CreateGameRequest, password_hash: Option<String>) -> CreateGame {
+        CreateGame {
+            board_size: request.board_size,
+            player_limit: request.player_limit,
+            hint_limit: request.hint_limit,
+            hint_penalty_secs: request.hint_penalty_secs,
+            clock: request.clock,
+            visibility: request.visibility,
+            password_hash,
+            lobby: request.lobby,
+        }
+    }
 }
 
 impl Message for CreateGame {
-    type Result = Result<u16, ()>;
+    type Result = Result<CreatedGame, CreateGameError>;
 }
 
 impl Handler<CreateGame> for GameHandler {
-    type Result = Result<u16, ()>;
+    type Result = Result<CreatedGame, CreateGameError>;
 
     fn handle(&mut self, msg: CreateGame, _ctx: &mut Self::Context) -> Self::Result {
-        let code = (0..0xff)
-            .map(|_| rand::thread_rng().gen_range(0u16..0xffffu16))
-            .filter(|code| !self.games.contains_key(&code))
-            .next()
-            .expect("generate a random code not already present");
-
-        let new_game = WsGame::new(GameConfig {
+        let config = GameConfig {
             board_size: msg.board_size,
             player_limit: msg.player_limit,
-        });
+            hint_limit: msg.hint_limit,
+            hint_penalty_secs: msg.hint_penalty_secs,
+            clock: msg.clock,
+            visibility: msg.visibility,
+        };
+
+        if !self.limits.allow(&config) {
+            return Err(CreateGameError::OutsideLimits);
+        }
+
+        let code = self.new_game_code()?;
+
+        let mut new_game = WsGame::new(config);
+
+        let host = Host::new();
+        let host_token = host.token().to_string();
+
+        new_game.host = Some(host);
+        new_game.waiting = msg.lobby;
+        new_game.password_hash = msg.password_hash;
 
         self.games.insert(code, new_game);
+        self.persist_game(code);
 
-        Ok(code)
+        Ok(CreatedGame { code, host_token })
     }
 }
@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use actix::{Handler, Message};
+use serde::Deserialize;
+
+use super::{
+    default_hint_limit, default_hint_penalty_secs, player_name, Clock, GameConfig, GameHandler,
+    NameError, PlayerId, Tournament, TournamentFormat, Visibility,
+};
+
+#[derive(Deserialize)]
+pub struct CreateTournament {
+    pub format: TournamentFormat,
+    /// Usernames of the players taking part, who may only join their own
+    /// matches while logged in
+    pub entrants: Vec<String>,
+    pub board_size: usize,
+    #[serde(default = "default_hint_limit")]
+    pub hint_limit: usize,
+    #[serde(default = "default_hint_penalty_secs")]
+    pub hint_penalty_secs: u64,
+    #[serde(default)]
+    pub clock: Clock,
+}
+
+#[derive(Debug)]
+pub enum CreateTournamentError {
+    TooFewEntrants,
+    DuplicateEntrant,
+    /// An entrant has no registered account to play under
+    UnknownEntrant(String),
+    NoRounds,
+    /// The settings of the games are outside the server's limits
+    OutsideLimits,
+    InvalidName(NameError),
+    NoFreeCode,
+}
+
+impl Message for CreateTournament {
+    type Result = Result<u16, CreateTournamentError>;
+}
+
+impl Handler<CreateTournament> for GameHandler {
+    type Result = Result<u16, CreateTournamentError>;
+
+    fn handle(&mut self, msg: CreateTournament, _ctx: &mut Self::Context) -> Self::Result {
+        let entrants = msg
+            .entrants
+            .iter()
+            .map(|entrant| player_name::normalise_name(entrant))
+            .collect::<Result<Vec<String>, NameError>>()
+            .map_err(CreateTournamentError::InvalidName)?;
+
+        if entrants.len() < 2 {
+            return Err(CreateTournamentError::TooFewEntrants);
+        }
+
+        let unique = entrants
+            .iter()
+            .map(|entrant| entrant.to_lowercase())
+            .collect::<HashSet<String>>();
+        if unique.len() != entrants.len() {
+            return Err(CreateTournamentError::DuplicateEntrant);
+        }
+
+        if msg.format == (TournamentFormat::Series { best_of: 0 }) {
+            return Err(CreateTournamentError::NoRounds);
+        }
+
+        // Entrants play under the spelling of their username, whatever the case given
+        let entrants = entrants
+            .into_iter()
+            .map(|entrant| {
+                self.accounts
+                    .get(&entrant.to_lowercase())
+                    .map(|account| (account.username.clone(), account.player_id.clone()))
+                    .ok_or(CreateTournamentError::UnknownEntrant(entrant))
+            })
+            .collect::<Result<Vec<(String, PlayerId)>, CreateTournamentError>>()?;
+
+        // Brackets are played in pairs, series with every entrant at once
+        let largest_match = match msg.format {
+            TournamentFormat::Bracket => 2,
+            TournamentFormat::Series { .. } => entrants.len(),
+        };
+        let game_config = GameConfig {
+            player_limit: largest_match,
+            board_size: msg.board_size,
+            hint_limit: msg.hint_limit,
+            hint_penalty_secs: msg.hint_penalty_secs,
+            clock: msg.clock,
+            visibility: Visibility::Private,
+        };
+
+        if !self.limits.allow(&game_config) {
+            return Err(CreateTournamentError::OutsideLimits);
+        }
+
+        let code = self
+            .new_tournament_code()
+            .map_err(|_| CreateTournamentError::NoFreeCode)?;
+
+        let tournament = Tournament::new(msg.format, game_config, entrants);
+
+        self.tournaments.insert(code, tournament);
+        if self.start_next_round(code).is_err() {
+            self.tournaments.remove(&code);
+            return Err(CreateTournamentError::NoFreeCode);
+        }
+        self.persist_tournament(code);
+
+        Ok(code)
+    }
+}
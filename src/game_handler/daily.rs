@@ -0,0 +1,360 @@
+//! The daily challenge: a board derived from the date that is the same for
+//! everyone, which every logged in player gets a single attempt at.
+//!
+//! Attempts are played as private single-player games, so they are only tied
+//! together by the day their results are recorded for. The board is keyed by a
+//! server secret so it can't be worked out ahead of time, and games that would
+//! show it stay closed until the day is over.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix::{Handler, Message};
+use blake2::digest::{KeyInit, Mac};
+use blake2::{Blake2s256, Blake2sMac256, Digest};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::storage::StorageError;
+use minesweeper_core::Board;
+
+use super::{
+    get_leaderboard::{rank_results, LeaderboardEntry},
+    Clock, GameConfig, GameHandler, GameResult, NoFreeCode, PlayerTokenError, Visibility, WsGame,
+    WsPlayerGame, DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+
+pub const DAILY_BOARD_SIZE: usize = 16;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Day containing the given time, counted in days since the epoch in UTC
+pub fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / SECS_PER_DAY
+}
+
+/// Formats a day as an ISO 8601 date such as `2024-03-01`
+pub fn format_date(day: u64) -> String {
+    // Counts from 0000-03-01 so that leap days fall at the end of a year
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+/// Parses an ISO 8601 date such as `2024-03-01` into a day, if it is a valid
+/// date since the epoch
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<i64>().ok()?;
+    let day_of_month = parts.next()?.parse::<i64>().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day_of_month) {
+        return None;
+    }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day_of_month - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    let day = u64::try_from(era * 146_097 + day_of_era - 719_468).ok()?;
+
+    // Rejects dates such as 2023-02-30 that roll over into the next month
+    (format_date(day) == date).then_some(day)
+}
+
+/// Seed of the board of a day, a MAC of the day under the server's secret
+fn daily_seed(secret: &[u8], day: u64) -> u64 {
+    let key = Blake2s256::digest(secret);
+    let mut mac =
+        <Blake2sMac256 as KeyInit>::new_from_slice(&key).expect("a 32 byte key fits BLAKE2s");
+    mac.update(&day.to_le_bytes());
+
+    let tag = mac.finalize().into_bytes();
+    u64::from_le_bytes(tag[..8].try_into().expect("the tag is 32 bytes long"))
+}
+
+/// Board of the daily challenge for a day
+pub fn daily_board(secret: &[u8], day: u64) -> Board {
+    Board::generate_seeded(DAILY_BOARD_SIZE, daily_seed(secret, day))
+}
+
+impl WsGame {
+    /// Whether the game is an attempt at a challenge others may still be
+    /// playing, so its board must not be shown to anyone but its player
+    pub(super) fn is_live_daily(&self) -> bool {
+        self.daily
+            .is_some_and(|day| day >= day_of(SystemTime::now()))
+    }
+}
+
+#[derive(Debug)]
+pub enum DailyError {
+    /// Only logged in players can be held to a single attempt, identities
+    /// being free to make
+    Anonymous,
+    Token(PlayerTokenError),
+    /// The player has already finished today's challenge
+    AlreadyPlayed,
+    NoFreeCode,
+    Storage(StorageError),
+}
+
+impl From<NoFreeCode> for DailyError {
+    fn from(_: NoFreeCode) -> Self {
+        DailyError::NoFreeCode
+    }
+}
+
+impl From<PlayerTokenError> for DailyError {
+    fn from(err: PlayerTokenError) -> Self {
+        DailyError::Token(err)
+    }
+}
+
+impl From<StorageError> for DailyError {
+    fn from(err: StorageError) -> Self {
+        DailyError::Storage(err)
+    }
+}
+
+impl GameHandler {
+    fn daily_results(&self, day: u64) -> Result<Vec<GameResult>, StorageError> {
+        Ok(self
+            .storage
+            .load_results()?
+            .into_iter()
+            .filter(|result| result.daily == Some(day))
+            .collect())
+    }
+}
+
+/// Starts the player's attempt at today's challenge, or hands back the one
+/// they already started
+pub struct PlayDaily {
+    pub session_token: Option<String>,
+}
+
+/// Game and player codes of an attempt at the daily challenge
+pub struct DailyAttempt {
+    pub game_code: u16,
+    pub player_code: u16,
+}
+
+impl Message for PlayDaily {
+    type Result = Result<DailyAttempt, DailyError>;
+}
+
+impl Handler<PlayDaily> for GameHandler {
+    type Result = Result<DailyAttempt, DailyError>;
+
+    fn handle(&mut self, msg: PlayDaily, _ctx: &mut Self::Context) -> Self::Result {
+        let token = msg.session_token.ok_or(DailyError::Anonymous)?;
+        let account = self
+            .session_account(&token)
+            .ok_or(DailyError::Token(PlayerTokenError::InvalidSession))?;
+        let (player_id, player_name) = (account.player_id.clone(), account.username.clone());
+
+        let day = day_of(SystemTime::now());
+
+        if self
+            .daily_results(day)?
+            .iter()
+            .any(|result| result.player_id.as_ref() == Some(&player_id))
+        {
+            return Err(DailyError::AlreadyPlayed);
+        }
+
+        // Players who left mid-game carry on where they were, with their clock running
+        let started = self.games.iter().find_map(|(&game_code, game)| {
+            game.players
+                .iter()
+                .find(|(_, player_game)| {
+                    game.daily == Some(day) && player_game.player_id.as_ref() == Some(&player_id)
+                })
+                .map(|(&player_code, _)| DailyAttempt {
+                    game_code,
+                    player_code,
+                })
+        });
+        if let Some(attempt) = started {
+            return Ok(attempt);
+        }
+
+        let game_code = self.new_game_code()?;
+        let player_code = rand::thread_rng().gen_range(0u16..0xffffu16);
+
+        let mut game = WsGame::with_board(
+            GameConfig {
+                board_size: DAILY_BOARD_SIZE,
+                player_limit: 1,
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock: Clock::default(),
+                visibility: Visibility::Private,
+            },
+            daily_board(&self.daily_secret, day),
+        );
+        game.daily = Some(day);
+        game.players.insert(
+            player_code,
+            WsPlayerGame {
+                board: game.board.clone(),
+                name: player_name,
+                start_time: SystemTime::now(),
+                finished_time: None,
+                moves: Vec::new(),
+                hints_used: 0,
+                last_hint: None,
+                player_id: Some(player_id),
+                left: false,
+            },
+        );
+
+        self.games.insert(game_code, game);
+        self.persist_game(game_code);
+
+        Ok(DailyAttempt {
+            game_code,
+            player_code,
+        })
+    }
+}
+
+/// Today's challenge and how the player asking has done at it
+pub struct GetDailyChallenge {
+    pub identity_token: Option<String>,
+    pub session_token: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DailyChallenge {
+    pub date: String,
+    pub board_size: usize,
+    pub mines: usize,
+    /// Number of players who have finished the challenge so far
+    pub finished_count: usize,
+    /// Whether the player asking has finished it, always false for anonymous players
+    pub completed: bool,
+    /// Leaderboard entry of the player asking, once they have cleared the board
+    /// without being flagged
+    pub personal_result: Option<LeaderboardEntry>,
+}
+
+impl Message for GetDailyChallenge {
+    type Result = Result<DailyChallenge, DailyError>;
+}
+
+impl Handler<GetDailyChallenge> for GameHandler {
+    type Result = Result<DailyChallenge, DailyError>;
+
+    fn handle(&mut self, msg: GetDailyChallenge, _ctx: &mut Self::Context) -> Self::Result {
+        let player_id = self
+            .resolve_player(msg.session_token.as_deref(), msg.identity_token.as_deref())?
+            .map(|(player_id, _)| player_id);
+
+        let day = day_of(SystemTime::now());
+        let results = self.daily_results(day)?;
+
+        let completed = player_id.as_ref().is_some_and(|player_id| {
+            results
+                .iter()
+                .any(|result| result.player_id.as_ref() == Some(player_id))
+        });
+        let personal_result = player_id.and_then(|player_id| {
+            rank_results(
+                results
+                    .iter()
+                    .filter(|result| !result.is_flagged() && !result.unranked),
+            )
+            .into_iter()
+            .find(|entry| entry.player_id.as_ref() == Some(&player_id))
+        });
+
+        Ok(DailyChallenge {
+            date: format_date(day),
+            board_size: DAILY_BOARD_SIZE,
+            mines: daily_board(&self.daily_secret, day).mines_count(),
+            finished_count: results.len(),
+            completed,
+            personal_result,
+        })
+    }
+}
+
+/// Fastest clears of the challenge of a day, today's unless given
+pub struct GetDailyLeaderboard {
+    pub day: Option<u64>,
+    pub limit: usize,
+    /// Whether results flagged as suspicious should be ranked as well
+    pub include_flagged: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DailyLeaderboard {
+    pub date: String,
+    pub top: Vec<LeaderboardEntry>,
+}
+
+impl Message for GetDailyLeaderboard {
+    type Result = Result<DailyLeaderboard, StorageError>;
+}
+
+impl Handler<GetDailyLeaderboard> for GameHandler {
+    type Result = Result<DailyLeaderboard, StorageError>;
+
+    fn handle(&mut self, msg: GetDailyLeaderboard, _ctx: &mut Self::Context) -> Self::Result {
+        let day = msg.day.unwrap_or_else(|| day_of(SystemTime::now()));
+        let results = self.daily_results(day)?;
+
+        Ok(DailyLeaderboard {
+            date: format_date(day),
+            top: rank_results(results.iter().filter(|result| {
+                (msg.include_flagged || !result.is_flagged()) && !result.unranked
+            }))
+            .into_iter()
+            .take(msg.limit)
+            .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        for (day, date) in [
+            (0, "1970-01-01"),
+            (11_016, "2000-02-29"),
+            (19_783, "2024-03-01"),
+        ] {
+            assert_eq!(format_date(day), date);
+            assert_eq!(parse_date(date), Some(day));
+        }
+
+        for date in ["2023-02-29", "2024-13-01", "1969-12-31", "2024-03", "today"] {
+            assert_eq!(parse_date(date), None, "{}", date);
+        }
+    }
+}
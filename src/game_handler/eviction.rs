@@ -0,0 +1,82 @@
+//! Dropping games nobody is coming back to, so that their codes can be handed
+//! out again and storage does not grow without end. Results live on in their
+//! own table.
+
+use std::time::{Duration, SystemTime};
+
+use actix::{AsyncContext, Context};
+
+use super::{GameHandler, WsGame};
+
+/// How long a finished game is kept for its players to look over or rematch
+const FINISHED_GAME_RETENTION: Duration = Duration::from_secs(60 * 60);
+/// How long a game nobody has joined is kept
+const EMPTY_GAME_RETENTION: Duration = Duration::from_secs(60 * 60);
+/// How often games are looked over for eviction
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+impl WsGame {
+    /// When the game was created or a player last joined or finished
+    fn last_activity(&self) -> SystemTime {
+        self.players
+            .values()
+            .flat_map(|player_game| [Some(player_game.start_time), player_game.finished_time])
+            .flatten()
+            .fold(self.created_time, SystemTime::max)
+    }
+
+    /// Whether the game is over, or was never joined, and has been left alone
+    /// for long enough
+    fn is_stale(&self, now: SystemTime) -> bool {
+        let retention = if self.players.is_empty() {
+            EMPTY_GAME_RETENTION
+        } else if self.is_finished() {
+            FINISHED_GAME_RETENTION
+        } else {
+            return false;
+        };
+
+        now.duration_since(self.last_activity())
+            .is_ok_and(|idle| idle >= retention)
+    }
+}
+
+impl GameHandler {
+    pub(super) fn schedule_eviction(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_interval(EVICTION_INTERVAL, |game_handler, _ctx| {
+            game_handler.evict_games(SystemTime::now());
+        });
+    }
+
+    /// Removes stale games that nobody is connected to, from memory and storage
+    pub(super) fn evict_games(&mut self, now: SystemTime) {
+        let stale = self
+            .games
+            .iter()
+            .filter(|(code, game)| {
+                let connected = self
+                    .subscribers
+                    .get(code)
+                    .is_some_and(|subscribers| !subscribers.is_empty());
+                // Entrants may still turn up to a match that has not been decided
+                let undecided_match = game
+                    .tournament
+                    .and_then(|tournament_code| self.tournaments.get(&tournament_code))
+                    .and_then(|tournament| tournament.match_for_game(**code))
+                    .is_some_and(|tournament_match| tournament_match.winner.is_none());
+
+                !connected && !undecided_match && game.is_stale(now)
+            })
+            .map(|(&code, _)| code)
+            .collect::<Vec<u16>>();
+
+        for code in stale {
+            self.games.remove(&code);
+            self.subscribers.remove(&code);
+
+            if let Err(err) = self.storage.delete_game(code) {
+                log::error!("Failed to delete game {:X}: {}", code, err);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use actix::{Handler, Message};
+use serde::{Deserialize, Serialize};
+
+use minesweeper_core::Board;
+
+use super::{GameHandler, RecordedMove};
+
+/// Version of the JSON game record format, bumped on incompatible changes
+pub const GAME_RECORD_VERSION: u32 = 1;
+
+pub struct ExportGame {
+    pub game_code: u16,
+}
+
+#[derive(Debug)]
+pub enum ExportGameError {
+    GameNotFound,
+    GameInProgress,
+}
+
+/// The layout of a game and the moves every player made on it.
+///
+/// Mines are given as cell indices in row-major order and move times are
+/// milliseconds since the player joined.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameRecord {
+    pub version: u32,
+    pub board_size: usize,
+    pub mines: Vec<usize>,
+    #[serde(default)]
+    pub players: Vec<PlayerRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayerRecord {
+    pub name: String,
+    pub moves: Vec<RecordedMove>,
+}
+
+impl GameRecord {
+    /// A record of a layout nobody has played yet
+    pub fn from_board(board: &Board) -> GameRecord {
+        GameRecord {
+            version: GAME_RECORD_VERSION,
+            board_size: board.size(),
+            mines: board.mine_positions(),
+            players: Vec::new(),
+        }
+    }
+
+    /// The unplayed board the record was made on
+    pub fn board(&self) -> Result<Board, minesweeper_core::Error> {
+        Board::from_mines(self.board_size, &self.mines)
+    }
+}
+
+impl Message for ExportGame {
+    type Result = Result<GameRecord, ExportGameError>;
+}
+
+impl Handler<ExportGame> for GameHandler {
+    type Result = Result<GameRecord, ExportGameError>;
+
+    fn handle(&mut self, msg: ExportGame, _ctx: &mut Self::Context) -> Self::Result {
+        let game = self
+            .games
+            .get(&msg.game_code)
+            .ok_or(ExportGameError::GameNotFound)?;
+
+        // Exporting reveals every mine, so only allow it once nobody is still playing
+        if !game.is_finished() || game.is_live_daily() {
+            return Err(ExportGameError::GameInProgress);
+        }
+
+        Ok(GameRecord {
+            version: GAME_RECORD_VERSION,
+            board_size: game.board.size(),
+            mines: game.board.mine_positions(),
+            players: game
+                .players
+                .values()
+                .map(|player_game| PlayerRecord {
+                    name: player_game.name.clone(),
+                    moves: player_game.moves.clone(),
+                })
+                .collect(),
+        })
+    }
+}
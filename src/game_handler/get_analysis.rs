@@ -0,0 +1,84 @@
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use minesweeper_core::SpectatorView;
+
+use super::GameHandler;
+
+/// Mine probabilities on a player's board for post-game analysis.
+///
+/// With `at_move` the board is rebuilt as it was after that many of the
+/// player's moves, otherwise the final board is analysed.
+pub struct GetAnalysis {
+    pub game_code: u16,
+    pub player_name: String,
+    pub at_move: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum GetAnalysisError {
+    GameNotFound,
+    PlayerNotFound,
+    /// Every player shares the layout, so analysis would help anyone still
+    /// playing, including anyone yet to play a daily challenge
+    GameInProgress,
+    MoveOutOfRange,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Analysis {
+    board_size: usize,
+    board_state: SpectatorView,
+    /// Chance of every cell holding a mine, `null` for revealed cells
+    probabilities: Vec<Option<f64>>,
+}
+
+impl Message for GetAnalysis {
+    type Result = Result<Analysis, GetAnalysisError>;
+}
+
+impl Handler<GetAnalysis> for GameHandler {
+    type Result = Result<Analysis, GetAnalysisError>;
+
+    fn handle(&mut self, msg: GetAnalysis, _ctx: &mut Self::Context) -> Self::Result {
+        let game = self
+            .games
+            .get(&msg.game_code)
+            .ok_or(GetAnalysisError::GameNotFound)?;
+
+        let player_game = game
+            .players
+            .values()
+            .find(|player_game| player_game.name == msg.player_name)
+            .ok_or(GetAnalysisError::PlayerNotFound)?;
+
+        if !game.is_finished() || game.is_live_daily() {
+            return Err(GetAnalysisError::GameInProgress);
+        }
+
+        let board = match msg.at_move {
+            None => player_game.board.clone(),
+            Some(at_move) => {
+                let moves = player_game
+                    .moves
+                    .get(..at_move)
+                    .ok_or(GetAnalysisError::MoveOutOfRange)?;
+
+                let mut board = game.board.clone();
+
+                for recorded in moves {
+                    // Moves were valid when they were made so replaying them cannot fail
+                    let _ = board.play(&recorded.action);
+                }
+
+                board
+            }
+        };
+
+        Ok(Analysis {
+            board_size: board.size(),
+            board_state: board.spectator_view(),
+            probabilities: board.mine_probabilities(),
+        })
+    }
+}
@@ -1,11 +1,8 @@
 use std::time;
 
 use actix::{Handler, Message};
-use serde::Serialize;
 
-use crate::game::ExternalCell;
-
-use super::GameHandler;
+use super::{Difficulty, GameHandler, GameStateUpdate, OpponentState, RematchState};
 
 pub struct GetGameState {
     pub game_code: u16,
@@ -18,14 +15,6 @@ pub enum GetGameStateError {
     PlayerNotFound,
 }
 
-#[derive(Serialize, Debug)]
-pub struct GameStateUpdate {
-    board_state: Vec<ExternalCell>,
-    board_size: usize,
-    start_time: Option<u64>,
-    finished_time: Option<u64>,
-}
-
 impl Message for GetGameState {
     type Result = Result<GameStateUpdate, GetGameStateError>;
 }
@@ -34,25 +23,98 @@ impl Handler<GetGameState> for GameHandler {
     type Result = Result<GameStateUpdate, GetGameStateError>;
 
     fn handle(&mut self, msg: GetGameState, _ctx: &mut Self::Context) -> Self::Result {
-        let player_game = self
+        let game = self
             .games
             .get(&msg.game_code)
-            .ok_or(GetGameStateError::GameNotFound)?
+            .ok_or(GetGameStateError::GameNotFound)?;
+
+        let player_game = game
             .players
             .get(&msg.player_code)
             .ok_or(GetGameStateError::PlayerNotFound)?;
 
+        let difficulty = Difficulty::from_board_size(game.config.board_size);
+        // The layout is shared, so a finished opponent's board would show
+        // everyone still playing where the mines are
+        let finished = game.is_finished();
+
+        let mut opponents = game
+            .players
+            .iter()
+            .filter(|(&player_code, _)| player_code != msg.player_code)
+            .map(|(_, opponent)| OpponentState {
+                name: opponent.name.clone(),
+                rating: self.player_rating(opponent, difficulty),
+                board: if finished {
+                    opponent.board.opponent_view()
+                } else {
+                    opponent.board.opponent_progress()
+                },
+            })
+            .collect::<Vec<OpponentState>>();
+        opponents.sort_by(|a, b| a.name.cmp(&b.name));
+
         Ok(GameStateUpdate {
-            board_state: player_game.board.get_external_state(),
+            board_state: player_game.board.player_view(),
             board_size: player_game.board.size(),
+            // Players could otherwise pass today's layout on to those yet to play
+            revealed_board: player_game
+                .board
+                .reveal_view()
+                .filter(|_| !game.is_live_daily()),
+            opponents,
             start_time: Some(
                 player_game
                     .start_time
                     .duration_since(time::UNIX_EPOCH)
-                    .expect("time went backwards")
+                    .unwrap_or_default()
                     .as_secs(),
             ),
-            finished_time: None,
+            finished_time: player_game.finished_time.map(|finished_time| {
+                finished_time
+                    .duration_since(time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            }),
+            stats: player_game.stats(),
+            hint: player_game.last_hint.clone(),
+            hints_remaining: game
+                .config
+                .hint_limit
+                .saturating_sub(player_game.hints_used),
+            deadline: game.player_deadline(msg.player_code).map(|deadline| {
+                deadline
+                    .duration_since(time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            }),
+            standings: game.standings(),
+            rematch: game.rematch.as_ref().map(|proposal| {
+                let mut accepted = proposal
+                    .accepted
+                    .iter()
+                    .filter_map(|player_code| game.players.get(player_code))
+                    .map(|player_game| player_game.name.clone())
+                    .collect::<Vec<String>>();
+                accepted.sort();
+
+                RematchState {
+                    same_board: proposal.same_board,
+                    accepted,
+                }
+            }),
+            tournament: game
+                .tournament
+                .and_then(|tournament_code| self.tournaments.get(&tournament_code))
+                .map(|tournament| tournament.state()),
+            host: game
+                .players
+                .iter()
+                .find(|(&player_code, _)| game.is_host(player_code))
+                .map(|(_, host)| host.name.clone()),
+            is_host: game.is_host(msg.player_code),
+            locked: game.locked,
+            started: !game.waiting,
         })
     }
 }
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use actix::{Handler, Message};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::StorageError;
+
+use super::{GameHandler, GameResult, GameStats, PlayerId};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    /// Classifies a board by its size, following the classic 9x9 and 16x16 boards
+    pub fn from_board_size(board_size: usize) -> Difficulty {
+        match board_size {
+            0..=9 => Difficulty::Beginner,
+            10..=16 => Difficulty::Intermediate,
+            _ => Difficulty::Expert,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    #[default]
+    All,
+}
+
+impl Period {
+    fn duration(self) -> Option<Duration> {
+        const DAY: u64 = 24 * 60 * 60;
+
+        match self {
+            Period::Day => Some(Duration::from_secs(DAY)),
+            Period::Week => Some(Duration::from_secs(7 * DAY)),
+            Period::Month => Some(Duration::from_secs(30 * DAY)),
+            Period::All => None,
+        }
+    }
+}
+
+/// Ranks won games of a difficulty by completion time, keeping only the best
+/// time of every player
+pub struct GetLeaderboard {
+    pub difficulty: Difficulty,
+    pub period: Period,
+    pub limit: usize,
+    /// Player whose personal best should be reported alongside the top times
+    pub player_name: Option<String>,
+    /// Identity whose personal best should be reported, taking precedence over the name
+    pub player_id: Option<PlayerId>,
+    /// Whether results flagged as suspicious should be ranked as well
+    pub include_flagged: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub player_name: String,
+    pub player_id: Option<PlayerId>,
+    pub time_ms: u64,
+    pub board_size: usize,
+    pub mines: usize,
+    pub finished_time: u64,
+    pub stats: GameStats,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Leaderboard {
+    pub difficulty: Difficulty,
+    pub top: Vec<LeaderboardEntry>,
+    pub personal_best: Option<LeaderboardEntry>,
+}
+
+impl Message for GetLeaderboard {
+    type Result = Result<Leaderboard, StorageError>;
+}
+
+impl GameResult {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_board_size(self.board_size)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.finished_time
+            .duration_since(self.start_time)
+            .unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> GameStats {
+        GameStats::new(
+            self.bbbv,
+            self.openings,
+            self.islands,
+            self.clicks,
+            self.duration(),
+        )
+    }
+}
+
+/// Who a result belongs to: players with an identity are told apart by it,
+/// anyone else only by their name
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum PlayerKey<'a> {
+    Identity(&'a PlayerId),
+    Name(&'a str),
+}
+
+impl GameResult {
+    fn player_key(&self) -> PlayerKey<'_> {
+        match &self.player_id {
+            Some(player_id) => PlayerKey::Identity(player_id),
+            None => PlayerKey::Name(&self.player_name),
+        }
+    }
+}
+
+/// Orders won results by completion time and ranks each player's best one
+pub(super) fn rank_results<'a>(
+    results: impl Iterator<Item = &'a GameResult>,
+) -> Vec<LeaderboardEntry> {
+    let mut best: HashMap<PlayerKey, &GameResult> = HashMap::new();
+
+    for result in results.filter(|result| result.won) {
+        best.entry(result.player_key())
+            .and_modify(|current| {
+                if result.duration() < current.duration() {
+                    *current = result;
+                }
+            })
+            .or_insert(result);
+    }
+
+    let mut best = best.into_values().collect::<Vec<&GameResult>>();
+    best.sort_by_key(|result| (result.duration(), result.finished_time));
+
+    best.into_iter()
+        .enumerate()
+        .map(|(index, result)| LeaderboardEntry {
+            rank: index + 1,
+            player_name: result.player_name.clone(),
+            player_id: result.player_id.clone(),
+            time_ms: result.duration().as_millis() as u64,
+            board_size: result.board_size,
+            mines: result.mines,
+            finished_time: result
+                .finished_time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("time went backwards")
+                .as_secs(),
+            stats: result.stats(),
+        })
+        .collect()
+}
+
+impl Handler<GetLeaderboard> for GameHandler {
+    type Result = Result<Leaderboard, StorageError>;
+
+    fn handle(&mut self, msg: GetLeaderboard, _ctx: &mut Self::Context) -> Self::Result {
+        let results = self.storage.load_results()?;
+
+        let since = msg
+            .period
+            .duration()
+            .and_then(|duration| SystemTime::now().checked_sub(duration));
+
+        let ranked = rank_results(results.iter().filter(|result| {
+            result.difficulty() == msg.difficulty
+                && (msg.include_flagged || !result.is_flagged())
+                && !result.unranked
+                && since.is_none_or(|since| result.finished_time >= since)
+        }));
+
+        let personal_best = match (msg.player_id, msg.player_name) {
+            (Some(player_id), _) => ranked
+                .iter()
+                .find(|entry| entry.player_id.as_ref() == Some(&player_id))
+                .cloned(),
+            (None, Some(player_name)) => ranked
+                .iter()
+                .find(|entry| entry.player_id.is_none() && entry.player_name == player_name)
+                .cloned(),
+            (None, None) => None,
+        };
+
+        Ok(Leaderboard {
+            difficulty: msg.difficulty,
+            top: ranked.into_iter().take(msg.limit).collect(),
+            personal_best,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn result(player_name: &str, seconds: u64, won: bool) -> GameResult {
+        let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+
+        GameResult {
+            game_code: 1,
+            player_name: player_name.to_string(),
+            player_id: None,
+            board_size: 9,
+            mines: 10,
+            won,
+            start_time,
+            finished_time: start_time + Duration::from_secs(seconds),
+            bbbv: 20,
+            openings: 3,
+            islands: 2,
+            clicks: 25,
+            suspicions: Vec::new(),
+            daily: None,
+            solo: false,
+            unranked: false,
+        }
+    }
+
+    #[test]
+    fn test_rank_keeps_best_time_per_player() {
+        let results = [
+            result("alice", 30, true),
+            result("bob", 20, true),
+            result("alice", 10, true),
+            result("carol", 5, false),
+        ];
+
+        let ranked = rank_results(results.iter());
+
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|entry| (entry.rank, entry.player_name.as_str(), entry.time_ms))
+                .collect::<Vec<_>>(),
+            vec![(1, "alice", 10_000), (2, "bob", 20_000)]
+        );
+    }
+
+    #[test]
+    fn test_rank_tells_identities_apart() {
+        let mut results = [
+            result("alice", 30, true),
+            result("alice", 10, true),
+            result("alice", 20, true),
+        ];
+        results[0].player_id = Some(PlayerId::new("a".to_string()));
+        results[1].player_id = Some(PlayerId::new("b".to_string()));
+
+        let ranked = rank_results(results.iter());
+
+        assert_eq!(
+            ranked.iter().map(|entry| entry.time_ms).collect::<Vec<_>>(),
+            vec![10_000, 20_000, 30_000]
+        );
+    }
+}
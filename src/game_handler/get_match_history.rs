@@ -0,0 +1,89 @@
+use std::time::UNIX_EPOCH;
+
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use crate::storage::StorageError;
+
+use super::{Difficulty, GameHandler, GameResult, GameStats, PlayerRef};
+
+/// A page of a player's finished games, newest first
+pub struct GetMatchHistory {
+    pub player: PlayerRef,
+    /// Number of the page, starting at 1
+    pub page: usize,
+    pub per_page: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MatchHistory {
+    page: usize,
+    per_page: usize,
+    /// Number of games in the whole history
+    total: usize,
+    entries: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HistoryEntry {
+    code: String,
+    board_size: usize,
+    difficulty: Difficulty,
+    won: bool,
+    finished_time: u64,
+    duration_ms: u64,
+    stats: GameStats,
+    /// Everyone else who played the same game
+    opponents: Vec<String>,
+}
+
+impl Message for GetMatchHistory {
+    type Result = Result<MatchHistory, StorageError>;
+}
+
+impl Handler<GetMatchHistory> for GameHandler {
+    type Result = Result<MatchHistory, StorageError>;
+
+    fn handle(&mut self, msg: GetMatchHistory, _ctx: &mut Self::Context) -> Self::Result {
+        let results = self.storage.load_results()?;
+
+        let mut history = results
+            .iter()
+            .filter(|result| result.belongs_to(&msg.player))
+            .collect::<Vec<&GameResult>>();
+        history.sort_by_key(|result| std::cmp::Reverse(result.finished_time));
+
+        let entries = history
+            .iter()
+            .skip(msg.page.saturating_sub(1).saturating_mul(msg.per_page))
+            .take(msg.per_page)
+            .map(|result| HistoryEntry {
+                code: format!("{:X}", result.game_code),
+                board_size: result.board_size,
+                difficulty: result.difficulty(),
+                won: result.won,
+                finished_time: result
+                    .finished_time
+                    .duration_since(UNIX_EPOCH)
+                    .expect("time went backwards")
+                    .as_secs(),
+                duration_ms: result.duration().as_millis() as u64,
+                stats: result.stats(),
+                opponents: results
+                    .iter()
+                    .filter(|other| {
+                        other.game_code == result.game_code && !other.belongs_to(&msg.player)
+                    })
+                    .map(|other| other.player_name.clone())
+                    .collect(),
+            })
+            .collect();
+
+        Ok(MatchHistory {
+            page: msg.page,
+            per_page: msg.per_page,
+            total: history.len(),
+            entries,
+        })
+    }
+}
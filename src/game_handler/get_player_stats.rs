@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use crate::storage::StorageError;
+
+use super::{Difficulty, GameHandler, GameResult, PlayerId};
+
+/// Whose results to look at. Players without an identity can only be told
+/// apart by name, so `Name` only matches results recorded without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerRef {
+    Id(PlayerId),
+    Name(String),
+}
+
+impl GameResult {
+    pub fn belongs_to(&self, player: &PlayerRef) -> bool {
+        match (player, &self.player_id) {
+            (PlayerRef::Id(player_id), Some(result_id)) => player_id == result_id,
+            (PlayerRef::Name(player_name), None) => *player_name == self.player_name,
+            _ => false,
+        }
+    }
+}
+
+/// Aggregates over every ranked result of a player
+pub struct GetPlayerStats {
+    pub player: PlayerRef,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PlayerStats {
+    pub games_played: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    /// Fastest win per difficulty, in milliseconds
+    pub best_times: BTreeMap<Difficulty, u64>,
+    /// Mean 3BV/s over won games
+    pub average_bbbv_per_second: Option<f64>,
+    pub longest_win_streak: usize,
+}
+
+impl Message for GetPlayerStats {
+    type Result = Result<PlayerStats, StorageError>;
+}
+
+impl PlayerStats {
+    /// Results flagged as suspicious count as games played but not towards
+    /// best times or speed
+    fn from_results(results: &[&GameResult]) -> PlayerStats {
+        let mut results = results.to_vec();
+        results.sort_by_key(|result| result.finished_time);
+
+        let wins = results.iter().filter(|result| result.won).count();
+
+        let mut best_times = BTreeMap::new();
+        let mut speeds = Vec::new();
+
+        for result in results
+            .iter()
+            .filter(|result| result.won && !result.is_flagged() && !result.unranked)
+        {
+            let time_ms = result.duration().as_millis() as u64;
+
+            best_times
+                .entry(result.difficulty())
+                .and_modify(|best: &mut u64| *best = (*best).min(time_ms))
+                .or_insert(time_ms);
+            speeds.push(result.stats().bbbv_per_second);
+        }
+
+        let mut longest_win_streak = 0;
+        let mut streak = 0;
+        for result in &results {
+            streak = if result.won { streak + 1 } else { 0 };
+            longest_win_streak = longest_win_streak.max(streak);
+        }
+
+        PlayerStats {
+            games_played: results.len(),
+            wins,
+            win_rate: if results.is_empty() {
+                0.0
+            } else {
+                wins as f64 / results.len() as f64
+            },
+            best_times,
+            average_bbbv_per_second: (!speeds.is_empty())
+                .then(|| speeds.iter().sum::<f64>() / speeds.len() as f64),
+            longest_win_streak,
+        }
+    }
+}
+
+impl Handler<GetPlayerStats> for GameHandler {
+    type Result = Result<PlayerStats, StorageError>;
+
+    fn handle(&mut self, msg: GetPlayerStats, _ctx: &mut Self::Context) -> Self::Result {
+        let results = self.storage.load_results()?;
+
+        Ok(PlayerStats::from_results(
+            &results
+                .iter()
+                .filter(|result| result.belongs_to(&msg.player) && !result.unranked)
+                .collect::<Vec<&GameResult>>(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn result(finished_secs: u64, seconds: u64, won: bool) -> GameResult {
+        let finished_time = SystemTime::UNIX_EPOCH + Duration::from_secs(finished_secs);
+
+        GameResult {
+            game_code: 1,
+            player_name: "alice".to_string(),
+            player_id: None,
+            board_size: 9,
+            mines: 10,
+            won,
+            start_time: finished_time - Duration::from_secs(seconds),
+            finished_time,
+            bbbv: 20,
+            openings: 3,
+            islands: 2,
+            clicks: 25,
+            suspicions: Vec::new(),
+            daily: None,
+            solo: false,
+            unranked: false,
+        }
+    }
+
+    #[test]
+    fn test_player_stats() {
+        // Listed out of order to check the streak follows finish times
+        let results = [
+            result(400, 10, true),
+            result(100, 40, true),
+            result(200, 20, true),
+            result(300, 30, false),
+            result(500, 20, true),
+            result(600, 5, false),
+        ];
+
+        let stats = PlayerStats::from_results(&results.iter().collect::<Vec<&GameResult>>());
+
+        assert_eq!(stats.games_played, 6);
+        assert_eq!(stats.wins, 4);
+        assert_eq!(stats.longest_win_streak, 2);
+        assert_eq!(stats.best_times[&Difficulty::Beginner], 10_000);
+        assert!((stats.average_bbbv_per_second.unwrap() - 1.125).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,63 @@
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use crate::storage::StorageError;
+
+use super::{Difficulty, GameHandler, PlayerId, RatingChange};
+
+/// Current ratings of a player along with how they got there, optionally for
+/// a single difficulty
+pub struct GetRatings {
+    pub player_id: PlayerId,
+    pub difficulty: Option<Difficulty>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RatingSummary {
+    pub difficulty: Difficulty,
+    pub rating: f64,
+    pub games: usize,
+    pub provisional: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PlayerRatings {
+    /// One entry per difficulty played, unrated difficulties are left out
+    pub ratings: Vec<RatingSummary>,
+    /// Every change of the ratings, oldest first
+    pub history: Vec<RatingChange>,
+}
+
+impl Message for GetRatings {
+    type Result = Result<PlayerRatings, StorageError>;
+}
+
+impl Handler<GetRatings> for GameHandler {
+    type Result = Result<PlayerRatings, StorageError>;
+
+    fn handle(&mut self, msg: GetRatings, _ctx: &mut Self::Context) -> Self::Result {
+        let included = |difficulty: Difficulty| msg.difficulty.is_none_or(|d| d == difficulty);
+
+        let mut ratings = self
+            .ratings
+            .values()
+            .filter(|rating| rating.player_id == msg.player_id && included(rating.difficulty))
+            .map(|rating| RatingSummary {
+                difficulty: rating.difficulty,
+                rating: rating.rating,
+                games: rating.games,
+                provisional: rating.is_provisional(),
+            })
+            .collect::<Vec<RatingSummary>>();
+        ratings.sort_by_key(|summary| summary.difficulty);
+
+        let history = self
+            .storage
+            .load_rating_history(&msg.player_id)?
+            .into_iter()
+            .filter(|change| included(change.difficulty))
+            .collect();
+
+        Ok(PlayerRatings { ratings, history })
+    }
+}
@@ -0,0 +1,27 @@
+use actix::{Handler, Message};
+
+use crate::storage::StorageError;
+
+use super::{GameHandler, GameResult};
+
+/// Fetches recorded results, optionally only those of a single game
+pub struct GetResults {
+    pub game_code: Option<u16>,
+}
+
+impl Message for GetResults {
+    type Result = Result<Vec<GameResult>, StorageError>;
+}
+
+impl Handler<GetResults> for GameHandler {
+    type Result = Result<Vec<GameResult>, StorageError>;
+
+    fn handle(&mut self, msg: GetResults, _ctx: &mut Self::Context) -> Self::Result {
+        let results = self.storage.load_results()?;
+
+        Ok(results
+            .into_iter()
+            .filter(|result| msg.game_code.is_none_or(|code| result.game_code == code))
+            .collect())
+    }
+}
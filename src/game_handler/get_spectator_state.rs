@@ -0,0 +1,71 @@
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use minesweeper_core::{RevealView, SpectatorView};
+
+use super::GameHandler;
+
+/// Every player's board in a game, as shown to someone watching it
+pub struct GetSpectatorState {
+    pub game_code: u16,
+}
+
+#[derive(Debug)]
+pub enum GetSpectatorStateError {
+    GameNotFound,
+    /// The game is an attempt at a daily challenge others may still be playing
+    DailyInProgress,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SpectatorState {
+    board_size: usize,
+    players: Vec<SpectatedPlayer>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SpectatedPlayer {
+    name: String,
+    board_state: SpectatorView,
+    /// Full layout of the board, only sent once every player has finished
+    revealed_board: Option<RevealView>,
+}
+
+impl Message for GetSpectatorState {
+    type Result = Result<SpectatorState, GetSpectatorStateError>;
+}
+
+impl Handler<GetSpectatorState> for GameHandler {
+    type Result = Result<SpectatorState, GetSpectatorStateError>;
+
+    fn handle(&mut self, msg: GetSpectatorState, _ctx: &mut Self::Context) -> Self::Result {
+        let game = self
+            .games
+            .get(&msg.game_code)
+            .ok_or(GetSpectatorStateError::GameNotFound)?;
+
+        if game.is_live_daily() {
+            return Err(GetSpectatorStateError::DailyInProgress);
+        }
+
+        // The layout is shared, so one player's reveal would show everyone
+        // still playing where the mines are
+        let finished = game.is_finished();
+
+        let mut players = game
+            .players
+            .values()
+            .map(|player_game| SpectatedPlayer {
+                name: player_game.name.clone(),
+                board_state: player_game.board.spectator_view(),
+                revealed_board: player_game.board.reveal_view().filter(|_| finished),
+            })
+            .collect::<Vec<SpectatedPlayer>>();
+        players.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(SpectatorState {
+            board_size: game.board.size(),
+            players,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use actix::{Handler, Message};
+
+use super::{GameHandler, MatchState, Tournament, TournamentStanding, TournamentState};
+
+pub struct GetTournament {
+    pub tournament_code: u16,
+}
+
+#[derive(Debug)]
+pub enum GetTournamentError {
+    TournamentNotFound,
+}
+
+impl Message for GetTournament {
+    type Result = Result<TournamentState, GetTournamentError>;
+}
+
+impl Tournament {
+    pub(super) fn state(&self) -> TournamentState {
+        TournamentState {
+            format: self.format,
+            round: self.round,
+            champion: self.champion.clone(),
+            standings: self
+                .ranking()
+                .into_iter()
+                .enumerate()
+                .map(|(index, (name, points))| TournamentStanding {
+                    rank: index + 1,
+                    name: name.to_string(),
+                    points,
+                })
+                .collect(),
+            matches: self
+                .matches
+                .iter()
+                .map(|tournament_match| MatchState {
+                    round: tournament_match.round,
+                    game_code: tournament_match
+                        .game_code
+                        .map(|game_code| format!("{:X}", game_code)),
+                    players: tournament_match.players.clone(),
+                    winner: tournament_match.winner.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Handler<GetTournament> for GameHandler {
+    type Result = Result<TournamentState, GetTournamentError>;
+
+    fn handle(&mut self, msg: GetTournament, _ctx: &mut Self::Context) -> Self::Result {
+        self.tournaments
+            .get(&msg.tournament_code)
+            .map(Tournament::state)
+            .ok_or(GetTournamentError::TournamentNotFound)
+    }
+}
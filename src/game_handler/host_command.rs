@@ -0,0 +1,223 @@
+use std::time::SystemTime;
+
+use actix::{Handler, Message};
+use serde::{Deserialize, Serialize};
+
+use minesweeper_core::Board;
+
+use super::{GameHandler, HostAction, WsGame};
+
+/// Control over a game created through `create-game`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Host {
+    /// Secret handed to the creator, which makes whoever joins with it the host
+    token: String,
+    /// Player currently holding the host's rights
+    player_code: Option<u16>,
+}
+
+impl Default for Host {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Host {
+    pub fn new() -> Host {
+        Host {
+            token: format!("{:032x}", rand::random::<u128>()),
+            player_code: None,
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// An action only the host of a game may take
+pub struct HostCommand {
+    pub game_code: u16,
+    pub player_code: u16,
+    pub action: HostAction,
+}
+
+#[derive(Debug)]
+pub enum HostError {
+    NoSuchGame,
+    NotHost,
+    NoSuchPlayer,
+    CannotKickSelf,
+    AlreadyStarted,
+    InvalidConfig,
+}
+
+impl Message for HostCommand {
+    type Result = Result<(), HostError>;
+}
+
+impl WsGame {
+    pub fn is_host(&self, player_code: u16) -> bool {
+        self.host
+            .as_ref()
+            .is_some_and(|host| host.player_code == Some(player_code))
+    }
+
+    /// Makes the player the host if the token is the game's host token
+    pub(super) fn claim_host(&mut self, player_code: u16, token: &str) -> bool {
+        match &mut self.host {
+            Some(host) if host.token == token => {
+                host.player_code = Some(player_code);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn player_code_by_name(&self, player_name: &str) -> Option<u16> {
+        self.players
+            .iter()
+            .find(|(_, player_game)| player_game.name == player_name)
+            .map(|(&player_code, _)| player_code)
+    }
+
+    /// Everyone's clock starts when the host starts the game
+    fn start(&mut self, now: SystemTime) {
+        self.waiting = false;
+        self.start_blitz_clock(now);
+
+        for player_game in self.players.values_mut() {
+            player_game.start_time = now;
+        }
+    }
+}
+
+impl Handler<HostCommand> for GameHandler {
+    type Result = Result<(), HostError>;
+
+    fn handle(&mut self, msg: HostCommand, ctx: &mut Self::Context) -> Self::Result {
+        let game = self
+            .games
+            .get_mut(&msg.game_code)
+            .ok_or(HostError::NoSuchGame)?;
+
+        if !game.is_host(msg.player_code) {
+            return Err(HostError::NotHost);
+        }
+
+        match msg.action {
+            HostAction::Kick { player_name } => {
+                let player_code = game
+                    .player_code_by_name(&player_name)
+                    .ok_or(HostError::NoSuchPlayer)?;
+
+                if player_code == msg.player_code {
+                    return Err(HostError::CannotKickSelf);
+                }
+
+                // The kicked player's socket closes once it can no longer find them
+                game.players.remove(&player_code);
+            }
+
+            HostAction::Lock => game.locked = true,
+            HostAction::Unlock => game.locked = false,
+
+            HostAction::Configure {
+                board_size,
+                player_limit,
+                hint_limit,
+                hint_penalty_secs,
+                clock,
+                visibility,
+            } => {
+                if !game.waiting {
+                    return Err(HostError::AlreadyStarted);
+                }
+
+                let mut config = game.config.clone();
+                config.board_size = board_size.unwrap_or(config.board_size);
+                config.player_limit = player_limit.unwrap_or(config.player_limit);
+                config.hint_limit = hint_limit.unwrap_or(config.hint_limit);
+                config.hint_penalty_secs = hint_penalty_secs.unwrap_or(config.hint_penalty_secs);
+                config.clock = clock.unwrap_or(config.clock);
+                config.visibility = visibility.unwrap_or(config.visibility);
+
+                if !self.limits.allow(&config) || config.player_limit < game.players.len() {
+                    return Err(HostError::InvalidConfig);
+                }
+
+                if config.board_size != game.config.board_size {
+                    game.board = Board::generate(config.board_size);
+
+                    for player_game in game.players.values_mut() {
+                        player_game.board = game.board.clone();
+                    }
+                }
+
+                game.config = config;
+            }
+
+            HostAction::Start => {
+                if !game.waiting {
+                    return Err(HostError::AlreadyStarted);
+                }
+
+                game.start(SystemTime::now());
+
+                let player_codes = game.players.keys().copied().collect::<Vec<u16>>();
+                for player_code in player_codes {
+                    self.schedule_expiry(ctx, msg.game_code, player_code);
+                }
+            }
+
+            HostAction::TransferHost { player_name } => {
+                let player_code = game
+                    .player_code_by_name(&player_name)
+                    .ok_or(HostError::NoSuchPlayer)?;
+
+                if let Some(host) = &mut game.host {
+                    host.player_code = Some(player_code);
+                }
+            }
+        }
+
+        self.persist_game(msg.game_code);
+        self.notify_game(msg.game_code, Some(msg.player_code));
+
+        Ok(())
+    }
+}
+
+impl GameHandler {
+    /// Hands the host's rights to someone else once the host disconnects,
+    /// preferring players who are still connected
+    pub(super) fn replace_host(&mut self, game_code: u16, player_code: u16) {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return;
+        };
+
+        if !game.is_host(player_code) {
+            return;
+        }
+
+        let connected = self.subscribers.get(&game_code);
+        let mut candidates = game
+            .players
+            .iter()
+            .filter(|(&candidate, _)| candidate != player_code)
+            .map(|(&candidate, player_game)| {
+                let is_connected =
+                    connected.is_some_and(|connected| connected.contains_key(&candidate));
+                (!is_connected, player_game.name.clone(), candidate)
+            })
+            .collect::<Vec<(bool, String, u16)>>();
+        candidates.sort();
+
+        if let Some(host) = &mut game.host {
+            host.player_code = candidates.first().map(|&(_, _, candidate)| candidate);
+        }
+
+        self.persist_game(game_code);
+        self.notify_game(game_code, None);
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::SystemTime;
+
+use actix::{Handler, Message};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::StorageError;
+
+use super::{password, GameHandler};
+
+/// Public identifier of an anonymous player, shown next to their results
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct PlayerId(String);
+
+impl PlayerId {
+    pub fn new(id: String) -> PlayerId {
+        PlayerId(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// An anonymous account. The token is a secret kept by the player's client
+/// and given when joining games, so that their results can be told apart from
+/// those of other players using the same name. The server only keeps the
+/// token's [`hash_token`](super::hash_token).
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub player_id: PlayerId,
+    pub token: String,
+    #[serde(skip)]
+    pub created_time: SystemTime,
+}
+
+pub struct CreateIdentity;
+
+/// A token sent to tell who is making a request did not check out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerTokenError {
+    UnknownIdentity,
+    /// The session token is unknown or has been logged out
+    InvalidSession,
+}
+
+impl GameHandler {
+    /// The player an identity token was handed out for
+    pub(super) fn identity_player(&self, token: &str) -> Option<&PlayerId> {
+        self.identities.get(&password::hash_token(token))
+    }
+
+    /// Player making a request, along with the name they play under when they
+    /// are logged in. A session wins over an identity when both are given.
+    pub(super) fn resolve_player(
+        &self,
+        session_token: Option<&str>,
+        identity_token: Option<&str>,
+    ) -> Result<Option<(PlayerId, Option<String>)>, PlayerTokenError> {
+        match (session_token, identity_token) {
+            (Some(token), _) => {
+                let account = self
+                    .session_account(token)
+                    .ok_or(PlayerTokenError::InvalidSession)?;
+
+                Ok(Some((
+                    account.player_id.clone(),
+                    Some(account.username.clone()),
+                )))
+            }
+            (None, Some(token)) => {
+                let player_id = self
+                    .identity_player(token)
+                    .cloned()
+                    .ok_or(PlayerTokenError::UnknownIdentity)?;
+
+                Ok(Some((player_id, None)))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+impl Message for CreateIdentity {
+    type Result = Result<Identity, StorageError>;
+}
+
+impl Handler<CreateIdentity> for GameHandler {
+    type Result = Result<Identity, StorageError>;
+
+    fn handle(&mut self, _msg: CreateIdentity, _ctx: &mut Self::Context) -> Self::Result {
+        let identity = Identity {
+            player_id: PlayerId(format!("{:016x}", rand::random::<u64>())),
+            token: format!("{:032x}", rand::random::<u128>()),
+            created_time: SystemTime::now(),
+        };
+
+        let token_hash = password::hash_token(&identity.token);
+
+        self.storage.save_identity(&Identity {
+            token: token_hash.clone(),
+            ..identity.clone()
+        })?;
+        self.identities
+            .insert(token_hash, identity.player_id.clone());
+
+        Ok(identity)
+    }
+}
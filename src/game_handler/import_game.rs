@@ -0,0 +1,48 @@
+use actix::{Handler, Message};
+
+use minesweeper_core::Board;
+
+use super::{
+    Clock, CreateGameError, GameConfig, GameHandler, Visibility, WsGame, DEFAULT_HINT_LIMIT,
+    DEFAULT_HINT_PENALTY_SECS,
+};
+
+/// Creates a new game on an existing board layout
+pub struct ImportGame {
+    pub board: Board,
+    pub player_limit: usize,
+}
+
+impl Message for ImportGame {
+    type Result = Result<u16, CreateGameError>;
+}
+
+impl Handler<ImportGame> for GameHandler {
+    type Result = Result<u16, CreateGameError>;
+
+    fn handle(&mut self, msg: ImportGame, _ctx: &mut Self::Context) -> Self::Result {
+        let config = GameConfig {
+            board_size: msg.board.size(),
+            player_limit: msg.player_limit,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Private,
+        };
+
+        if !self.limits.allow(&config) {
+            return Err(CreateGameError::OutsideLimits);
+        }
+
+        let code = self.new_game_code()?;
+
+        let mut new_game = WsGame::with_board(config, msg.board);
+        // Whoever imported the board already knows where its mines are
+        new_game.unranked = true;
+
+        self.games.insert(code, new_game);
+        self.persist_game(code);
+
+        Ok(code)
+    }
+}
@@ -3,11 +3,15 @@ use std::time::SystemTime;
 use actix::{Handler, Message};
 use rand::Rng;
 
-use super::{GameHandler, WsPlayerGame};
+use super::{player_name, GameHandler, NameError, PlayerTokenError, WsPlayerGame};
 
 pub struct JoinGame {
     game_code: u16,
     player_name: String,
+    host_token: Option<String>,
+    verified_password_hash: Option<String>,
+    identity_token: Option<String>,
+    session_token: Option<String>,
 }
 
 impl JoinGame {
@@ -15,48 +19,211 @@ impl JoinGame {
         JoinGame {
             game_code,
             player_name,
+            host_token: None,
+            verified_password_hash: None,
+            identity_token: None,
+            session_token: None,
         }
     }
+
+    /// Joins as the host of the game
+    pub fn with_host_token(mut self, host_token: String) -> JoinGame {
+        self.host_token = Some(host_token);
+        self
+    }
+
+    /// Joins a game with a password, once the password given has been
+    /// verified against the hash from [`GetPasswordHash`]
+    pub fn with_verified_password(mut self, password_hash: String) -> JoinGame {
+        self.verified_password_hash = Some(password_hash);
+        self
+    }
+
+    /// Joins as the anonymous account the token was handed out for
+    pub fn with_identity_token(mut self, identity_token: String) -> JoinGame {
+        self.identity_token = Some(identity_token);
+        self
+    }
+
+    /// Joins as a logged in user, playing under their username
+    pub fn with_session_token(mut self, session_token: String) -> JoinGame {
+        self.session_token = Some(session_token);
+        self
+    }
+}
+
+/// Hash of the password a game was created with, if any
+pub struct GetPasswordHash {
+    pub game_code: u16,
 }
 
 pub enum JoinGameError {
     GameNotFound,
     GameFull,
+    /// The shared deadline of a blitz game has already passed
+    DeadlinePassed,
+    /// A player has finished the board, and with it seen its layout
+    AlreadyFinished,
+    /// The game is a tournament match the player is not part of
+    NotInMatch,
+    /// The player's identity or account is already playing the game
+    AlreadyJoined,
+    /// The host has locked the lobby
+    Locked,
+    InvalidHostToken,
+    /// The game has a password and it was missing or wrong
+    WrongPassword,
+    InvalidName(NameError),
+    Token(PlayerTokenError),
+}
+
+impl Message for GetPasswordHash {
+    type Result = Option<String>;
 }
 
 impl Message for JoinGame {
     type Result = Result<u16, JoinGameError>;
 }
 
+impl Handler<GetPasswordHash> for GameHandler {
+    type Result = Option<String>;
+
+    fn handle(&mut self, msg: GetPasswordHash, _ctx: &mut Self::Context) -> Self::Result {
+        self.games
+            .get(&msg.game_code)
+            .and_then(|game| game.password_hash.clone())
+    }
+}
+
 impl Handler<JoinGame> for GameHandler {
     type Result = Result<u16, JoinGameError>;
 
-    fn handle(&mut self, join_game: JoinGame, _ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, join_game: JoinGame, ctx: &mut Self::Context) -> Self::Result {
+        let player = self
+            .resolve_player(
+                join_game.session_token.as_deref(),
+                join_game.identity_token.as_deref(),
+            )
+            .map_err(JoinGameError::Token)?;
+        let (player_id, username) = match player {
+            Some((player_id, username)) => (Some(player_id), username),
+            None => (None, None),
+        };
+
+        let player_name = match username {
+            Some(username) => username,
+            None => self
+                .anonymous_name(&join_game.player_name)
+                .map_err(JoinGameError::InvalidName)?,
+        };
+
         let game = self
             .games
             .get_mut(&join_game.game_code)
             .ok_or(JoinGameError::GameNotFound)?;
 
+        let is_host = match &join_game.host_token {
+            Some(token) if game.host.as_ref().is_some_and(|host| host.token() == token) => true,
+            Some(_) => return Err(JoinGameError::InvalidHostToken),
+            None => false,
+        };
+
+        // The host token is the stronger secret, so the host skips the password
+        if let Some(password_hash) = &game.password_hash {
+            let password_matches = join_game.verified_password_hash.as_ref() == Some(password_hash);
+
+            if !is_host && !password_matches {
+                return Err(JoinGameError::WrongPassword);
+            }
+        }
+
+        if game.locked && !is_host {
+            return Err(JoinGameError::Locked);
+        }
+
         if game.players.len() >= game.config.player_limit {
             return Err(JoinGameError::GameFull);
         }
 
+        // Whoever finished could otherwise come back under another name and
+        // play a layout they already know. Tournament matches only seat their
+        // entrants, who may join after their opponent is done.
+        let finished_by_someone = game
+            .players
+            .values()
+            .any(|player_game| player_game.finished_time.is_some());
+
+        if finished_by_someone && game.tournament.is_none() {
+            return Err(JoinGameError::AlreadyFinished);
+        }
+
+        // Otherwise the same player could take several places in a race
+        let already_joined = player_id.is_some()
+            && game
+                .players
+                .values()
+                .any(|player_game| player_game.player_id == player_id);
+
+        if already_joined {
+            return Err(JoinGameError::AlreadyJoined);
+        }
+
+        // Entrants play a match logged in to their own account
+        let player_name = if let Some(tournament_code) = game.tournament {
+            let entrant = self
+                .tournaments
+                .get(&tournament_code)
+                .zip(player_id.as_ref())
+                .and_then(|(tournament, player_id)| {
+                    tournament.entrant_in_game(join_game.game_code, player_id)
+                })
+                .ok_or(JoinGameError::NotInMatch)?;
+
+            entrant.to_string()
+        } else {
+            player_name::unique_name(
+                &player_name,
+                game.players
+                    .values()
+                    .map(|player_game| player_game.name.as_str()),
+            )
+        };
+
+        let now = SystemTime::now();
+
+        game.start_blitz_clock(now);
+        if game.deadline.is_some_and(|deadline| deadline <= now) {
+            return Err(JoinGameError::DeadlinePassed);
+        }
+
         let player_code = (0..0xff)
             .map(|_| rand::thread_rng().gen_range(0u16..0xffffu16))
-            .filter(|code| !game.players.contains_key(&code))
-            .next()
+            .find(|code| !game.players.contains_key(code))
             .expect("generate a random code not already present");
 
         game.players.insert(
             player_code,
             WsPlayerGame {
                 board: game.board.clone(),
-                name: join_game.player_name,
-                start_time: SystemTime::now(),
+                name: player_name,
+                start_time: now,
                 finished_time: None,
+                moves: Vec::new(),
+                hints_used: 0,
+                last_hint: None,
+                player_id,
+                left: false,
             },
         );
 
+        if let Some(token) = &join_game.host_token {
+            game.claim_host(player_code, token);
+        }
+
+        self.persist_game(join_game.game_code);
+        self.schedule_expiry(ctx, join_game.game_code, player_code);
+        self.notify_game(join_game.game_code, Some(player_code));
+
         Ok(player_code)
     }
 }
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Clock, GameConfig};
+
+/// Bounds on the games players can set up, and how often the handler does its
+/// periodic work
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameLimits {
+    pub max_board_size: usize,
+    pub max_player_limit: usize,
+    pub max_hint_limit: usize,
+    /// Most seconds a single hint can add to a player's time
+    pub max_hint_penalty_secs: u64,
+    /// Longest time limit a clock can be set to
+    pub max_clock_secs: u64,
+    /// How often waiting players are looked at again by matchmaking
+    pub matchmaking_interval_secs: u64,
+}
+
+impl Default for GameLimits {
+    fn default() -> Self {
+        GameLimits {
+            max_board_size: 50,
+            max_player_limit: 16,
+            max_hint_limit: 10,
+            max_hint_penalty_secs: 5 * 60,
+            max_clock_secs: 60 * 60,
+            matchmaking_interval_secs: 5,
+        }
+    }
+}
+
+impl GameLimits {
+    /// Whether a game may be played with the given settings
+    pub fn allow(&self, config: &GameConfig) -> bool {
+        let clock_allowed = match config.clock {
+            Clock::Unlimited => true,
+            Clock::PerPlayer { limit_secs } | Clock::Blitz { limit_secs } => {
+                limit_secs <= self.max_clock_secs
+            }
+        };
+
+        (2..=self.max_board_size).contains(&config.board_size)
+            && (1..=self.max_player_limit).contains(&config.player_limit)
+            && config.hint_limit <= self.max_hint_limit
+            && config.hint_penalty_secs <= self.max_hint_penalty_secs
+            && clock_allowed
+    }
+
+    pub fn matchmaking_interval(&self) -> Duration {
+        Duration::from_secs(self.matchmaking_interval_secs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_handler::Visibility;
+
+    #[test]
+    fn test_allow() {
+        let limits = GameLimits::default();
+        let config = GameConfig {
+            board_size: 16,
+            player_limit: 2,
+            hint_limit: 3,
+            hint_penalty_secs: 10,
+            clock: Clock::Blitz { limit_secs: 60 },
+            visibility: Visibility::Public,
+        };
+
+        assert!(limits.allow(&config));
+
+        for config in [
+            GameConfig {
+                board_size: limits.max_board_size + 1,
+                ..config.clone()
+            },
+            GameConfig {
+                player_limit: 0,
+                ..config.clone()
+            },
+            GameConfig {
+                hint_penalty_secs: u64::MAX,
+                ..config.clone()
+            },
+            GameConfig {
+                clock: Clock::PerPlayer {
+                    limit_secs: limits.max_clock_secs + 1,
+                },
+                ..config.clone()
+            },
+        ] {
+            assert!(!limits.allow(&config), "{:?}", config);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use actix::{Handler, Message};
+use serde::Serialize;
+
+use super::{Clock, Difficulty, GameHandler, Visibility};
+
+/// Public games that still have room for more players
+pub struct ListGames;
+
+#[derive(Serialize, Debug)]
+pub struct Lobby {
+    code: String,
+    player_count: usize,
+    player_limit: usize,
+    board_size: usize,
+    difficulty: Difficulty,
+    hint_limit: usize,
+    clock: Clock,
+    /// Whether a password is needed to join
+    password_protected: bool,
+    /// Mean rating of the players in the lobby who have one
+    average_rating: Option<f64>,
+}
+
+impl Message for ListGames {
+    type Result = Vec<Lobby>;
+}
+
+impl Handler<ListGames> for GameHandler {
+    type Result = Vec<Lobby>;
+
+    fn handle(&mut self, _msg: ListGames, _ctx: &mut Self::Context) -> Self::Result {
+        let mut lobbies = self
+            .games
+            .iter()
+            .filter(|(_, game)| {
+                game.config.visibility == Visibility::Public
+                    && game.tournament.is_none()
+                    && !game.locked
+                    && game.players.len() < game.config.player_limit
+                    && !game.is_finished()
+                    && game.deadline.is_none()
+            })
+            .map(|(code, game)| {
+                let difficulty = Difficulty::from_board_size(game.config.board_size);
+
+                let ratings = game
+                    .players
+                    .values()
+                    .filter_map(|player_game| self.player_rating(player_game, difficulty))
+                    .collect::<Vec<f64>>();
+
+                Lobby {
+                    code: format!("{:X}", code),
+                    player_count: game.players.len(),
+                    player_limit: game.config.player_limit,
+                    board_size: game.config.board_size,
+                    difficulty,
+                    hint_limit: game.config.hint_limit,
+                    clock: game.config.clock,
+                    password_protected: game.password_hash.is_some(),
+                    average_rating: (!ratings.is_empty())
+                        .then(|| ratings.iter().sum::<f64>() / ratings.len() as f64),
+                }
+            })
+            .collect::<Vec<Lobby>>();
+
+        // Fullest lobbies first, as those are the quickest to start
+        lobbies.sort_by(|a, b| {
+            b.player_count
+                .cmp(&a.player_count)
+                .then_with(|| a.code.cmp(&b.code))
+        });
+
+        lobbies
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Instant;
+
+use actix::{AsyncContext, Handler, Message, Recipient};
+use serde::Serialize;
+
+use super::{
+    rating::DEFAULT_RATING, Clock, Difficulty, GameConfig, GameHandler, PlayerTokenError,
+    Visibility, WsGame, DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+
+/// Number of waiting players put together in a game
+pub const MATCH_SIZE: usize = 2;
+
+/// Largest difference in rating between players matched straight away
+const RATING_WINDOW: f64 = 100.0;
+/// How much further apart players may be rated for every second they wait
+const RATING_WINDOW_GROWTH: f64 = 10.0;
+
+impl Difficulty {
+    /// Board size of games created by matchmaking
+    pub fn board_size(self) -> usize {
+        match self {
+            Difficulty::Beginner => 9,
+            Difficulty::Intermediate => 16,
+            Difficulty::Expert => 24,
+        }
+    }
+}
+
+/// Sent to every player in a match once a game has been created for them
+#[derive(Serialize, Debug, Clone)]
+pub struct MatchFound {
+    pub game_code: String,
+    pub players: Vec<String>,
+}
+
+impl Message for MatchFound {
+    type Result = ();
+}
+
+/// A player waiting in the matchmaking queue
+pub struct QueuedPlayer {
+    ticket: u64,
+    name: String,
+    rating: f64,
+    entered: Instant,
+    recipient: Recipient<MatchFound>,
+}
+
+impl QueuedPlayer {
+    /// How far apart in rating the player accepts opponents to be
+    fn rating_window(&self, now: Instant) -> f64 {
+        RATING_WINDOW + RATING_WINDOW_GROWTH * now.duration_since(self.entered).as_secs_f64()
+    }
+}
+
+/// Puts a player in the queue for a difficulty, returning a ticket to leave it
+/// with. The recipient is told about the game once enough players of similar
+/// rating are waiting. Players who are not logged in queue with the default
+/// rating.
+pub struct EnterQueue {
+    pub difficulty: Difficulty,
+    pub player_name: String,
+    pub identity_token: Option<String>,
+    pub session_token: Option<String>,
+    pub recipient: Recipient<MatchFound>,
+}
+
+impl Message for EnterQueue {
+    type Result = Result<u64, PlayerTokenError>;
+}
+
+pub struct LeaveQueue {
+    pub ticket: u64,
+}
+
+impl Message for LeaveQueue {
+    type Result = ();
+}
+
+impl Handler<EnterQueue> for GameHandler {
+    type Result = Result<u64, PlayerTokenError>;
+
+    fn handle(&mut self, msg: EnterQueue, _ctx: &mut Self::Context) -> Self::Result {
+        let player =
+            self.resolve_player(msg.session_token.as_deref(), msg.identity_token.as_deref())?;
+
+        let rating = player.map_or(DEFAULT_RATING, |(player_id, _)| {
+            self.rating(&player_id, msg.difficulty).rating
+        });
+
+        self.next_ticket += 1;
+        let ticket = self.next_ticket;
+
+        self.queues
+            .entry(msg.difficulty)
+            .or_default()
+            .push(QueuedPlayer {
+                ticket,
+                name: msg.player_name,
+                rating,
+                entered: Instant::now(),
+                recipient: msg.recipient,
+            });
+
+        self.match_queue(msg.difficulty, Instant::now());
+
+        Ok(ticket)
+    }
+}
+
+impl Handler<LeaveQueue> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: LeaveQueue, _ctx: &mut Self::Context) -> Self::Result {
+        for queue in self.queues.values_mut() {
+            queue.retain(|queued| queued.ticket != msg.ticket);
+        }
+    }
+}
+
+/// Picks the group of waiting players closest in rating that every one of them
+/// would accept, as indices into the queue
+fn closest_group(queue: &[QueuedPlayer], now: Instant) -> Option<Vec<usize>> {
+    let mut by_rating = (0..queue.len()).collect::<Vec<usize>>();
+    by_rating.sort_by(|&a, &b| queue[a].rating.total_cmp(&queue[b].rating));
+
+    by_rating
+        .windows(MATCH_SIZE)
+        .filter_map(|group| {
+            let spread = queue[group[group.len() - 1]].rating - queue[group[0]].rating;
+            let accepted = group
+                .iter()
+                .all(|&index| queue[index].rating_window(now) >= spread);
+
+            accepted.then_some((spread, group))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, group)| group.to_vec())
+}
+
+impl GameHandler {
+    /// Starts looking at waiting players again every so often, so that those
+    /// who could not be matched yet are once they accept wider rating gaps
+    pub(super) fn schedule_matchmaking(&mut self, ctx: &mut <Self as actix::Actor>::Context) {
+        ctx.run_interval(self.limits.matchmaking_interval(), |game_handler, _ctx| {
+            let difficulties = game_handler
+                .queues
+                .keys()
+                .copied()
+                .collect::<Vec<Difficulty>>();
+
+            for difficulty in difficulties {
+                game_handler.match_queue(difficulty, Instant::now());
+            }
+        });
+    }
+
+    /// Starts games for as many groups of similarly rated players as possible
+    fn match_queue(&mut self, difficulty: Difficulty, now: Instant) {
+        let Some(queue) = self.queues.get_mut(&difficulty) else {
+            return;
+        };
+
+        // Players who went away without leaving should not be matched
+        queue.retain(|queued| queued.recipient.connected());
+
+        let mut matches = Vec::new();
+        while let Some(mut group) = closest_group(queue, now) {
+            // Removing from the back keeps the remaining indices valid
+            group.sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut matched = group
+                .into_iter()
+                .map(|index| queue.remove(index))
+                .collect::<Vec<QueuedPlayer>>();
+            matched.sort_by_key(|queued| queued.ticket);
+
+            matches.push(matched);
+        }
+
+        for matched in matches {
+            self.start_match(difficulty, matched);
+        }
+    }
+
+    fn start_match(&mut self, difficulty: Difficulty, matched: Vec<QueuedPlayer>) {
+        // The players wait on in the queue until codes are freed again
+        let Ok(game_code) = self.new_game_code() else {
+            log::error!("No free code for a {:?} match", difficulty);
+            self.queues.entry(difficulty).or_default().extend(matched);
+            return;
+        };
+
+        self.games.insert(
+            game_code,
+            WsGame::new(GameConfig {
+                player_limit: matched.len(),
+                board_size: difficulty.board_size(),
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock: Clock::default(),
+                visibility: Visibility::Private,
+            }),
+        );
+        self.persist_game(game_code);
+
+        let match_found = MatchFound {
+            game_code: format!("{:X}", game_code),
+            players: matched.iter().map(|queued| queued.name.clone()).collect(),
+        };
+
+        for queued in matched {
+            queued.recipient.do_send(match_found.clone());
+        }
+    }
+}
@@ -1,78 +1,599 @@
+mod account;
+mod anti_cheat;
+mod clock;
 mod create_game;
+mod create_tournament;
+mod daily;
+mod eviction;
+mod export_game;
+mod get_analysis;
 mod get_game_state;
+mod get_leaderboard;
+mod get_match_history;
+mod get_player_stats;
+mod get_ratings;
+mod get_results;
+mod get_spectator_state;
+mod get_tournament;
+mod host_command;
+mod identity;
+mod import_game;
 mod join_game;
+mod limits;
+mod list_games;
+mod matchmaking;
+mod password;
 mod player_move;
+mod player_name;
+mod rating;
+mod rematch;
+mod solo;
+mod subscribe;
+mod tournament;
+
+pub use account::hash_new_password;
+pub use account::Account;
+pub use account::AccountError;
+pub use account::GetAccountPasswordHash;
+pub use account::Login;
+pub use account::Logout;
+pub use account::Register;
+pub use account::Session;
+pub use account::MIN_PASSWORD_LENGTH;
+pub use account::SESSION_TTL;
+
+pub use anti_cheat::Suspicion;
 
 pub use create_game::CreateGame;
+pub use create_game::CreateGameError;
+
+pub use create_tournament::CreateTournament;
+pub use create_tournament::CreateTournamentError;
+
+pub use daily::parse_date;
+pub use daily::DailyError;
+pub use daily::GetDailyChallenge;
+pub use daily::GetDailyLeaderboard;
+pub use daily::PlayDaily;
+
+pub use export_game::ExportGame;
+pub use export_game::ExportGameError;
+pub use export_game::GameRecord;
+
+pub use import_game::ImportGame;
+
+pub use get_analysis::GetAnalysis;
+pub use get_analysis::GetAnalysisError;
 
-pub use get_game_state::GameStateUpdate;
 pub use get_game_state::GetGameState;
-pub use get_game_state::GetGameStateError;
 
+pub use get_leaderboard::Difficulty;
+pub use get_leaderboard::GetLeaderboard;
+pub use get_leaderboard::Period;
+
+pub use get_match_history::GetMatchHistory;
+
+pub use get_player_stats::GetPlayerStats;
+pub use get_player_stats::PlayerRef;
+
+pub use get_ratings::GetRatings;
+
+pub use get_results::GetResults;
+
+pub use get_spectator_state::GetSpectatorState;
+pub use get_spectator_state::GetSpectatorStateError;
+
+pub use get_tournament::GetTournament;
+pub use get_tournament::GetTournamentError;
+
+pub use host_command::Host;
+pub use host_command::HostCommand;
+
+pub use identity::CreateIdentity;
+pub use identity::Identity;
+pub use identity::PlayerId;
+pub use identity::PlayerTokenError;
+
+pub use join_game::GetPasswordHash;
 pub use join_game::JoinGame;
 pub use join_game::JoinGameError;
 
-pub use player_move::MoveError;
-pub use player_move::PlayerAction;
+pub use limits::GameLimits;
+
+pub use list_games::ListGames;
+
+pub use matchmaking::EnterQueue;
+pub use matchmaking::LeaveQueue;
+pub use matchmaking::MatchFound;
+
+pub use password::dummy_password_hash;
+pub use password::hash_password;
+pub use password::hash_token;
+pub use password::verify_password;
+
+pub use player_name::NameError;
+pub use player_name::MAX_NAME_LENGTH;
+
 pub use player_move::PlayerMove;
 
-use crate::game;
+pub use rating::Rating;
+pub use rating::RatingChange;
+
+pub use rematch::RematchProposal;
+pub use rematch::RequestRematch;
+
+pub use solo::GetPersonalBests;
+pub use solo::PlaySolo;
+pub use solo::SoloError;
+
+pub use subscribe::GameUpdated;
+pub use subscribe::Subscribe;
+pub use subscribe::Unsubscribe;
+
+pub use tournament::Tournament;
+
+pub use minesweeper_core::protocol::{
+    Clock, GameStateUpdate, GameStats, HostAction, MatchState, OpponentState, RematchState,
+    Standing, TournamentFormat, TournamentStanding, TournamentState, Visibility,
+    DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+pub use minesweeper_core::{Guess, Hint, PlayerAction};
+
+use crate::storage::{InMemoryStorage, Storage, StorageError};
 
 #[cfg(test)]
 mod test;
 
 use std::collections::HashMap;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
-use actix::{Actor, Context};
-use serde::Deserialize;
+use actix::{Actor, Context, Recipient};
+use minesweeper_core::protocol::{default_hint_limit, default_hint_penalty_secs};
+use minesweeper_core::Board;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct GameConfig {
     pub player_limit: usize,
     pub board_size: usize,
+    /// Number of hints each player may ask for
+    #[serde(default = "default_hint_limit")]
+    pub hint_limit: usize,
+    /// Seconds added to a player's time for every hint they take
+    #[serde(default = "default_hint_penalty_secs")]
+    pub hint_penalty_secs: u64,
+    #[serde(default)]
+    pub clock: Clock,
+    #[serde(default)]
+    pub visibility: Visibility,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WsGame {
     config: GameConfig,
 
     // The common unsolved board
-    board: game::Board,
+    board: Board,
 
     players: HashMap<u16, WsPlayerGame>,
+
+    /// Shared deadline of a blitz game, set once the first player joins
+    #[serde(default)]
+    deadline: Option<SystemTime>,
+
+    /// Rematch waiting for every player to agree, once the game is finished
+    #[serde(default)]
+    rematch: Option<RematchProposal>,
+
+    /// Tournament the game is a match of
+    #[serde(default)]
+    tournament: Option<u16>,
+
+    #[serde(default)]
+    host: Option<Host>,
+    /// Whether new players are turned away
+    #[serde(default)]
+    locked: bool,
+    /// Whether the game is still waiting for the host to start it
+    #[serde(default)]
+    waiting: bool,
+    /// Hash of the password players need to join, if one was set
+    #[serde(default)]
+    password_hash: Option<String>,
+    /// Whether the ratings of the players have been updated from this game
+    #[serde(default)]
+    rated: bool,
+    /// Day of the daily challenge this game is a player's attempt at
+    #[serde(default)]
+    daily: Option<u64>,
+    /// Whether the game was started by a player to play on their own
+    #[serde(default)]
+    solo: bool,
+    /// Whether the layout may already be known to the players, as in a rematch
+    /// on the same board or on an imported board, so results do not count
+    /// towards rankings
+    #[serde(default)]
+    unranked: bool,
+    #[serde(default = "SystemTime::now")]
+    created_time: SystemTime,
 }
 
 impl WsGame {
     pub fn new(config: GameConfig) -> WsGame {
+        Self::with_board(config.clone(), Board::generate(config.board_size))
+    }
+
+    /// Creates a game played on a known layout rather than a random one
+    pub fn with_board(config: GameConfig, board: Board) -> WsGame {
         WsGame {
-            board: game::Board::generate(config.board_size.clone()),
+            board,
             players: HashMap::new(),
             config,
+            deadline: None,
+            rematch: None,
+            tournament: None,
+            host: None,
+            locked: false,
+            waiting: false,
+            password_hash: None,
+            rated: false,
+            daily: None,
+            solo: false,
+            unranked: false,
+            created_time: SystemTime::now(),
         }
     }
+
+    /// Whether every player who joined has finished their board
+    pub fn is_finished(&self) -> bool {
+        !self.players.is_empty()
+            && self
+                .players
+                .values()
+                .all(|player_game| player_game.board.is_finished())
+    }
+
+    /// Players ordered by how well they are doing: those who cleared their
+    /// board by time, followed by everyone else by the number of cells revealed
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut players = self.players.values().collect::<Vec<&WsPlayerGame>>();
+
+        players.sort_by_key(|player_game| {
+            let won = player_game.board.is_cleared();
+
+            (
+                !won,
+                won.then(|| player_game.duration()).flatten(),
+                std::cmp::Reverse(player_game.board.revealed_count()),
+                player_game.name.clone(),
+            )
+        });
+
+        players
+            .into_iter()
+            .enumerate()
+            .map(|(index, player_game)| Standing {
+                rank: index + 1,
+                name: player_game.name.clone(),
+                revealed: player_game.board.revealed_count(),
+                won: player_game.board.is_cleared(),
+                finished: player_game.board.is_finished(),
+                time_ms: player_game
+                    .duration()
+                    .map(|duration| duration.as_millis() as u64),
+            })
+            .collect()
+    }
 }
 
-#[derive(Debug, Clone)]
-struct WsPlayerGame {
-    board: game::Board,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsPlayerGame {
+    board: Board,
     name: String,
     start_time: SystemTime,
     finished_time: Option<SystemTime>,
+    moves: Vec<RecordedMove>,
+    #[serde(default)]
+    hints_used: usize,
+    /// Answer to the player's latest hint request, cleared by their next move
+    #[serde(default)]
+    last_hint: Option<Hint>,
+    /// Anonymous account the player joined with, if any
+    #[serde(default)]
+    player_id: Option<PlayerId>,
+    /// Whether the player's connection closed, which counts as declining a rematch
+    #[serde(default)]
+    left: bool,
+}
+
+/// A move that was applied to a player's board along with the number of
+/// milliseconds since the player joined, as measured by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMove {
+    #[serde(flatten)]
+    pub action: PlayerAction,
+    pub time: u64,
+    /// For reveals, the chance that the cell held a mine given what the player
+    /// could see at the time. Only worked out when a won game is reviewed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<f64>,
+}
+
+/// Outcome of a single player's game, recorded once their board is finished
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub game_code: u16,
+    pub player_name: String,
+    #[serde(default)]
+    pub player_id: Option<PlayerId>,
+    pub board_size: usize,
+    pub mines: usize,
+    pub won: bool,
+    pub start_time: SystemTime,
+    pub finished_time: SystemTime,
+    pub bbbv: usize,
+    pub openings: usize,
+    pub islands: usize,
+    pub clicks: usize,
+    /// Reasons the result looks scripted, empty for results that look genuine
+    pub suspicions: Vec<Suspicion>,
+    /// Day of the daily challenge the result was played for, counted from the epoch
+    #[serde(default)]
+    pub daily: Option<u64>,
+    /// Whether the result is of a solo game, which count towards personal bests
+    #[serde(default)]
+    pub solo: bool,
+    /// Whether the result is left out of leaderboards, stats and personal bests
+    #[serde(default)]
+    pub unranked: bool,
+}
+
+impl GameResult {
+    pub fn is_flagged(&self) -> bool {
+        !self.suspicions.is_empty()
+    }
+}
+
+impl WsPlayerGame {
+    /// Number of reveals and flags made, not counting hint requests
+    fn clicks(&self) -> usize {
+        self.moves
+            .iter()
+            .filter(|recorded| !matches!(recorded.action, PlayerAction::Hint { .. }))
+            .count()
+    }
+
+    /// Time the player took, available once their board is finished
+    fn duration(&self) -> Option<Duration> {
+        let finished_time = self.finished_time?;
+
+        Some(
+            finished_time
+                .duration_since(self.start_time)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Metrics of the player's game, available once their board is finished
+    fn stats(&self) -> Option<GameStats> {
+        Some(GameStats::new(
+            self.board.bbbv(),
+            self.board.openings(),
+            self.board.islands(),
+            self.clicks(),
+            self.duration()?,
+        ))
+    }
 }
 
 pub struct GameHandler {
     games: HashMap<u16, WsGame>,
+    tournaments: HashMap<u16, Tournament>,
+    /// Players known by the hash of the token they were given
+    identities: HashMap<String, PlayerId>,
+    /// Registered accounts by lowercased username
+    accounts: HashMap<String, Account>,
+    /// Logged in sessions by the hash of their token
+    sessions: HashMap<String, Session>,
+    /// Ratings of players with an account, by difficulty
+    ratings: HashMap<(PlayerId, Difficulty), Rating>,
+    storage: Box<dyn Storage>,
+    /// Connections to notify about changes, by game and player code
+    subscribers: HashMap<u16, HashMap<u16, Recipient<GameUpdated>>>,
+    /// Players waiting to be matched, oldest first
+    queues: HashMap<Difficulty, Vec<matchmaking::QueuedPlayer>>,
+    next_ticket: u64,
+    limits: GameLimits,
+    /// Key the boards of the daily challenge are derived from
+    daily_secret: Vec<u8>,
 }
 
 impl Default for GameHandler {
     fn default() -> Self {
         Self {
             games: HashMap::new(),
+            tournaments: HashMap::new(),
+            identities: HashMap::new(),
+            accounts: HashMap::new(),
+            sessions: HashMap::new(),
+            ratings: HashMap::new(),
+            storage: Box::<InMemoryStorage>::default(),
+            subscribers: HashMap::new(),
+            queues: HashMap::new(),
+            next_ticket: 0,
+            limits: GameLimits::default(),
+            daily_secret: rand::random::<[u8; 32]>().to_vec(),
         }
     }
 }
 
+impl GameHandler {
+    /// Creates a handler backed by the given storage, restoring any games saved in it
+    pub fn new(storage: Box<dyn Storage>) -> Result<GameHandler, StorageError> {
+        let mut game_handler = GameHandler {
+            games: storage.load_games()?,
+            tournaments: storage.load_tournaments()?,
+            identities: storage
+                .load_identities()?
+                .into_iter()
+                .map(|identity| (identity.token, identity.player_id))
+                .collect(),
+            accounts: storage
+                .load_accounts()?
+                .into_iter()
+                .map(|account| (account.username.to_lowercase(), account))
+                .collect(),
+            sessions: storage
+                .load_sessions()?
+                .into_iter()
+                .map(|session| (session.token.clone(), session))
+                .collect(),
+            ratings: storage
+                .load_ratings()?
+                .into_iter()
+                .map(|rating| ((rating.player_id.clone(), rating.difficulty), rating))
+                .collect(),
+            storage,
+            subscribers: HashMap::new(),
+            queues: HashMap::new(),
+            next_ticket: 0,
+            limits: GameLimits::default(),
+            daily_secret: Vec::new(),
+        };
+
+        game_handler.daily_secret = match game_handler.storage.load_daily_secret()? {
+            Some(secret) => secret,
+            None => {
+                let secret = rand::random::<[u8; 32]>().to_vec();
+                game_handler.storage.save_daily_secret(&secret)?;
+                secret
+            }
+        };
+
+        game_handler.prune_sessions()?;
+
+        Ok(game_handler)
+    }
+
+    /// Bounds the games that can be created to the given limits
+    pub fn with_limits(mut self, limits: GameLimits) -> GameHandler {
+        self.limits = limits;
+        self
+    }
+
+    /// Derives the daily challenge from the given secret rather than the one
+    /// generated and kept in storage
+    pub fn with_daily_secret(mut self, secret: &str) -> GameHandler {
+        self.daily_secret = secret.as_bytes().to_vec();
+        self
+    }
+
+    /// Records the result of a player whose board has just finished
+    fn finish_player(&mut self, game_code: u16, player_code: u16, finished_time: SystemTime) {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return;
+        };
+        let daily = game.daily;
+        let solo = game.solo;
+        let unranked = game.unranked;
+        let Some(player_game) = game.players.get_mut(&player_code) else {
+            return;
+        };
+
+        player_game.finished_time = Some(finished_time);
+
+        let stats = player_game.stats().expect("finished games have stats");
+        let won = player_game.board.is_cleared();
+        if won {
+            anti_cheat::record_risks(&game.board, &mut player_game.moves);
+        }
+
+        let result = GameResult {
+            game_code,
+            player_name: player_game.name.clone(),
+            player_id: player_game.player_id.clone(),
+            board_size: player_game.board.size(),
+            mines: player_game.board.mines_count(),
+            won,
+            start_time: player_game.start_time,
+            finished_time,
+            bbbv: stats.bbbv,
+            openings: stats.openings,
+            islands: stats.islands,
+            clicks: stats.clicks,
+            suspicions: anti_cheat::review(&player_game.moves, &stats, won),
+            daily,
+            solo,
+            unranked,
+        };
+
+        self.record_result(result);
+        self.finish_tournament_game(game_code);
+        self.rate_game(game_code);
+    }
+
+    /// Writes the current state of a game through to storage.
+    ///
+    /// Storage failures are logged rather than surfaced so that a broken
+    /// database does not interrupt games that are being played.
+    fn persist_game(&mut self, code: u16) {
+        let Some(game) = self.games.get(&code) else {
+            return;
+        };
+
+        if let Err(err) = self.storage.save_game(code, game) {
+            log::error!("Failed to save game {:X}: {}", code, err);
+        }
+    }
+
+    fn persist_tournament(&mut self, code: u16) {
+        let Some(tournament) = self.tournaments.get(&code) else {
+            return;
+        };
+
+        if let Err(err) = self.storage.save_tournament(code, tournament) {
+            log::error!("Failed to save tournament {:X}: {}", code, err);
+        }
+    }
+
+    fn record_result(&mut self, result: GameResult) {
+        if let Err(err) = self.storage.save_result(&result) {
+            log::error!(
+                "Failed to save result of game {:X}: {}",
+                result.game_code,
+                err
+            );
+        }
+    }
+
+    fn new_game_code(&self) -> Result<u16, NoFreeCode> {
+        (0..0xff)
+            .map(|_| rand::thread_rng().gen_range(0u16..0xffffu16))
+            .find(|code| !self.games.contains_key(code))
+            .ok_or(NoFreeCode)
+    }
+
+    fn new_tournament_code(&self) -> Result<u16, NoFreeCode> {
+        (0..0xff)
+            .map(|_| rand::thread_rng().gen_range(0u16..0xffffu16))
+            .find(|code| !self.tournaments.contains_key(code))
+            .ok_or(NoFreeCode)
+    }
+}
+
+/// No unused code could be found for a new game or tournament. Codes are freed
+/// again as finished games are evicted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoFreeCode;
+
 impl Actor for GameHandler {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.schedule_all_expiries(ctx);
+        self.schedule_matchmaking(ctx);
+        self.schedule_eviction(ctx);
+        self.schedule_round_deadlines(ctx);
+    }
 }
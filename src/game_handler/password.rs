@@ -0,0 +1,71 @@
+//! Hashing of passwords and of the tokens handed out to clients.
+//!
+//! Password hashing is slow on purpose, so routes call those functions on a
+//! blocking thread and only hand the results to the game handler. Tokens are
+//! long and random, so a single fast hash is enough to keep them out of storage.
+
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use blake2::{Blake2s256, Digest};
+
+/// Hashes a password into a PHC string with a random salt
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("hash a password with default parameters")
+        .to_string()
+}
+
+/// Whether the password matches a hash made by [`hash_password`]
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    let Ok(password_hash) = PasswordHash::new(password_hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &password_hash)
+        .is_ok()
+}
+
+/// A hash of no one's password, to verify against when the account is unknown
+/// so that logging in as a missing user takes as long as a wrong password
+pub fn dummy_password_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    DUMMY_HASH.get_or_init(|| hash_password("not anyone's password"))
+}
+
+/// What the server keeps of an identity or session token
+pub fn hash_token(token: &str) -> String {
+    Blake2s256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verifies_only_the_hashed_password() {
+        let password_hash = hash_password("hunter2");
+
+        assert!(!password_hash.contains("hunter2"));
+        assert!(verify_password("hunter2", &password_hash));
+        assert!(!verify_password("hunter3", &password_hash));
+        assert!(!verify_password("hunter2", dummy_password_hash()));
+    }
+
+    #[test]
+    fn test_hashes_tokens_consistently() {
+        assert_eq!(hash_token("secret"), hash_token("secret"));
+        assert_ne!(hash_token("secret"), hash_token("secrets"));
+        assert_eq!(hash_token("secret").len(), 64);
+    }
+}
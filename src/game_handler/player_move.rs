@@ -1,21 +1,17 @@
+use std::time::{Duration, SystemTime};
+
 use actix::{Handler, Message};
-use serde::Deserialize;
 
-use super::GameHandler;
+use minesweeper_core::PlayerAction;
 
-#[derive(Deserialize, Debug, PartialEq, Eq)]
-#[serde(tag = "action")]
-pub enum PlayerAction {
-    #[serde(rename = "flag")]
-    Flag { index: usize },
-    #[serde(rename = "reveal")]
-    Reveal { index: usize },
-}
+use super::{GameHandler, RecordedMove};
 
 pub enum MoveError {
     NoSuchGame,
     NoSuchPlayer,
     InvalidMove,
+    HintLimitReached,
+    NotStarted,
 }
 pub struct PlayerMove {
     pub game_code: u16,
@@ -30,29 +26,84 @@ impl Message for PlayerMove {
 impl Handler<PlayerMove> for GameHandler {
     type Result = Result<(), MoveError>;
 
-    fn handle(&mut self, msg: PlayerMove, _ctx: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: PlayerMove, ctx: &mut Self::Context) -> Self::Result {
+        // Timers can fire late, so moves made after the deadline are refused here
+        if self.expire_player(msg.game_code, msg.player_code) {
+            return Err(MoveError::InvalidMove);
+        }
+
         let game = self
             .games
             .get_mut(&msg.game_code)
             .ok_or(MoveError::NoSuchGame)?;
 
+        if game.waiting {
+            return Err(MoveError::NotStarted);
+        }
+
         let player_game = game
             .players
             .get_mut(&msg.player_code)
             .ok_or(MoveError::NoSuchPlayer)?;
 
         match msg.action {
-            PlayerAction::Flag { index } => player_game
-                .board
-                .toggle_flag(index)
-                .map_err(|_| MoveError::InvalidMove)?,
+            PlayerAction::Hint { allow_guess } => {
+                let mut hints_used = player_game.hints_used;
+                let hint = player_game
+                    .board
+                    .take_hint(allow_guess, game.config.hint_limit, &mut hints_used)
+                    .map_err(|_| MoveError::HintLimitReached)?;
+
+                // Moving the start back keeps the player's clock and recorded
+                // times consistent with the penalty for the hints counted
+                if hint.is_useful() {
+                    player_game.start_time = player_game
+                        .start_time
+                        .checked_sub(Duration::from_secs(game.config.hint_penalty_secs))
+                        .ok_or(MoveError::InvalidMove)?;
+                }
 
-            PlayerAction::Reveal { index } => player_game
+                player_game.hints_used = hints_used;
+                player_game.last_hint = Some(hint);
+            }
+            _ => player_game
                 .board
-                .reveal(index)
+                .play(&msg.action)
                 .map_err(|_| MoveError::InvalidMove)?,
         };
 
+        if !matches!(msg.action, PlayerAction::Hint { .. }) {
+            player_game.last_hint = None;
+        }
+
+        let now = SystemTime::now();
+
+        player_game.moves.push(RecordedMove {
+            action: msg.action,
+            time: now
+                .duration_since(player_game.start_time)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            risk: None,
+        });
+
+        let finished = player_game.board.is_finished();
+        // Only hints given by this move are kept, and all but an empty one cost time
+        let penalised = player_game
+            .last_hint
+            .as_ref()
+            .is_some_and(|hint| hint.is_useful());
+
+        if finished {
+            self.finish_player(msg.game_code, msg.player_code, now);
+        } else if penalised {
+            // The penalty brought a per player deadline forward
+            self.schedule_expiry(ctx, msg.game_code, msg.player_code);
+        }
+
+        self.persist_game(msg.game_code);
+        self.notify_game(msg.game_code, Some(msg.player_code));
+
         Ok(())
     }
 }
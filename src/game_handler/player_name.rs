@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+/// Longest display name accepted, in characters
+pub const MAX_NAME_LENGTH: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    TooLong,
+    ControlCharacter,
+    /// Only the owner of the account may play under its username
+    Registered,
+}
+
+/// Trims a display name and collapses runs of whitespace into single spaces,
+/// rejecting names that are empty, too long or contain control characters
+pub fn normalise_name(raw: &str) -> Result<String, NameError> {
+    if raw
+        .chars()
+        .any(|character| character.is_control() && !character.is_whitespace())
+    {
+        return Err(NameError::ControlCharacter);
+    }
+
+    let name = raw.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(NameError::TooLong);
+    }
+
+    Ok(name)
+}
+
+/// Suffixes the name with a number if another player in the game already
+/// uses it, ignoring case. The result stays within [`MAX_NAME_LENGTH`].
+pub(super) fn unique_name<'a>(name: &str, taken: impl Iterator<Item = &'a str>) -> String {
+    let taken = taken
+        .map(|taken| taken.to_lowercase())
+        .collect::<HashSet<String>>();
+
+    if !taken.contains(&name.to_lowercase()) {
+        return name.to_string();
+    }
+
+    (2..)
+        .map(|number| {
+            let suffix = format!(" ({})", number);
+            let base = name
+                .chars()
+                .take(MAX_NAME_LENGTH - suffix.chars().count())
+                .collect::<String>();
+
+            format!("{}{}", base.trim_end(), suffix)
+        })
+        .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+        .expect("find a free suffix")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalise_name() {
+        assert_eq!(
+            normalise_name("  Ada \t Lovelace "),
+            Ok("Ada Lovelace".to_string())
+        );
+        assert_eq!(normalise_name(" \n "), Err(NameError::Empty));
+        assert_eq!(normalise_name("a\u{7}b"), Err(NameError::ControlCharacter));
+        assert_eq!(
+            normalise_name(&"x".repeat(MAX_NAME_LENGTH + 1)),
+            Err(NameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn test_unique_name() {
+        assert_eq!(unique_name("bob", ["alice"].into_iter()), "bob");
+        assert_eq!(
+            unique_name("bob", ["Bob", "bob (2)"].into_iter()),
+            "bob (3)"
+        );
+
+        let long = "x".repeat(MAX_NAME_LENGTH);
+        let suffixed = unique_name(&long, [long.as_str()].into_iter());
+        assert_eq!(suffixed.chars().count(), MAX_NAME_LENGTH);
+        assert!(suffixed.ends_with(" (2)"));
+    }
+}
@@ -0,0 +1,298 @@
+//! Elo ratings from race placements.
+//!
+//! A race between several players is scored as if every pair of them had
+//! played a match, won by whoever placed higher and drawn by players who
+//! finished level. Only players with an account are rated, separately for
+//! every difficulty, since identities cost nothing to make.
+
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use super::{Difficulty, GameHandler, PlayerId, Standing, WsPlayerGame};
+
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// Ratings based on fewer games than this move faster and are shown as provisional
+pub const PROVISIONAL_GAMES: usize = 10;
+
+const PROVISIONAL_K: f64 = 40.0;
+const ESTABLISHED_K: f64 = 20.0;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub player_id: PlayerId,
+    pub difficulty: Difficulty,
+    pub rating: f64,
+    /// Number of rated games played
+    pub games: usize,
+}
+
+/// A single update of a player's rating, kept as their rating history
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RatingChange {
+    pub player_id: PlayerId,
+    pub difficulty: Difficulty,
+    pub game_code: u16,
+    pub rating_before: f64,
+    pub rating_after: f64,
+    #[serde(serialize_with = "serialize_secs")]
+    pub time: SystemTime,
+}
+
+fn serialize_secs<S: serde::Serializer>(
+    time: &SystemTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    )
+}
+
+impl Rating {
+    pub fn new(player_id: PlayerId, difficulty: Difficulty) -> Rating {
+        Rating {
+            player_id,
+            difficulty,
+            rating: DEFAULT_RATING,
+            games: 0,
+        }
+    }
+
+    pub fn is_provisional(&self) -> bool {
+        self.games < PROVISIONAL_GAMES
+    }
+
+    fn k_factor(&self) -> f64 {
+        if self.is_provisional() {
+            PROVISIONAL_K
+        } else {
+            ESTABLISHED_K
+        }
+    }
+}
+
+/// Chance of a player rated `rating` beating one rated `opponent`
+fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// New ratings of players listed with their place, tied players sharing one
+fn rate_placements(placements: &[(usize, &Rating)]) -> Vec<f64> {
+    let opponents = placements.len().saturating_sub(1).max(1) as f64;
+
+    placements
+        .iter()
+        .enumerate()
+        .map(|(index, &(place, player))| {
+            let surplus = placements
+                .iter()
+                .enumerate()
+                .filter(|&(other_index, _)| other_index != index)
+                .map(|(_, &(other_place, other))| {
+                    let score = match place.cmp(&other_place) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    score - expected_score(player.rating, other.rating)
+                })
+                .sum::<f64>();
+
+            player.rating + player.k_factor() / opponents * surplus
+        })
+        .collect()
+}
+
+impl GameHandler {
+    pub(super) fn rating(&self, player_id: &PlayerId, difficulty: Difficulty) -> Rating {
+        self.ratings
+            .get(&(player_id.clone(), difficulty))
+            .cloned()
+            .unwrap_or_else(|| Rating::new(player_id.clone(), difficulty))
+    }
+
+    /// Rating shown next to a player, anonymous players have none
+    pub(super) fn player_rating(
+        &self,
+        player_game: &WsPlayerGame,
+        difficulty: Difficulty,
+    ) -> Option<f64> {
+        player_game
+            .player_id
+            .as_ref()
+            .map(|player_id| self.rating(player_id, difficulty).rating)
+    }
+
+    fn is_account(&self, player_id: &PlayerId) -> bool {
+        self.accounts
+            .values()
+            .any(|account| account.player_id == *player_id)
+    }
+
+    /// Updates the ratings of everyone in a race once all of them have finished
+    pub(super) fn rate_game(&mut self, game_code: u16) {
+        let Some(game) = self.games.get(&game_code) else {
+            return;
+        };
+
+        // Replaying a board everyone has already seen says little about skill
+        if game.rated || game.unranked || !game.is_finished() {
+            return;
+        }
+
+        let difficulty = Difficulty::from_board_size(game.config.board_size);
+        let standings = game.standings();
+        let performance = |standing: &Standing| {
+            (
+                standing.won,
+                standing.time_ms.filter(|_| standing.won),
+                standing.revealed,
+            )
+        };
+
+        let mut placements = Vec::<(usize, PlayerId)>::new();
+        for (index, standing) in standings.iter().enumerate() {
+            let place = standings
+                .iter()
+                .position(|other| performance(other) == performance(standing))
+                .unwrap_or(index);
+            let player_id = game
+                .players
+                .values()
+                .find(|player_game| player_game.name == standing.name)
+                .and_then(|player_game| player_game.player_id.clone())
+                .filter(|player_id| self.is_account(player_id));
+
+            if let Some(player_id) = player_id {
+                if placements.iter().all(|(_, placed)| *placed != player_id) {
+                    placements.push((place, player_id));
+                }
+            }
+        }
+
+        // A race needs at least two rated players to say anything about skill
+        if placements.len() < 2 {
+            return;
+        }
+        if let Some(game) = self.games.get_mut(&game_code) {
+            game.rated = true;
+        }
+
+        let ratings = placements
+            .iter()
+            .map(|(place, player_id)| (*place, self.rating(player_id, difficulty)))
+            .collect::<Vec<(usize, Rating)>>();
+        let updated = rate_placements(
+            &ratings
+                .iter()
+                .map(|(place, rating)| (*place, rating))
+                .collect::<Vec<(usize, &Rating)>>(),
+        );
+
+        let now = SystemTime::now();
+
+        for ((_, rating), rating_after) in ratings.into_iter().zip(updated) {
+            let change = RatingChange {
+                player_id: rating.player_id.clone(),
+                difficulty,
+                game_code,
+                rating_before: rating.rating,
+                rating_after,
+                time: now,
+            };
+            let rating = Rating {
+                rating: rating_after,
+                games: rating.games + 1,
+                ..rating
+            };
+
+            if let Err(err) = self.storage.save_rating(&rating, &change) {
+                log::error!("Failed to save rating from game {:X}: {}", game_code, err);
+            }
+
+            self.ratings
+                .insert((rating.player_id.clone(), difficulty), rating);
+        }
+
+        self.persist_game(game_code);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rating(rating: f64, games: usize) -> Rating {
+        Rating {
+            player_id: PlayerId::new(format!("{}", rating)),
+            difficulty: Difficulty::Beginner,
+            rating,
+            games,
+        }
+    }
+
+    #[test]
+    fn test_equal_players() {
+        let winner = rating(1500.0, 20);
+        let loser = rating(1500.0, 20);
+
+        assert_eq!(
+            rate_placements(&[(0, &winner), (1, &loser)]),
+            vec![1510.0, 1490.0]
+        );
+    }
+
+    #[test]
+    fn test_tied_players_draw() {
+        let first = rating(1500.0, 20);
+        let second = rating(1500.0, 20);
+        let stronger = rating(1600.0, 20);
+
+        assert_eq!(
+            rate_placements(&[(0, &first), (0, &second)]),
+            vec![1500.0, 1500.0]
+        );
+
+        let ratings = rate_placements(&[(0, &first), (0, &stronger)]);
+        assert!(ratings[0] > first.rating);
+        assert!(ratings[1] < stronger.rating);
+    }
+
+    #[test]
+    fn test_upset_moves_more_than_expected_result() {
+        let strong = rating(1800.0, 20);
+        let weak = rating(1400.0, 20);
+
+        let expected = rate_placements(&[(0, &strong), (1, &weak)]);
+        let upset = rate_placements(&[(0, &weak), (1, &strong)]);
+
+        assert!(upset[0] - weak.rating > expected[0] - strong.rating);
+    }
+
+    #[test]
+    fn test_provisional_ratings_move_faster() {
+        let new = rating(1500.0, 0);
+        let established = rating(1500.0, 20);
+
+        let ratings = rate_placements(&[(0, &new), (1, &established)]);
+
+        assert_eq!(ratings, vec![1520.0, 1490.0]);
+    }
+
+    #[test]
+    fn test_race_is_scored_pairwise() {
+        let players = [rating(1500.0, 20), rating(1500.0, 20), rating(1500.0, 20)];
+
+        let ratings = rate_placements(
+            &players
+                .iter()
+                .enumerate()
+                .collect::<Vec<(usize, &Rating)>>(),
+        );
+
+        assert_eq!(ratings, vec![1510.0, 1500.0, 1490.0]);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+use std::time::SystemTime;
+
+use actix::{Context, Handler, Message};
+use serde::{Deserialize, Serialize};
+
+use minesweeper_core::Board;
+
+use super::{GameHandler, WsGame};
+
+/// A rematch one of the players asked for, waiting on everyone else to agree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RematchProposal {
+    /// Whether the rematch is played on the same layout as the last game
+    pub same_board: bool,
+    /// Codes of the players who agreed so far, including the one who asked
+    pub accepted: BTreeSet<u16>,
+}
+
+/// Asks for a rematch, or agrees to the one already asked for. `same_board` is
+/// only used by whoever asks first.
+pub struct RequestRematch {
+    pub game_code: u16,
+    pub player_code: u16,
+    pub same_board: bool,
+}
+
+#[derive(Debug)]
+pub enum RematchError {
+    NoSuchGame,
+    NoSuchPlayer,
+    GameInProgress,
+    /// The daily challenge only gets a single attempt
+    DailyChallenge,
+}
+
+impl Message for RequestRematch {
+    type Result = Result<(), RematchError>;
+}
+
+impl WsGame {
+    /// Starts the game again for everyone who is still connected, on a fresh
+    /// board unless the proposal asked for the same one
+    fn restart(&mut self, same_board: bool, now: SystemTime) {
+        if !same_board {
+            self.board = Board::generate(self.config.board_size);
+        }
+
+        self.players.retain(|_, player_game| !player_game.left);
+        self.rematch = None;
+        self.deadline = None;
+        self.rated = false;
+        // Everyone has seen where the mines are on the same board
+        self.unranked = same_board;
+        self.start_blitz_clock(now);
+
+        for player_game in self.players.values_mut() {
+            player_game.board = self.board.clone();
+            player_game.start_time = now;
+            player_game.finished_time = None;
+            player_game.moves.clear();
+            player_game.hints_used = 0;
+            player_game.last_hint = None;
+        }
+    }
+}
+
+impl Handler<RequestRematch> for GameHandler {
+    type Result = Result<(), RematchError>;
+
+    fn handle(&mut self, msg: RequestRematch, ctx: &mut Self::Context) -> Self::Result {
+        let game = self
+            .games
+            .get_mut(&msg.game_code)
+            .ok_or(RematchError::NoSuchGame)?;
+
+        if !game.players.contains_key(&msg.player_code) {
+            return Err(RematchError::NoSuchPlayer);
+        }
+
+        if !game.is_finished() {
+            return Err(RematchError::GameInProgress);
+        }
+
+        if game.daily.is_some() {
+            return Err(RematchError::DailyChallenge);
+        }
+
+        let proposal = game.rematch.get_or_insert_with(|| RematchProposal {
+            same_board: msg.same_board,
+            accepted: BTreeSet::new(),
+        });
+        proposal.accepted.insert(msg.player_code);
+
+        self.start_agreed_rematch(ctx, msg.game_code);
+
+        self.persist_game(msg.game_code);
+        self.notify_game(msg.game_code, Some(msg.player_code));
+
+        Ok(())
+    }
+}
+
+impl GameHandler {
+    /// Starts the proposed rematch of a game once every player who is still
+    /// connected agreed to it, leaving behind those who left. Returns whether
+    /// it started.
+    pub(super) fn start_agreed_rematch(&mut self, ctx: &mut Context<Self>, game_code: u16) -> bool {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return false;
+        };
+        let Some(proposal) = &game.rematch else {
+            return false;
+        };
+
+        let same_board = proposal.same_board;
+        let everyone_accepted = game
+            .players
+            .iter()
+            .filter(|(_, player_game)| !player_game.left)
+            .all(|(player_code, _)| proposal.accepted.contains(player_code));
+
+        if !everyone_accepted {
+            return false;
+        }
+
+        game.restart(same_board, SystemTime::now());
+
+        let player_codes = game.players.keys().copied().collect::<Vec<u16>>();
+        for player_code in player_codes {
+            self.schedule_expiry(ctx, game_code, player_code);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,157 @@
+//! Games a player starts on their own. They skip the lobby, so play starts as
+//! soon as the player connects, and wins count towards personal bests.
+
+use std::time::SystemTime;
+
+use actix::{Handler, Message};
+use rand::Rng;
+
+use crate::storage::StorageError;
+use minesweeper_core::PersonalBests;
+
+use super::{
+    Clock, GameConfig, GameHandler, NameError, NoFreeCode, PlayerTokenError, Visibility, WsGame,
+    WsPlayerGame, DEFAULT_HINT_LIMIT, DEFAULT_HINT_PENALTY_SECS,
+};
+
+#[derive(Debug)]
+pub enum SoloError {
+    /// The board size is outside the server's limits
+    InvalidBoardSize,
+    /// Only players with an identity or session have personal bests
+    Anonymous,
+    Token(PlayerTokenError),
+    InvalidName(NameError),
+    NoFreeCode,
+    Storage(StorageError),
+}
+
+impl From<NoFreeCode> for SoloError {
+    fn from(_: NoFreeCode) -> Self {
+        SoloError::NoFreeCode
+    }
+}
+
+impl From<PlayerTokenError> for SoloError {
+    fn from(err: PlayerTokenError) -> Self {
+        SoloError::Token(err)
+    }
+}
+
+impl From<StorageError> for SoloError {
+    fn from(err: StorageError) -> Self {
+        SoloError::Storage(err)
+    }
+}
+
+/// Starts a game with a single player in it
+pub struct PlaySolo {
+    pub board_size: usize,
+    pub player_name: String,
+    pub identity_token: Option<String>,
+    pub session_token: Option<String>,
+}
+
+/// Game and player codes of a solo game
+pub struct SoloAttempt {
+    pub game_code: u16,
+    pub player_code: u16,
+}
+
+impl Message for PlaySolo {
+    type Result = Result<SoloAttempt, SoloError>;
+}
+
+impl Handler<PlaySolo> for GameHandler {
+    type Result = Result<SoloAttempt, SoloError>;
+
+    fn handle(&mut self, msg: PlaySolo, _ctx: &mut Self::Context) -> Self::Result {
+        let player =
+            self.resolve_player(msg.session_token.as_deref(), msg.identity_token.as_deref())?;
+        let (player_id, username) = match player {
+            Some((player_id, username)) => (Some(player_id), username),
+            None => (None, None),
+        };
+
+        let player_name = match username {
+            Some(username) => username,
+            None => self
+                .anonymous_name(&msg.player_name)
+                .map_err(SoloError::InvalidName)?,
+        };
+
+        let config = GameConfig {
+            board_size: msg.board_size,
+            player_limit: 1,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Private,
+        };
+        if !self.limits.allow(&config) {
+            return Err(SoloError::InvalidBoardSize);
+        }
+
+        let game_code = self.new_game_code()?;
+        let player_code = rand::thread_rng().gen_range(0u16..0xffffu16);
+
+        let mut game = WsGame::new(config);
+        game.solo = true;
+        game.players.insert(
+            player_code,
+            WsPlayerGame {
+                board: game.board.clone(),
+                name: player_name,
+                start_time: SystemTime::now(),
+                finished_time: None,
+                moves: Vec::new(),
+                hints_used: 0,
+                last_hint: None,
+                player_id,
+                left: false,
+            },
+        );
+
+        self.games.insert(game_code, game);
+        self.persist_game(game_code);
+
+        Ok(SoloAttempt {
+            game_code,
+            player_code,
+        })
+    }
+}
+
+/// Fastest solo clears of the player asking, leaving out results flagged as
+/// suspicious
+pub struct GetPersonalBests {
+    pub identity_token: Option<String>,
+    pub session_token: Option<String>,
+}
+
+impl Message for GetPersonalBests {
+    type Result = Result<PersonalBests, SoloError>;
+}
+
+impl Handler<GetPersonalBests> for GameHandler {
+    type Result = Result<PersonalBests, SoloError>;
+
+    fn handle(&mut self, msg: GetPersonalBests, _ctx: &mut Self::Context) -> Self::Result {
+        let (player_id, _) = self
+            .resolve_player(msg.session_token.as_deref(), msg.identity_token.as_deref())?
+            .ok_or(SoloError::Anonymous)?;
+
+        let mut bests = PersonalBests::default();
+        for result in self.storage.load_results()?.iter().filter(|result| {
+            result.solo
+                && result.won
+                && !result.is_flagged()
+                && !result.unranked
+                && result.player_id.as_ref() == Some(&player_id)
+        }) {
+            bests.record(result.board_size, result.duration().as_millis() as u64);
+        }
+
+        Ok(bests)
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::SystemTime;
+
+use actix::{Handler, Message, Recipient};
+
+use super::GameHandler;
+
+/// Sent to subscribers whenever something they can see in a game changes
+pub struct GameUpdated;
+
+impl Message for GameUpdated {
+    type Result = ();
+}
+
+/// Registers a player's connection to be told about changes to their game
+pub struct Subscribe {
+    pub game_code: u16,
+    pub player_code: u16,
+    pub recipient: Recipient<GameUpdated>,
+}
+
+impl Message for Subscribe {
+    type Result = ();
+}
+
+pub struct Unsubscribe {
+    pub game_code: u16,
+    pub player_code: u16,
+}
+
+impl Message for Unsubscribe {
+    type Result = ();
+}
+
+impl Handler<Subscribe> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: Subscribe, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(player_game) = self
+            .games
+            .get_mut(&msg.game_code)
+            .and_then(|game| game.players.get_mut(&msg.player_code))
+        {
+            player_game.left = false;
+        }
+
+        self.subscribers
+            .entry(msg.game_code)
+            .or_default()
+            .insert(msg.player_code, msg.recipient);
+    }
+}
+
+impl Handler<Unsubscribe> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: Unsubscribe, ctx: &mut Self::Context) -> Self::Result {
+        if let Some(subscribers) = self.subscribers.get_mut(&msg.game_code) {
+            subscribers.remove(&msg.player_code);
+
+            if subscribers.is_empty() {
+                self.subscribers.remove(&msg.game_code);
+            }
+        }
+
+        if let Some(player_game) = self
+            .games
+            .get_mut(&msg.game_code)
+            .and_then(|game| game.players.get_mut(&msg.player_code))
+        {
+            player_game.left = true;
+        }
+        self.forfeit(msg.game_code, msg.player_code);
+
+        // Whoever left no longer holds up a rematch the others agreed to
+        if self.start_agreed_rematch(ctx, msg.game_code) {
+            self.notify_game(msg.game_code, None);
+        }
+        self.persist_game(msg.game_code);
+
+        self.replace_host(msg.game_code, msg.player_code);
+    }
+}
+
+impl GameHandler {
+    /// Ends the board of a player who left a race part way through. The race
+    /// is only decided, and rated, once every board is finished, so otherwise
+    /// whoever is losing could hold it up forever by leaving. Solo games and
+    /// daily attempts are the player's alone and carry on when they return.
+    fn forfeit(&mut self, game_code: u16, player_code: u16) {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return;
+        };
+
+        if game.waiting || game.solo || game.daily.is_some() {
+            return;
+        }
+
+        let Some(player_game) = game.players.get_mut(&player_code) else {
+            return;
+        };
+
+        if player_game.board.is_finished() {
+            return;
+        }
+
+        player_game.board.end();
+        self.finish_player(game_code, player_code, SystemTime::now());
+        self.notify_game(game_code, None);
+    }
+
+    /// Tells every connection subscribed to a game, other than `except`, that it changed
+    pub(super) fn notify_game(&self, game_code: u16, except: Option<u16>) {
+        let Some(subscribers) = self.subscribers.get(&game_code) else {
+            return;
+        };
+
+        for (&player_code, recipient) in subscribers {
+            if Some(player_code) != except {
+                recipient.do_send(GameUpdated);
+            }
+        }
+    }
+}
@@ -1,8 +1,25 @@
 use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
 
-use actix::{Actor, Handler, Message};
+use actix::{Actor, Context, Handler, Message};
 
-use super::{CreateGame, GameConfig, GameHandler, WsGame};
+use minesweeper_core::Board;
+
+use crate::storage::{SqliteStorage, Storage};
+
+use super::{
+    hash_new_password, hash_password, hash_token, verify_password, Account, AccountError, Clock,
+    CreateGame, CreateGameError, CreateIdentity, CreateTournament, CreateTournamentError,
+    DailyError, Difficulty, EnterQueue, GameConfig, GameHandler, GameResult,
+    GetAccountPasswordHash, GetAnalysis, GetAnalysisError, GetDailyChallenge, GetDailyLeaderboard,
+    GetGameState, GetLeaderboard, GetMatchHistory, GetPasswordHash, GetPersonalBests,
+    GetPlayerStats, GetRatings, GetResults, GetSpectatorState, GetSpectatorStateError,
+    GetTournament, HostAction, HostCommand, ImportGame, JoinGame, JoinGameError, ListGames, Login,
+    Logout, MatchFound, NameError, Period, PlayDaily, PlaySolo, PlayerAction, PlayerId, PlayerMove,
+    PlayerRef, PlayerTokenError, Rating, Register, RequestRematch, Session, SoloError,
+    TournamentFormat, Unsubscribe, Visibility, WsGame, DEFAULT_HINT_LIMIT,
+    DEFAULT_HINT_PENALTY_SECS, SESSION_TTL,
+};
 
 struct GetSnapshot;
 
@@ -17,6 +34,82 @@ impl Handler<GetSnapshot> for GameHandler {
     }
 }
 
+/// Runs the sweep for stale games as though it were the given time
+struct EvictGames(SystemTime);
+
+impl Message for EvictGames {
+    type Result = ();
+}
+
+impl Handler<EvictGames> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: EvictGames, _ctx: &mut Self::Context) -> Self::Result {
+        self.evict_games(msg.0);
+    }
+}
+
+/// Settles overdue tournament rounds as though it were the given time
+struct EnforceRoundDeadlines(SystemTime);
+
+impl Message for EnforceRoundDeadlines {
+    type Result = ();
+}
+
+impl Handler<EnforceRoundDeadlines> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: EnforceRoundDeadlines, _ctx: &mut Self::Context) -> Self::Result {
+        self.enforce_round_deadlines(msg.0);
+    }
+}
+
+/// Takes every game code, as though the server had run out of them
+struct FillGameCodes;
+
+impl Message for FillGameCodes {
+    type Result = ();
+}
+
+impl Handler<FillGameCodes> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, _msg: FillGameCodes, _ctx: &mut Self::Context) -> Self::Result {
+        let game = WsGame::with_board(
+            GameConfig {
+                board_size: 2,
+                player_limit: 1,
+                hint_limit: 0,
+                hint_penalty_secs: 0,
+                clock: Clock::default(),
+                visibility: Visibility::Private,
+            },
+            Board::from_mines(2, &[0]).unwrap(),
+        );
+
+        for code in 0..=u16::MAX {
+            self.games.insert(code, game.clone());
+        }
+    }
+}
+
+/// Ranks an imported game, so tests can race on a layout they know
+struct RankGame(u16);
+
+impl Message for RankGame {
+    type Result = ();
+}
+
+impl Handler<RankGame> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: RankGame, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(game) = self.games.get_mut(&msg.0) {
+            game.unranked = false;
+        }
+    }
+}
+
 #[actix_rt::test]
 async fn can_create_game() {
     let game_handler = GameHandler::default();
@@ -26,6 +119,12 @@ async fn can_create_game() {
         .send(CreateGame {
             board_size: 8,
             player_limit: 3,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Public,
+            password_hash: None,
+            lobby: false,
         })
         .await;
 
@@ -34,11 +133,12 @@ async fn can_create_game() {
     let result = result.unwrap();
     assert!(result.is_ok());
 
-    let id = result.unwrap();
+    let id = result.unwrap().code;
 
     let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
 
     assert_eq!(games.len(), 1);
+    assert!(!games[&id].waiting);
 
     let config = &games.get(&id).unwrap().config;
 
@@ -46,7 +146,1992 @@ async fn can_create_game() {
         *config,
         GameConfig {
             board_size: 8,
-            player_limit: 3
+            player_limit: 3,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Public,
         }
     );
 }
+
+#[actix_rt::test]
+async fn can_create_game_with_a_clock() {
+    let game_handler_addr = GameHandler::default().start();
+
+    for clock in [
+        Clock::PerPlayer { limit_secs: 60 },
+        Clock::Blitz { limit_secs: 120 },
+    ] {
+        let created = game_handler_addr
+            .send(CreateGame {
+                board_size: 8,
+                player_limit: 3,
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock,
+                visibility: Visibility::Private,
+                password_hash: None,
+                lobby: false,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+
+        assert_eq!(games.get(&created.code).unwrap().config.clock, clock);
+    }
+}
+
+#[actix_rt::test]
+async fn records_result_when_board_is_cleared() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 1,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let player_code = game_handler_addr
+        .send(JoinGame::new(game_code, "alice".to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    for index in [1, 2, 3] {
+        let result = game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap();
+
+        assert!(result.is_ok());
+    }
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].player_name, "alice");
+    assert!(results[0].won);
+    assert_eq!(results[0].bbbv, 3);
+    assert_eq!(results[0].clicks, 3);
+}
+
+#[actix_rt::test]
+async fn ends_game_when_clock_runs_out() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let created = game_handler_addr
+        .send(CreateGame {
+            board_size: 4,
+            player_limit: 1,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::PerPlayer { limit_secs: 0 },
+            visibility: Visibility::Private,
+            password_hash: None,
+            lobby: false,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let game_code = created.code;
+
+    let player_code = game_handler_addr
+        .send(JoinGame::new(game_code, "alice".to_string()).with_host_token(created.host_token))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let result = game_handler_addr
+        .send(PlayerMove {
+            game_code,
+            player_code,
+            action: PlayerAction::Reveal { index: 0 },
+        })
+        .await
+        .unwrap();
+
+    assert!(result.is_err());
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].won);
+
+    let state = game_handler_addr
+        .send(GetGameState {
+            game_code,
+            player_code,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let state = serde_json::to_value(state).unwrap();
+    assert!(state["revealed_board"].is_array());
+    assert_eq!(state["standings"][0]["finished"], true);
+}
+
+#[actix_rt::test]
+async fn rematch_starts_once_everyone_agrees() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut player_codes = Vec::new();
+    for name in ["alice", "bob"] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        player_codes.push(player_code);
+    }
+
+    let request_rematch = |player_code| RequestRematch {
+        game_code,
+        player_code,
+        same_board: true,
+    };
+
+    // Nobody can ask for a rematch before everyone has finished
+    assert!(game_handler_addr
+        .send(request_rematch(player_codes[0]))
+        .await
+        .unwrap()
+        .is_err());
+
+    for &player_code in &player_codes {
+        assert!(game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index: 0 },
+            })
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    for &player_code in &player_codes {
+        assert!(game_handler_addr
+            .send(request_rematch(player_code))
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let game = &games[&game_code];
+
+    assert!(game.rematch.is_none());
+    assert_eq!(game.players.len(), 2);
+    for player_game in game.players.values() {
+        assert!(!player_game.board.is_finished());
+        assert!(player_game.moves.is_empty());
+        assert_eq!(player_game.board.mine_positions(), vec![0]);
+    }
+}
+
+#[actix_rt::test]
+async fn rematch_goes_ahead_without_players_who_left() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    game_handler_addr.send(RankGame(game_code)).await.unwrap();
+
+    let mut player_codes = Vec::new();
+    for name in ["alice", "bob"] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        player_codes.push(player_code);
+    }
+
+    let explode = |player_code| PlayerMove {
+        game_code,
+        player_code,
+        action: PlayerAction::Reveal { index: 0 },
+    };
+
+    for &player_code in &player_codes {
+        assert!(game_handler_addr
+            .send(explode(player_code))
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    assert!(game_handler_addr
+        .send(RequestRematch {
+            game_code,
+            player_code: player_codes[0],
+            same_board: true,
+        })
+        .await
+        .unwrap()
+        .is_ok());
+
+    game_handler_addr
+        .send(Unsubscribe {
+            game_code,
+            player_code: player_codes[1],
+        })
+        .await
+        .unwrap();
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let game = &games[&game_code];
+
+    assert!(game.rematch.is_none());
+    assert_eq!(game.players.len(), 1);
+    assert!(game.players.contains_key(&player_codes[0]));
+
+    // Everyone already saw this layout, so the rematch does not count
+    assert!(game_handler_addr
+        .send(explode(player_codes[0]))
+        .await
+        .unwrap()
+        .is_ok());
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.unranked)
+            .collect::<Vec<bool>>(),
+        vec![false, false, true]
+    );
+}
+
+#[actix_rt::test]
+async fn host_controls_the_lobby() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let created = game_handler_addr
+        .send(CreateGame {
+            board_size: 4,
+            player_limit: 3,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Public,
+            password_hash: None,
+            lobby: true,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let game_code = created.code;
+
+    let host = game_handler_addr
+        .send(JoinGame::new(game_code, "alice".to_string()).with_host_token(created.host_token))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+    let guest = game_handler_addr
+        .send(JoinGame::new(game_code, "bob".to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let host_command = |player_code, action| HostCommand {
+        game_code,
+        player_code,
+        action,
+    };
+
+    // Only the host may use host commands, and nobody can move before the start
+    assert!(game_handler_addr
+        .send(host_command(guest, HostAction::Lock))
+        .await
+        .unwrap()
+        .is_err());
+    assert!(game_handler_addr
+        .send(PlayerMove {
+            game_code,
+            player_code: guest,
+            action: PlayerAction::Reveal { index: 0 },
+        })
+        .await
+        .unwrap()
+        .is_err());
+
+    for action in [
+        HostAction::Kick {
+            player_name: "bob".to_string(),
+        },
+        HostAction::Lock,
+    ] {
+        game_handler_addr
+            .send(host_command(host, action))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    assert!(game_handler_addr
+        .send(JoinGame::new(game_code, "carol".to_string()))
+        .await
+        .unwrap()
+        .is_err());
+
+    // A penalty this large would move the player's start out of range
+    assert!(game_handler_addr
+        .send(host_command(
+            host,
+            HostAction::Configure {
+                board_size: None,
+                player_limit: None,
+                hint_limit: None,
+                hint_penalty_secs: Some(u64::MAX),
+                clock: None,
+                visibility: None,
+            }
+        ))
+        .await
+        .unwrap()
+        .is_err());
+
+    for action in [
+        HostAction::Configure {
+            board_size: Some(5),
+            player_limit: None,
+            hint_limit: Some(0),
+            hint_penalty_secs: None,
+            clock: None,
+            visibility: None,
+        },
+        HostAction::Start,
+    ] {
+        game_handler_addr
+            .send(host_command(host, action))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let game = &games[&game_code];
+
+    assert_eq!(game.players.len(), 1);
+    assert_eq!(game.players[&host].board.size(), 5);
+    assert_eq!(game.config.hint_limit, 0);
+    assert!(!game.waiting);
+}
+
+#[actix_rt::test]
+async fn password_is_required_to_join() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let created = game_handler_addr
+        .send(CreateGame {
+            board_size: 4,
+            player_limit: 4,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::default(),
+            visibility: Visibility::Private,
+            password_hash: Some(hash_password("open sesame")),
+            lobby: false,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let password_hash = game_handler_addr
+        .send(GetPasswordHash {
+            game_code: created.code,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(verify_password("open sesame", &password_hash));
+    assert!(!verify_password("sesame", &password_hash));
+
+    // Only the game's own hash proves the password was checked
+    for join_game in [
+        JoinGame::new(created.code, "alice".to_string()),
+        JoinGame::new(created.code, "alice".to_string())
+            .with_verified_password(hash_password("open sesame")),
+    ] {
+        let result = game_handler_addr.send(join_game).await.unwrap();
+        assert!(matches!(result, Err(JoinGameError::WrongPassword)));
+    }
+
+    for join_game in [
+        JoinGame::new(created.code, "alice".to_string()).with_verified_password(password_hash),
+        JoinGame::new(created.code, "bob".to_string()).with_host_token(created.host_token),
+    ] {
+        assert!(game_handler_addr.send(join_game).await.unwrap().is_ok());
+    }
+}
+
+#[actix_rt::test]
+async fn names_are_unique_and_results_follow_identities() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let identity = game_handler_addr
+        .send(CreateIdentity)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 3,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    for join_game in [
+        JoinGame::new(game_code, "\u{7}".to_string()),
+        JoinGame::new(game_code, "alice".to_string()).with_identity_token("nope".to_string()),
+    ] {
+        assert!(game_handler_addr.send(join_game).await.unwrap().is_err());
+    }
+
+    game_handler_addr
+        .send(JoinGame::new(game_code, "Alice".to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let player_code = game_handler_addr
+        .send(JoinGame::new(game_code, "  alice ".to_string()).with_identity_token(identity.token))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    for index in [1, 2, 3] {
+        game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("move"));
+    }
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(results[0].player_name, "alice (2)");
+    assert_eq!(results[0].player_id, Some(identity.player_id));
+}
+
+#[actix_rt::test]
+async fn logged_in_players_join_under_their_username() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let register = |username: &str| Register {
+        username: username.to_string(),
+        password_hash: hash_new_password("correct horse").unwrap(),
+    };
+
+    game_handler_addr
+        .send(register("Alice"))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(matches!(
+        game_handler_addr.send(register("alice")).await.unwrap(),
+        Err(AccountError::UsernameTaken)
+    ));
+    assert!(matches!(
+        hash_new_password("short"),
+        Err(AccountError::WeakPassword)
+    ));
+
+    let password_hash = game_handler_addr
+        .send(GetAccountPasswordHash {
+            username: "alice".to_string(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!verify_password("wrong horse", &password_hash));
+
+    assert!(matches!(
+        game_handler_addr
+            .send(Login {
+                username: "alice".to_string(),
+                password_hash: hash_new_password("correct horse").unwrap(),
+            })
+            .await
+            .unwrap(),
+        Err(AccountError::InvalidCredentials)
+    ));
+
+    let session = game_handler_addr
+        .send(Login {
+            username: "alice".to_string(),
+            password_hash,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let player_code = game_handler_addr
+        .send(
+            JoinGame::new(game_code, "someone else".to_string())
+                .with_session_token(session.token.clone()),
+        )
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let player_game = &games[&game_code].players[&player_code];
+
+    assert_eq!(player_game.name, "Alice");
+    assert_eq!(player_game.player_id, Some(session.player_id));
+
+    game_handler_addr
+        .send(Logout {
+            token: session.token.clone(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let result = game_handler_addr
+        .send(JoinGame::new(game_code, "alice".to_string()).with_session_token(session.token))
+        .await
+        .unwrap();
+    assert!(matches!(
+        result,
+        Err(JoinGameError::Token(PlayerTokenError::InvalidSession))
+    ));
+}
+
+#[actix_rt::test]
+async fn sessions_expire_and_usernames_are_kept_for_their_accounts() {
+    let mut storage = SqliteStorage::open(":memory:").unwrap();
+    let account = Account {
+        username: "Alice".to_string(),
+        password_hash: hash_password("correct horse"),
+        player_id: PlayerId::new("00c0ffee".to_string()),
+        created_time: SystemTime::now(),
+    };
+    storage.save_account(&account).unwrap();
+
+    for (token, age) in [("expired", SESSION_TTL), ("fresh", Duration::ZERO)] {
+        storage
+            .save_session(&Session {
+                token: hash_token(token),
+                username: account.username.clone(),
+                player_id: account.player_id.clone(),
+                created_time: SystemTime::now() - age,
+            })
+            .unwrap();
+    }
+
+    let game_handler_addr = GameHandler::new(Box::new(storage)).unwrap().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let join = |session_token: &str| {
+        JoinGame::new(game_code, "bob".to_string()).with_session_token(session_token.to_string())
+    };
+
+    assert!(matches!(
+        game_handler_addr.send(join("expired")).await.unwrap(),
+        Err(JoinGameError::Token(PlayerTokenError::InvalidSession))
+    ));
+    assert!(matches!(
+        game_handler_addr
+            .send(JoinGame::new(game_code, "ALICE".to_string()))
+            .await
+            .unwrap(),
+        Err(JoinGameError::InvalidName(NameError::Registered))
+    ));
+    assert!(game_handler_addr.send(join("fresh")).await.unwrap().is_ok());
+}
+
+#[actix_rt::test]
+async fn match_history_pages_through_finished_games() {
+    let game_handler_addr = GameHandler::default().start();
+
+    for _ in 0..3 {
+        let game_code = game_handler_addr
+            .send(ImportGame {
+                board: Board::from_mines(2, &[0]).unwrap(),
+                player_limit: 1,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, "alice".to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        assert!(game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index: 0 },
+            })
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    let page = |page, per_page| {
+        let game_handler_addr = game_handler_addr.clone();
+
+        async move {
+            let history = game_handler_addr
+                .send(GetMatchHistory {
+                    player: PlayerRef::Name("alice".to_string()),
+                    page,
+                    per_page,
+                })
+                .await
+                .unwrap()
+                .unwrap();
+
+            serde_json::to_value(history).unwrap()
+        }
+    };
+
+    let history = page(2, 2).await;
+    assert_eq!(history["total"], 3);
+    assert_eq!(history["entries"].as_array().unwrap().len(), 1);
+
+    // Pages far past the end are empty rather than overflowing
+    let history = page(usize::MAX, 100).await;
+    assert!(history["entries"].as_array().unwrap().is_empty());
+}
+
+/// Joins the game and makes a move, which ends it at once on a clock of zero
+async fn join_and_time_out(
+    game_handler_addr: &actix::Addr<GameHandler>,
+    game_code: u16,
+    session_token: &str,
+) {
+    let player_code = game_handler_addr
+        .send(JoinGame::new(game_code, String::new()).with_session_token(session_token.to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let _ = game_handler_addr
+        .send(PlayerMove {
+            game_code,
+            player_code,
+            action: PlayerAction::Reveal { index: 0 },
+        })
+        .await
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn bracket_advances_winners_to_a_champion() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut sessions = HashMap::new();
+    for username in ["alice", "bob", "carol"] {
+        let session = game_handler_addr
+            .send(Register {
+                username: username.to_string(),
+                password_hash: hash_new_password("correct horse").unwrap(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        sessions.insert(username, session.token);
+    }
+
+    assert!(matches!(
+        game_handler_addr
+            .send(CreateTournament {
+                format: TournamentFormat::Bracket,
+                entrants: vec!["alice".to_string(), "dave".to_string()],
+                board_size: 4,
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock: Clock::default(),
+            })
+            .await
+            .unwrap(),
+        Err(CreateTournamentError::UnknownEntrant(_))
+    ));
+
+    assert!(matches!(
+        game_handler_addr
+            .send(CreateTournament {
+                format: TournamentFormat::Bracket,
+                entrants: vec!["alice".to_string(), "bob".to_string()],
+                board_size: 10_000,
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock: Clock::default(),
+            })
+            .await
+            .unwrap(),
+        Err(CreateTournamentError::OutsideLimits)
+    ));
+
+    let tournament_code = game_handler_addr
+        .send(CreateTournament {
+            format: TournamentFormat::Bracket,
+            entrants: vec!["Alice".to_string(), "bob".to_string(), "carol".to_string()],
+            board_size: 4,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::PerPlayer { limit_secs: 0 },
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let tournament_state = || async {
+        let state = game_handler_addr
+            .send(GetTournament { tournament_code })
+            .await
+            .unwrap()
+            .unwrap();
+
+        serde_json::to_value(state).unwrap()
+    };
+    let game_code = |state: &serde_json::Value, index: usize| {
+        u16::from_str_radix(state["matches"][index]["game_code"].as_str().unwrap(), 16).unwrap()
+    };
+
+    // Carol gets a bye, so she cannot join the first match
+    let state = tournament_state().await;
+    assert_eq!(state["matches"][1]["winner"], "carol");
+
+    let first_match = game_code(&state, 0);
+    assert!(game_handler_addr
+        .send(
+            JoinGame::new(first_match, String::new()).with_session_token(sessions["carol"].clone())
+        )
+        .await
+        .unwrap()
+        .is_err());
+
+    // Entrants are known by their account, not the name they give
+    assert!(game_handler_addr
+        .send(JoinGame::new(first_match, "alice".to_string()))
+        .await
+        .unwrap()
+        .is_err());
+
+    // Neither player makes progress, so the tie goes to the name that sorts first
+    join_and_time_out(&game_handler_addr, first_match, &sessions["bob"]).await;
+    join_and_time_out(&game_handler_addr, first_match, &sessions["alice"]).await;
+
+    let state = tournament_state().await;
+    assert_eq!(state["round"], 2);
+    assert_eq!(state["matches"][0]["winner"], "alice");
+    assert_eq!(
+        state["matches"][2]["players"],
+        serde_json::json!(["alice", "carol"])
+    );
+
+    let final_match = game_code(&state, 2);
+    join_and_time_out(&game_handler_addr, final_match, &sessions["carol"]).await;
+    join_and_time_out(&game_handler_addr, final_match, &sessions["alice"]).await;
+
+    let state = tournament_state().await;
+    assert_eq!(state["champion"], "alice");
+    assert_eq!(state["standings"][0]["points"], 2);
+}
+
+#[actix_rt::test]
+async fn lists_only_open_public_games() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut codes = Vec::new();
+    for (player_limit, visibility) in [
+        (2, Visibility::Public),
+        (1, Visibility::Public),
+        (2, Visibility::Private),
+    ] {
+        let code = game_handler_addr
+            .send(CreateGame {
+                board_size: 9,
+                player_limit,
+                hint_limit: DEFAULT_HINT_LIMIT,
+                hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+                clock: Clock::default(),
+                visibility,
+                password_hash: None,
+                lobby: false,
+            })
+            .await
+            .unwrap()
+            .unwrap()
+            .code;
+
+        codes.push(code);
+    }
+
+    // Fills the second game
+    game_handler_addr
+        .send(JoinGame::new(codes[1], "alice".to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    let lobbies = game_handler_addr.send(ListGames).await.unwrap();
+    let lobbies = serde_json::to_value(lobbies).unwrap();
+
+    assert_eq!(lobbies.as_array().unwrap().len(), 1);
+    assert_eq!(lobbies[0]["code"], format!("{:X}", codes[0]));
+    assert_eq!(lobbies[0]["difficulty"], "beginner");
+}
+
+/// Keeps every match a player is told about
+#[derive(Default)]
+struct MatchCollector {
+    found: Vec<MatchFound>,
+}
+
+impl Actor for MatchCollector {
+    type Context = Context<Self>;
+}
+
+impl Handler<MatchFound> for MatchCollector {
+    type Result = ();
+
+    fn handle(&mut self, msg: MatchFound, _ctx: &mut Self::Context) {
+        self.found.push(msg);
+    }
+}
+
+struct TakeMatches;
+
+impl Message for TakeMatches {
+    type Result = Vec<MatchFound>;
+}
+
+impl Handler<TakeMatches> for MatchCollector {
+    type Result = Vec<MatchFound>;
+
+    fn handle(&mut self, _msg: TakeMatches, _ctx: &mut Self::Context) -> Self::Result {
+        std::mem::take(&mut self.found)
+    }
+}
+
+#[actix_rt::test]
+async fn matchmaking_groups_players_by_difficulty() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut collectors = Vec::new();
+    for (name, difficulty) in [
+        ("alice", Difficulty::Beginner),
+        ("bob", Difficulty::Expert),
+        ("carol", Difficulty::Beginner),
+    ] {
+        let collector = MatchCollector::default().start();
+
+        game_handler_addr
+            .send(EnterQueue {
+                difficulty,
+                player_name: name.to_string(),
+                identity_token: None,
+                session_token: None,
+                recipient: collector.clone().recipient(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        collectors.push(collector);
+    }
+
+    let mut found = Vec::new();
+    for collector in &collectors {
+        found.push(collector.send(TakeMatches).await.unwrap());
+    }
+
+    // Bob is still waiting for another expert
+    assert!(found[1].is_empty());
+    assert_eq!(found[0].len(), 1);
+    assert_eq!(found[0][0].game_code, found[2][0].game_code);
+    assert_eq!(found[0][0].players, ["alice", "carol"]);
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let game_code = u16::from_str_radix(&found[0][0].game_code, 16).unwrap();
+    assert_eq!(games[&game_code].config.board_size, 9);
+    assert_eq!(games[&game_code].config.player_limit, 2);
+}
+
+struct SetRating(Rating);
+
+impl Message for SetRating {
+    type Result = ();
+}
+
+impl Handler<SetRating> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetRating, _ctx: &mut Self::Context) -> Self::Result {
+        self.ratings
+            .insert((msg.0.player_id.clone(), msg.0.difficulty), msg.0);
+    }
+}
+
+#[actix_rt::test]
+async fn matchmaking_pairs_similar_ratings() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut collectors = Vec::new();
+    for (name, rating) in [("alice", 1500.0), ("bob", 2000.0), ("carol", 1550.0)] {
+        let identity = game_handler_addr
+            .send(CreateIdentity)
+            .await
+            .unwrap()
+            .unwrap();
+
+        game_handler_addr
+            .send(SetRating(Rating {
+                player_id: identity.player_id,
+                difficulty: Difficulty::Beginner,
+                rating,
+                games: 20,
+            }))
+            .await
+            .unwrap();
+
+        let collector = MatchCollector::default().start();
+
+        game_handler_addr
+            .send(EnterQueue {
+                difficulty: Difficulty::Beginner,
+                player_name: name.to_string(),
+                identity_token: Some(identity.token),
+                session_token: None,
+                recipient: collector.clone().recipient(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        collectors.push(collector);
+    }
+
+    let mut found = Vec::new();
+    for collector in &collectors {
+        found.push(collector.send(TakeMatches).await.unwrap());
+    }
+
+    // Bob is rated too far above the others to be matched straight away
+    assert!(found[1].is_empty());
+    assert_eq!(found[0][0].players, ["alice", "carol"]);
+    assert_eq!(found[0][0].game_code, found[2][0].game_code);
+}
+
+#[actix_rt::test]
+async fn identities_take_one_place_in_a_game() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let identity = game_handler_addr
+        .send(CreateIdentity)
+        .await
+        .unwrap()
+        .unwrap();
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 3,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let join = || {
+        JoinGame::new(game_code, "alice".to_string()).with_identity_token(identity.token.clone())
+    };
+
+    assert!(game_handler_addr.send(join()).await.unwrap().is_ok());
+    assert!(matches!(
+        game_handler_addr.send(join()).await.unwrap(),
+        Err(JoinGameError::AlreadyJoined)
+    ));
+}
+
+#[actix_rt::test]
+async fn races_update_ratings_of_logged_in_players() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 3,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    game_handler_addr.send(RankGame(game_code)).await.unwrap();
+
+    let mut players = Vec::new();
+    for name in ["alice", "bob", "carol"] {
+        // Carol only has an identity, so she is left out of the ratings
+        let (join_game, player_id) = if name == "carol" {
+            let identity = game_handler_addr
+                .send(CreateIdentity)
+                .await
+                .unwrap()
+                .unwrap();
+
+            (
+                JoinGame::new(game_code, name.to_string()).with_identity_token(identity.token),
+                identity.player_id,
+            )
+        } else {
+            let session = game_handler_addr
+                .send(Register {
+                    username: name.to_string(),
+                    password_hash: hash_new_password("correct horse").unwrap(),
+                })
+                .await
+                .unwrap()
+                .unwrap();
+
+            (
+                JoinGame::new(game_code, String::new()).with_session_token(session.token),
+                session.player_id,
+            )
+        };
+
+        let player_code = game_handler_addr
+            .send(join_game)
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        players.push((player_code, player_id));
+    }
+
+    // Carol wins outright, Alice clears her board after her and Bob hits the mine
+    for (player, indices) in [(2, vec![1, 2, 3]), (0, vec![1, 2, 3]), (1, vec![0])] {
+        for index in indices {
+            game_handler_addr
+                .send(PlayerMove {
+                    game_code,
+                    player_code: players[player].0,
+                    action: PlayerAction::Reveal { index },
+                })
+                .await
+                .unwrap()
+                .unwrap_or_else(|_| panic!("move"));
+        }
+    }
+
+    let mut ratings = Vec::new();
+    for (_, player_id) in &players[..2] {
+        ratings.push(
+            game_handler_addr
+                .send(GetRatings {
+                    player_id: player_id.clone(),
+                    difficulty: None,
+                })
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+    }
+
+    let alice = &ratings[0];
+    assert_eq!(alice.ratings.len(), 1);
+    assert_eq!(alice.ratings[0].difficulty, Difficulty::Beginner);
+    assert_eq!(alice.ratings[0].rating, 1520.0);
+    assert!(alice.ratings[0].provisional);
+    assert_eq!(alice.history.len(), 1);
+    assert_eq!(alice.history[0].game_code, game_code);
+
+    assert_eq!(ratings[1].ratings[0].rating, 1480.0);
+
+    let carol = game_handler_addr
+        .send(GetRatings {
+            player_id: players[2].1.clone(),
+            difficulty: None,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(carol.ratings.is_empty());
+}
+
+#[actix_rt::test]
+async fn daily_challenge_is_played_once() {
+    let game_handler_addr = GameHandler::default()
+        .with_daily_secret("a secret for testing")
+        .start();
+
+    let session = game_handler_addr
+        .send(Register {
+            username: "alice".to_string(),
+            password_hash: hash_new_password("correct horse").unwrap(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    let play_daily = |session_token: Option<String>| PlayDaily { session_token };
+
+    assert!(matches!(
+        game_handler_addr.send(play_daily(None)).await.unwrap(),
+        Err(DailyError::Anonymous)
+    ));
+
+    let attempt = game_handler_addr
+        .send(play_daily(Some(session.token.clone())))
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Coming back before finishing carries on with the same game
+    let resumed = game_handler_addr
+        .send(play_daily(Some(session.token.clone())))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (resumed.game_code, resumed.player_code),
+        (attempt.game_code, attempt.player_code)
+    );
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let game = &games[&attempt.game_code];
+    let day = game.daily.unwrap();
+    let board = &game.board;
+    assert_eq!(
+        board.mine_positions(),
+        super::daily::daily_board(b"a secret for testing", day).mine_positions()
+    );
+    // The date alone does not give the board away
+    assert_ne!(
+        board.mine_positions(),
+        Board::generate_seeded(board.size(), day).mine_positions()
+    );
+
+    let mines = board.mine_positions();
+    for index in (0..board.size() * board.size()).filter(|index| !mines.contains(index)) {
+        // Cells opened by an earlier reveal are rejected, which is fine here
+        let _ = game_handler_addr
+            .send(PlayerMove {
+                game_code: attempt.game_code,
+                player_code: attempt.player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap();
+    }
+
+    assert!(matches!(
+        game_handler_addr
+            .send(play_daily(Some(session.token.clone())))
+            .await
+            .unwrap(),
+        Err(DailyError::AlreadyPlayed)
+    ));
+
+    // Nothing shows the layout to anyone else while the day lasts
+    let state = game_handler_addr
+        .send(GetGameState {
+            game_code: attempt.game_code,
+            player_code: attempt.player_code,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(state.revealed_board.is_none());
+    assert!(matches!(
+        game_handler_addr
+            .send(GetSpectatorState {
+                game_code: attempt.game_code,
+            })
+            .await
+            .unwrap(),
+        Err(GetSpectatorStateError::DailyInProgress)
+    ));
+    assert!(matches!(
+        game_handler_addr
+            .send(GetAnalysis {
+                game_code: attempt.game_code,
+                player_name: "alice".to_string(),
+                at_move: None,
+            })
+            .await
+            .unwrap(),
+        Err(GetAnalysisError::GameInProgress)
+    ));
+
+    let challenge = game_handler_addr
+        .send(GetDailyChallenge {
+            identity_token: None,
+            session_token: Some(session.token),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(challenge.completed);
+    assert_eq!(challenge.finished_count, 1);
+    // Clearing the board this quickly gets the result flagged
+    assert!(challenge.personal_result.is_none());
+
+    let leaderboard = game_handler_addr
+        .send(GetDailyLeaderboard {
+            day: Some(day),
+            limit: 10,
+            include_flagged: true,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(leaderboard.top.len(), 1);
+    assert_eq!(leaderboard.top[0].player_id, Some(session.player_id));
+}
+
+struct RecordResult(GameResult);
+
+impl Message for RecordResult {
+    type Result = ();
+}
+
+impl Handler<RecordResult> for GameHandler {
+    type Result = ();
+
+    fn handle(&mut self, msg: RecordResult, _ctx: &mut Self::Context) -> Self::Result {
+        self.record_result(msg.0);
+    }
+}
+
+#[actix_rt::test]
+async fn solo_games_skip_the_lobby_and_keep_personal_bests() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let identity = game_handler_addr
+        .send(CreateIdentity)
+        .await
+        .unwrap()
+        .unwrap();
+    let play_solo = |board_size: usize| PlaySolo {
+        board_size,
+        player_name: "alice".to_string(),
+        identity_token: Some(identity.token.clone()),
+        session_token: None,
+    };
+
+    assert!(matches!(
+        game_handler_addr.send(play_solo(1)).await.unwrap(),
+        Err(SoloError::InvalidBoardSize)
+    ));
+
+    let attempt = game_handler_addr.send(play_solo(8)).await.unwrap().unwrap();
+
+    let state = game_handler_addr
+        .send(GetGameState {
+            game_code: attempt.game_code,
+            player_code: attempt.player_code,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(state.started);
+    assert!(state.host.is_none());
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    let board = &games[&attempt.game_code].board;
+    let mines = board.mine_positions();
+    for index in (0..board.size() * board.size()).filter(|index| !mines.contains(index)) {
+        let _ = game_handler_addr
+            .send(PlayerMove {
+                game_code: attempt.game_code,
+                player_code: attempt.player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap();
+    }
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(attempt.game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].solo && results[0].won);
+
+    let get_personal_bests = |identity_token: Option<String>| GetPersonalBests {
+        identity_token,
+        session_token: None,
+    };
+
+    // Clearing the board this quickly gets the result flagged
+    let bests = game_handler_addr
+        .send(get_personal_bests(Some(identity.token.clone())))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bests.get(8), None);
+
+    game_handler_addr
+        .send(RecordResult(GameResult {
+            suspicions: Vec::new(),
+            ..results[0].clone()
+        }))
+        .await
+        .unwrap();
+
+    let bests = game_handler_addr
+        .send(get_personal_bests(Some(identity.token)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(bests.get(8), Some(results[0].duration().as_millis() as u64));
+
+    assert!(matches!(
+        game_handler_addr
+            .send(get_personal_bests(None))
+            .await
+            .unwrap(),
+        Err(SoloError::Anonymous)
+    ));
+}
+
+/// Payloads sent while a game is in progress must not depend on where the
+/// unrevealed mines are
+#[actix_rt::test]
+async fn payloads_do_not_leak_unrevealed_mines() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut payloads = Vec::new();
+
+    // Both layouts show a 1 in the centre once it is revealed
+    for mines in [[2], [6]] {
+        let game_code = game_handler_addr
+            .send(ImportGame {
+                board: Board::from_mines(3, &mines).unwrap(),
+                player_limit: 2,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut player_codes = Vec::new();
+        for name in ["alice", "bob"] {
+            let player_code = game_handler_addr
+                .send(JoinGame::new(game_code, name.to_string()))
+                .await
+                .unwrap()
+                .unwrap_or_else(|_| panic!("join game"));
+
+            player_codes.push(player_code);
+        }
+
+        for action in [
+            PlayerAction::Reveal { index: 4 },
+            PlayerAction::Flag { index: 0 },
+        ] {
+            assert!(game_handler_addr
+                .send(PlayerMove {
+                    game_code,
+                    player_code: player_codes[0],
+                    action,
+                })
+                .await
+                .unwrap()
+                .is_ok());
+        }
+
+        let mut game_payloads = Vec::new();
+
+        for &player_code in &player_codes {
+            let state = game_handler_addr
+                .send(GetGameState {
+                    game_code,
+                    player_code,
+                })
+                .await
+                .unwrap()
+                .unwrap();
+
+            let mut state = serde_json::to_value(state).unwrap();
+            state.as_object_mut().unwrap().remove("start_time");
+            game_payloads.push(state);
+        }
+
+        let spectator_state = game_handler_addr
+            .send(GetSpectatorState { game_code })
+            .await
+            .unwrap()
+            .unwrap();
+        game_payloads.push(serde_json::to_value(spectator_state).unwrap());
+
+        payloads.push(game_payloads);
+    }
+
+    assert_eq!(payloads[0], payloads[1]);
+}
+
+#[actix_rt::test]
+async fn analysis_waits_for_every_player_to_finish() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(3, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut player_codes = Vec::new();
+    for name in ["alice", "bob"] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        player_codes.push(player_code);
+    }
+
+    let explode = |player_code| PlayerMove {
+        game_code,
+        player_code,
+        action: PlayerAction::Reveal { index: 0 },
+    };
+    let analysis = || GetAnalysis {
+        game_code,
+        player_name: "alice".to_string(),
+        at_move: None,
+    };
+
+    assert!(game_handler_addr
+        .send(explode(player_codes[0]))
+        .await
+        .unwrap()
+        .is_ok());
+
+    // Alice is done, but her analysis would show bob where the mines are
+    assert!(matches!(
+        game_handler_addr.send(analysis()).await.unwrap(),
+        Err(GetAnalysisError::GameInProgress)
+    ));
+
+    assert!(game_handler_addr
+        .send(explode(player_codes[1]))
+        .await
+        .unwrap()
+        .is_ok());
+
+    assert!(game_handler_addr.send(analysis()).await.unwrap().is_ok());
+}
+
+#[actix_rt::test]
+async fn spectators_see_no_mines_while_anyone_is_playing() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(3, &[0, 8]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut player_codes = Vec::new();
+    for name in ["alice", "bob"] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        player_codes.push(player_code);
+    }
+
+    // Alice hits a mine while bob is still playing
+    for (player_code, index) in [(player_codes[0], 0), (player_codes[1], 4)] {
+        assert!(game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    let spectator_state = game_handler_addr
+        .send(GetSpectatorState { game_code })
+        .await
+        .unwrap()
+        .unwrap();
+    let payload = serde_json::to_value(spectator_state).unwrap();
+
+    for player in payload["players"].as_array().unwrap() {
+        assert!(player["revealed_board"].is_null());
+
+        for index in [0, 8] {
+            assert_eq!(player["board_state"][index]["state"], "unrevealed");
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn opponents_see_progress_only_while_anyone_is_playing() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut player_codes = Vec::new();
+    for name in ["alice", "bob"] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        player_codes.push(player_code);
+    }
+    let (alice, bob) = (player_codes[0], player_codes[1]);
+
+    let play = |player_code: u16, index: usize| PlayerMove {
+        game_code,
+        player_code,
+        action: PlayerAction::Reveal { index },
+    };
+    let state_of = |player_code: u16| GetGameState {
+        game_code,
+        player_code,
+    };
+
+    // Alice clears the board while bob is still playing
+    for index in [1, 2, 3] {
+        assert!(game_handler_addr
+            .send(play(alice, index))
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    let state = game_handler_addr
+        .send(state_of(bob))
+        .await
+        .unwrap()
+        .unwrap();
+    let opponent = &state.opponents[0].board;
+    assert!(opponent.cells().is_none());
+    assert_eq!(opponent.revealed(), 3);
+
+    assert!(game_handler_addr.send(play(bob, 0)).await.unwrap().is_ok());
+
+    let state = game_handler_addr
+        .send(state_of(alice))
+        .await
+        .unwrap()
+        .unwrap();
+    let opponent = &state.opponents[0].board;
+    assert!(opponent.exploded());
+    assert!(opponent.cells().is_some());
+}
+
+#[actix_rt::test]
+async fn no_one_joins_once_a_player_has_finished() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 3,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let join = |name: &str| JoinGame::new(game_code, name.to_string());
+
+    let alice = game_handler_addr
+        .send(join("alice"))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    assert!(game_handler_addr
+        .send(PlayerMove {
+            game_code,
+            player_code: alice,
+            action: PlayerAction::Reveal { index: 0 },
+        })
+        .await
+        .unwrap()
+        .is_ok());
+
+    // Alice has seen the layout, so she cannot come back as someone else
+    assert!(matches!(
+        game_handler_addr.send(join("mallory")).await.unwrap(),
+        Err(JoinGameError::AlreadyFinished)
+    ));
+}
+
+#[actix_rt::test]
+async fn imported_boards_stay_off_leaderboards_and_stats() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 1,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let player_code = game_handler_addr
+        .send(JoinGame::new(game_code, "alice".to_string()))
+        .await
+        .unwrap()
+        .unwrap_or_else(|_| panic!("join game"));
+
+    for index in [1, 2, 3] {
+        assert!(game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    let results = game_handler_addr
+        .send(GetResults {
+            game_code: Some(game_code),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(results[0].won && results[0].unranked);
+
+    let leaderboard = game_handler_addr
+        .send(GetLeaderboard {
+            difficulty: Difficulty::Beginner,
+            period: Period::default(),
+            limit: 10,
+            player_name: Some("alice".to_string()),
+            player_id: None,
+            include_flagged: true,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(leaderboard.top.is_empty());
+    assert!(leaderboard.personal_best.is_none());
+
+    let stats = game_handler_addr
+        .send(GetPlayerStats {
+            player: PlayerRef::Name("alice".to_string()),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.games_played, 0);
+    assert!(stats.best_times.is_empty());
+}
+
+#[actix_rt::test]
+async fn stale_games_are_evicted_to_free_their_codes() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let import = || ImportGame {
+        board: Board::from_mines(2, &[0]).unwrap(),
+        player_limit: 1,
+    };
+
+    let finished = game_handler_addr.send(import()).await.unwrap().unwrap();
+    let playing = game_handler_addr.send(import()).await.unwrap().unwrap();
+    let empty = game_handler_addr.send(import()).await.unwrap().unwrap();
+
+    for (game_code, name) in [(finished, "alice"), (playing, "bob")] {
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, name.to_string()))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        if game_code == finished {
+            assert!(game_handler_addr
+                .send(PlayerMove {
+                    game_code,
+                    player_code,
+                    action: PlayerAction::Reveal { index: 0 },
+                })
+                .await
+                .unwrap()
+                .is_ok());
+        }
+    }
+
+    let now = SystemTime::now();
+    let codes = || async {
+        let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+        let mut codes = games.keys().copied().collect::<Vec<u16>>();
+        codes.sort();
+        codes
+    };
+
+    game_handler_addr
+        .send(EvictGames(now + Duration::from_secs(60)))
+        .await
+        .unwrap();
+    let mut kept = vec![finished, playing, empty];
+    kept.sort();
+    assert_eq!(codes().await, kept);
+
+    // Only the game still being played outlasts a long wait
+    game_handler_addr
+        .send(EvictGames(now + Duration::from_secs(2 * 60 * 60)))
+        .await
+        .unwrap();
+    assert_eq!(codes().await, vec![playing]);
+
+    // With no code free, new games are refused rather than taking the server down
+    game_handler_addr.send(FillGameCodes).await.unwrap();
+    assert!(matches!(
+        game_handler_addr.send(import()).await.unwrap(),
+        Err(CreateGameError::NoFreeCode)
+    ));
+    assert!(matches!(
+        game_handler_addr
+            .send(PlaySolo {
+                board_size: 8,
+                player_name: "carol".to_string(),
+                identity_token: None,
+                session_token: None,
+            })
+            .await
+            .unwrap(),
+        Err(SoloError::NoFreeCode)
+    ));
+}
+
+#[actix_rt::test]
+async fn leaving_a_race_forfeits_it() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let game_code = game_handler_addr
+        .send(ImportGame {
+            board: Board::from_mines(2, &[0]).unwrap(),
+            player_limit: 2,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    game_handler_addr.send(RankGame(game_code)).await.unwrap();
+
+    let mut players = Vec::new();
+    for username in ["alice", "bob"] {
+        let session = game_handler_addr
+            .send(Register {
+                username: username.to_string(),
+                password_hash: hash_new_password("correct horse").unwrap(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let player_code = game_handler_addr
+            .send(JoinGame::new(game_code, String::new()).with_session_token(session.token))
+            .await
+            .unwrap()
+            .unwrap_or_else(|_| panic!("join game"));
+
+        players.push((player_code, session.player_id));
+    }
+
+    for index in [1, 2, 3] {
+        assert!(game_handler_addr
+            .send(PlayerMove {
+                game_code,
+                player_code: players[0].0,
+                action: PlayerAction::Reveal { index },
+            })
+            .await
+            .unwrap()
+            .is_ok());
+    }
+
+    // Bob is losing and closes his connection rather than finishing
+    game_handler_addr
+        .send(Unsubscribe {
+            game_code,
+            player_code: players[1].0,
+        })
+        .await
+        .unwrap();
+
+    let games = game_handler_addr.send(GetSnapshot).await.unwrap().unwrap();
+    assert!(games[&game_code].is_finished());
+
+    for (_, player_id) in &players {
+        let ratings = game_handler_addr
+            .send(GetRatings {
+                player_id: player_id.clone(),
+                difficulty: None,
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ratings.history.len(), 1);
+    }
+}
+
+#[actix_rt::test]
+async fn entrants_who_do_not_play_forfeit_once_the_round_is_overdue() {
+    let game_handler_addr = GameHandler::default().start();
+
+    let mut sessions = HashMap::new();
+    for username in ["alice", "bob"] {
+        let session = game_handler_addr
+            .send(Register {
+                username: username.to_string(),
+                password_hash: hash_new_password("correct horse").unwrap(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        sessions.insert(username, session.token);
+    }
+
+    let tournament_code = game_handler_addr
+        .send(CreateTournament {
+            format: TournamentFormat::Bracket,
+            entrants: vec!["bob".to_string(), "alice".to_string()],
+            board_size: 4,
+            hint_limit: DEFAULT_HINT_LIMIT,
+            hint_penalty_secs: DEFAULT_HINT_PENALTY_SECS,
+            clock: Clock::PerPlayer { limit_secs: 0 },
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let tournament_state = || async {
+        let state = game_handler_addr
+            .send(GetTournament { tournament_code })
+            .await
+            .unwrap()
+            .unwrap();
+
+        serde_json::to_value(state).unwrap()
+    };
+
+    let state = tournament_state().await;
+    let game_code =
+        u16::from_str_radix(state["matches"][0]["game_code"].as_str().unwrap(), 16).unwrap();
+
+    // Alice plays her game while bob never turns up
+    join_and_time_out(&game_handler_addr, game_code, &sessions["alice"]).await;
+
+    let now = SystemTime::now();
+    game_handler_addr
+        .send(EnforceRoundDeadlines(now))
+        .await
+        .unwrap();
+    assert!(tournament_state().await["matches"][0]["winner"].is_null());
+
+    game_handler_addr
+        .send(EnforceRoundDeadlines(
+            now + Duration::from_secs(2 * 60 * 60),
+        ))
+        .await
+        .unwrap();
+
+    let state = tournament_state().await;
+    assert_eq!(state["matches"][0]["winner"], "alice");
+    assert_eq!(state["champion"], "alice");
+}
@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use actix::{AsyncContext, Context};
+use serde::{Deserialize, Serialize};
+
+use super::{Clock, GameConfig, GameHandler, NoFreeCode, PlayerId, TournamentFormat, WsGame};
+
+/// Time entrants have to join and finish the games of a round, on top of
+/// whatever the clock of the games allows
+const ROUND_GRACE: Duration = Duration::from_secs(30 * 60);
+/// How often rounds are looked over for entrants who did not play
+const ROUND_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A series of games between a fixed set of entrants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tournament {
+    pub(super) format: TournamentFormat,
+    /// Settings of every game played, apart from the player limit
+    pub(super) game_config: GameConfig,
+    pub(super) entrants: Vec<String>,
+    /// Account of each entrant, the only player allowed to play under their name
+    #[serde(default)]
+    pub(super) entrant_ids: BTreeMap<String, PlayerId>,
+    pub(super) matches: Vec<Match>,
+    /// Matches won by each entrant
+    pub(super) points: BTreeMap<String, usize>,
+    /// Number of the round being played, starting at 1
+    pub(super) round: usize,
+    /// When the games of the current round were created
+    #[serde(default)]
+    pub(super) round_started: Option<SystemTime>,
+    pub(super) champion: Option<String>,
+}
+
+/// A single game within a round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub round: usize,
+    /// Code of the game to join, missing for a bye
+    pub game_code: Option<u16>,
+    pub players: Vec<String>,
+    pub winner: Option<String>,
+}
+
+impl Tournament {
+    pub fn new(
+        format: TournamentFormat,
+        game_config: GameConfig,
+        entrants: Vec<(String, PlayerId)>,
+    ) -> Self {
+        Tournament {
+            format,
+            game_config,
+            points: entrants.iter().map(|(name, _)| (name.clone(), 0)).collect(),
+            entrants: entrants.iter().map(|(name, _)| name.clone()).collect(),
+            entrant_ids: entrants.into_iter().collect(),
+            matches: Vec::new(),
+            round: 0,
+            round_started: None,
+            champion: None,
+        }
+    }
+
+    /// Entrants ordered by points, ties keeping the order they entered in
+    pub fn ranking(&self) -> Vec<(&str, usize)> {
+        let mut ranking = self
+            .entrants
+            .iter()
+            .map(|name| (name.as_str(), self.points.get(name).copied().unwrap_or(0)))
+            .collect::<Vec<(&str, usize)>>();
+
+        ranking.sort_by_key(|&(_, points)| std::cmp::Reverse(points));
+        ranking
+    }
+
+    fn current_matches(&self) -> impl Iterator<Item = &Match> {
+        self.matches
+            .iter()
+            .filter(move |tournament_match| tournament_match.round == self.round)
+    }
+
+    /// The match a game belongs to
+    pub fn match_for_game(&self, game_code: u16) -> Option<&Match> {
+        self.matches
+            .iter()
+            .find(|tournament_match| tournament_match.game_code == Some(game_code))
+    }
+
+    /// Name under which the player plays the game, if they are one of its entrants
+    pub fn entrant_in_game(&self, game_code: u16, player_id: &PlayerId) -> Option<&str> {
+        self.match_for_game(game_code)?
+            .players
+            .iter()
+            .find(|name| self.entrant_ids.get(name.as_str()) == Some(player_id))
+            .map(String::as_str)
+    }
+
+    /// When entrants who have not finished their game of the current round
+    /// forfeit it
+    fn round_deadline(&self) -> Option<SystemTime> {
+        let clock = match self.game_config.clock {
+            Clock::Unlimited => Duration::ZERO,
+            Clock::PerPlayer { limit_secs } | Clock::Blitz { limit_secs } => {
+                Duration::from_secs(limit_secs)
+            }
+        };
+
+        self.round_started?.checked_add(ROUND_GRACE + clock)
+    }
+
+    fn round_over(&self) -> bool {
+        self.current_matches()
+            .all(|tournament_match| tournament_match.winner.is_some())
+    }
+
+    /// Groups of players for the next round, or `None` when the tournament is decided
+    fn next_pairings(&self) -> Option<Vec<Vec<String>>> {
+        match self.format {
+            TournamentFormat::Series { best_of } => {
+                let majority = best_of / 2 + 1;
+                let (_, leader_points) = self.ranking()[0];
+
+                if self.round >= best_of || leader_points >= majority {
+                    None
+                } else {
+                    Some(vec![self.entrants.clone()])
+                }
+            }
+            TournamentFormat::Bracket => {
+                let remaining = if self.round == 0 {
+                    self.entrants.clone()
+                } else {
+                    self.current_matches()
+                        .filter_map(|tournament_match| tournament_match.winner.clone())
+                        .collect()
+                };
+
+                if remaining.len() < 2 {
+                    None
+                } else {
+                    Some(remaining.chunks(2).map(|pair| pair.to_vec()).collect())
+                }
+            }
+        }
+    }
+}
+
+impl GameHandler {
+    /// Creates the games of the tournament's next round, or crowns the
+    /// champion if there is nothing left to play. The tournament is left as it
+    /// was when there are not enough free codes for the round's games.
+    pub(super) fn start_next_round(&mut self, tournament_code: u16) -> Result<(), NoFreeCode> {
+        let Some(tournament) = self.tournaments.get(&tournament_code) else {
+            return Ok(());
+        };
+
+        let Some(pairings) = tournament.next_pairings() else {
+            let champion = tournament.ranking()[0].0.to_string();
+
+            if let Some(tournament) = self.tournaments.get_mut(&tournament_code) {
+                tournament.champion = Some(champion);
+            }
+            return Ok(());
+        };
+
+        let round = tournament.round + 1;
+        let game_config = tournament.game_config.clone();
+        let mut matches = Vec::new();
+
+        for players in pairings {
+            // An entrant without an opponent goes straight through
+            if players.len() == 1 {
+                matches.push(Match {
+                    round,
+                    game_code: None,
+                    winner: Some(players[0].clone()),
+                    players,
+                });
+                continue;
+            }
+
+            let game_code = match self.new_game_code() {
+                Ok(game_code) => game_code,
+                Err(err) => {
+                    for game_code in matches.iter().filter_map(|m: &Match| m.game_code) {
+                        self.games.remove(&game_code);
+                    }
+                    return Err(err);
+                }
+            };
+            let mut game = WsGame::new(GameConfig {
+                player_limit: players.len(),
+                ..game_config.clone()
+            });
+            game.tournament = Some(tournament_code);
+
+            self.games.insert(game_code, game);
+
+            matches.push(Match {
+                round,
+                game_code: Some(game_code),
+                winner: None,
+                players,
+            });
+        }
+
+        for game_code in matches.iter().filter_map(|m| m.game_code) {
+            self.persist_game(game_code);
+        }
+
+        if let Some(tournament) = self.tournaments.get_mut(&tournament_code) {
+            tournament.round = round;
+            tournament.round_started = Some(SystemTime::now());
+            tournament.matches.extend(matches);
+        }
+
+        Ok(())
+    }
+
+    /// Records the winner of a tournament game once everyone in it has
+    /// finished, moving on to the next round when it was the last one
+    pub(super) fn finish_tournament_game(&mut self, game_code: u16) {
+        let Some(game) = self.games.get(&game_code) else {
+            return;
+        };
+
+        let Some(tournament_code) = game.tournament else {
+            return;
+        };
+
+        // Everyone in the match has to have joined and finished
+        if game.players.len() < game.config.player_limit || !game.is_finished() {
+            return;
+        }
+
+        let Some(winner) = game.standings().into_iter().next() else {
+            return;
+        };
+
+        self.decide_match(tournament_code, game_code, winner.name);
+    }
+
+    /// Gives a match to its winner, moving on to the next round when it was
+    /// the last one left
+    fn decide_match(&mut self, tournament_code: u16, game_code: u16, winner: String) {
+        let Some(tournament) = self.tournaments.get_mut(&tournament_code) else {
+            return;
+        };
+
+        let Some(tournament_match) = tournament
+            .matches
+            .iter_mut()
+            .find(|tournament_match| tournament_match.game_code == Some(game_code))
+        else {
+            return;
+        };
+
+        if tournament_match.winner.is_some() {
+            return;
+        }
+
+        tournament_match.winner = Some(winner.clone());
+        *tournament.points.entry(winner).or_default() += 1;
+
+        if tournament.round_over() {
+            if let Err(NoFreeCode) = self.start_next_round(tournament_code) {
+                log::error!(
+                    "No free codes for the next round of tournament {:X}",
+                    tournament_code
+                );
+            }
+        }
+
+        self.persist_tournament(tournament_code);
+        self.notify_tournament(tournament_code);
+    }
+
+    /// Tells everyone connected to any of the tournament's games that it changed
+    fn notify_tournament(&self, tournament_code: u16) {
+        let Some(tournament) = self.tournaments.get(&tournament_code) else {
+            return;
+        };
+
+        for game_code in tournament
+            .matches
+            .iter()
+            .filter_map(|tournament_match| tournament_match.game_code)
+        {
+            self.notify_game(game_code, None);
+        }
+    }
+
+    pub(super) fn schedule_round_deadlines(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_interval(ROUND_CHECK_INTERVAL, |game_handler, _ctx| {
+            game_handler.enforce_round_deadlines(SystemTime::now());
+        });
+    }
+
+    /// Settles the rounds that have run past their deadline. Entrants still
+    /// playing forfeit, and each match goes to the best placed player who
+    /// turned up, or to the entrant listed first when nobody did. Rounds that
+    /// are over but could not be followed for want of free codes are started.
+    pub(super) fn enforce_round_deadlines(&mut self, now: SystemTime) {
+        let undecided = self
+            .tournaments
+            .iter()
+            .filter(|(_, tournament)| tournament.champion.is_none())
+            .map(|(&tournament_code, _)| tournament_code)
+            .collect::<Vec<u16>>();
+
+        for tournament_code in undecided {
+            let Some(tournament) = self.tournaments.get(&tournament_code) else {
+                continue;
+            };
+
+            if tournament.round_over() {
+                if self.start_next_round(tournament_code).is_ok() {
+                    self.persist_tournament(tournament_code);
+                    self.notify_tournament(tournament_code);
+                }
+                continue;
+            }
+
+            if tournament
+                .round_deadline()
+                .is_none_or(|deadline| deadline > now)
+            {
+                continue;
+            }
+
+            let overdue = tournament
+                .current_matches()
+                .filter(|tournament_match| tournament_match.winner.is_none())
+                .filter_map(|tournament_match| {
+                    Some((
+                        tournament_match.game_code?,
+                        tournament_match.players[0].clone(),
+                    ))
+                })
+                .collect::<Vec<(u16, String)>>();
+
+            for (game_code, first_listed) in overdue {
+                self.forfeit_unfinished(game_code, now);
+
+                let winner = self
+                    .games
+                    .get(&game_code)
+                    .and_then(|game| game.standings().into_iter().next())
+                    .map_or(first_listed, |standing| standing.name);
+
+                self.decide_match(tournament_code, game_code, winner);
+            }
+        }
+    }
+
+    /// Ends the board of every player in the game who has not finished yet
+    fn forfeit_unfinished(&mut self, game_code: u16, now: SystemTime) {
+        let Some(game) = self.games.get_mut(&game_code) else {
+            return;
+        };
+
+        let unfinished = game
+            .players
+            .iter_mut()
+            .filter(|(_, player_game)| !player_game.board.is_finished())
+            .map(|(&player_code, player_game)| {
+                player_game.board.end();
+                player_code
+            })
+            .collect::<Vec<u16>>();
+
+        for player_code in unfinished {
+            self.finish_player(game_code, player_code, now);
+        }
+
+        self.persist_game(game_code);
+        self.notify_game(game_code, None);
+    }
+}
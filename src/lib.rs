@@ -0,0 +1,8 @@
+//! Multiplayer minesweeper server: the actor running games on the rules from
+//! `minesweeper-core` and the HTTP API on top of them.
+
+pub mod commands;
+pub mod config;
+pub mod game_handler;
+pub mod routes;
+pub mod storage;
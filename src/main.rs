@@ -1,28 +1,51 @@
-use actix::Actor;
-use actix_cors::Cors;
-use actix_web::{web, App, HttpServer};
+use clap::{Parser, Subcommand};
 
-mod game;
-mod game_handler;
-mod routes;
+use minesweeper::commands::{bench, generate, replay, serve, solve};
+use minesweeper::config::ConfigArgs;
+
+/// Multiplayer minesweeper server and board tools
+#[derive(Parser, Debug)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// Runs the server when left out
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Settings of the server run without a subcommand
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Runs the game server
+    Serve(ConfigArgs),
+    /// Prints a board generated from a seed
+    Generate(generate::GenerateArgs),
+    /// Plays through a board file with the solver
+    Solve(solve::SolveArgs),
+    /// Steps through the moves of an exported game
+    Replay(replay::ReplayArgs),
+    /// Measures board generation and solver throughput
+    Bench(bench::BenchArgs),
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let game_handler_addr = web::Data::new(game_handler::GameHandler::default().start());
+    let cli = Cli::parse();
 
-    std::env::set_var("RUST_LOG", "debug");
-    env_logger::init();
+    let result = match cli.command {
+        None => return serve::serve(&cli.config).await,
+        Some(Command::Serve(args)) => return serve::serve(&args).await,
+        Some(Command::Generate(args)) => generate::generate(&args),
+        Some(Command::Solve(args)) => solve::solve(&args),
+        Some(Command::Replay(args)) => replay::replay(&args),
+        Some(Command::Bench(args)) => bench::bench(&args),
+    };
 
-    HttpServer::new(move || {
-        let cors = Cors::permissive();
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
 
-        App::new()
-            .wrap(cors)
-            .wrap(actix_web::middleware::Logger::default())
-            .service(routes::routes())
-            .app_data(game_handler_addr.clone())
-    })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    Ok(())
 }
@@ -0,0 +1,40 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetAnalysis, GetAnalysisError};
+
+#[derive(Deserialize, Debug)]
+struct AnalysisQuery {
+    code: String,
+    player_name: String,
+    at_move: Option<usize>,
+}
+
+#[get("analysis")]
+async fn analysis(
+    query: web::Query<AnalysisQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    let game_code = u16::from_str_radix(&query.code, 16)
+        .map_err(|_err| error::ErrorBadRequest("Invalid game code"))?;
+
+    let analysis = game_handler
+        .send(GetAnalysis {
+            game_code,
+            player_name: query.player_name,
+            at_move: query.at_move,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| match err {
+            GetAnalysisError::GameNotFound => error::ErrorNotFound("Unable to find the game"),
+            GetAnalysisError::PlayerNotFound => error::ErrorNotFound("Unable to find the player"),
+            GetAnalysisError::GameInProgress => error::ErrorForbidden("Game is still in progress"),
+            GetAnalysisError::MoveOutOfRange => error::ErrorBadRequest("No such move"),
+        })?;
+
+    Ok(web::Json(analysis))
+}
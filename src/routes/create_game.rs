@@ -1,28 +1,43 @@
 use actix::Addr;
 use actix_web::{error, post, web, Responder};
-use serde::Serialize;
+use minesweeper_core::protocol::{CreateGameRequest, NewGameResponse};
 
-use crate::game_handler::{self, CreateGame};
-
-#[derive(Debug, Serialize)]
-struct NewGameResponse {
-    code: String,
-}
+use crate::game_handler::{self, hash_password, CreateGame, CreateGameError};
 
 #[post("create-game")]
 async fn create_game(
-    config: web::Json<CreateGame>,
+    config: web::Json<CreateGameRequest>,
     game_handler: web::Data<Addr<game_handler::GameHandler>>,
 ) -> actix_web::Result<impl Responder> {
-    let config = config.into_inner();
+    let mut config = config.into_inner();
+    let password_hash = match config.password.take() {
+        Some(password) => Some(web::block(move || hash_password(&password)).await?),
+        None => None,
+    };
+    let config = CreateGame::new(config, password_hash);
 
-    let code = game_handler
+    let created = game_handler
         .send(config)
         .await
         .map_err(|_| error::ErrorInternalServerError("Something went terribly wrong."))?
-        .map_err(|_| error::ErrorInternalServerError("Something went terribly wrong."))?;
+        .map_err(create_game_error)?;
 
     Ok(web::Json(NewGameResponse {
-        code: format!("{:X}", code),
+        code: format!("{:X}", created.code),
+        host_token: created.host_token,
     }))
 }
+
+pub(super) fn create_game_error(err: CreateGameError) -> actix_web::Error {
+    match err {
+        CreateGameError::OutsideLimits => {
+            error::ErrorBadRequest("Game settings are outside the server's limits")
+        }
+        CreateGameError::NoFreeCode => no_free_code(),
+    }
+}
+
+/// Every game code is in use until finished games are cleared away
+pub(super) fn no_free_code() -> actix_web::Error {
+    error::ErrorServiceUnavailable("The server is full, try again later")
+}
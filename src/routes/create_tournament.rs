@@ -0,0 +1,47 @@
+use actix::Addr;
+use actix_web::{error, post, web, Responder};
+use serde::Serialize;
+
+use crate::game_handler::{self, CreateTournament, CreateTournamentError};
+
+use super::create_game::no_free_code;
+use super::join_game::invalid_name;
+
+#[derive(Debug, Serialize)]
+struct NewTournamentResponse {
+    code: String,
+}
+
+#[post("create-tournament")]
+async fn create_tournament(
+    config: web::Json<CreateTournament>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let code = game_handler
+        .send(config.into_inner())
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| match err {
+            CreateTournamentError::TooFewEntrants => {
+                error::ErrorBadRequest("A tournament needs at least two entrants")
+            }
+            CreateTournamentError::DuplicateEntrant => {
+                error::ErrorBadRequest("Entrant names must be unique")
+            }
+            CreateTournamentError::UnknownEntrant(name) => {
+                error::ErrorBadRequest(format!("{} does not have an account", name))
+            }
+            CreateTournamentError::NoRounds => {
+                error::ErrorBadRequest("A series needs at least one round")
+            }
+            CreateTournamentError::OutsideLimits => {
+                error::ErrorBadRequest("Game settings are outside the server's limits")
+            }
+            CreateTournamentError::InvalidName(err) => invalid_name(err),
+            CreateTournamentError::NoFreeCode => no_free_code(),
+        })?;
+
+    Ok(web::Json(NewTournamentResponse {
+        code: format!("{:X}", code),
+    }))
+}
@@ -0,0 +1,44 @@
+use actix::Addr;
+use actix_web::{error, get, web, HttpRequest, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, DailyError, GetDailyChallenge};
+
+use super::create_game::no_free_code;
+use super::session::{player_token_error, session_token};
+
+#[derive(Deserialize, Debug)]
+struct DailyQuery {
+    /// Token from `identity`, to tell whether the player has played today
+    identity_token: Option<String>,
+}
+
+#[get("daily")]
+async fn daily(
+    req: HttpRequest,
+    query: web::Query<DailyQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let challenge = game_handler
+        .send(GetDailyChallenge {
+            identity_token: query.into_inner().identity_token,
+            session_token: session_token(&req),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(daily_error)?;
+
+    Ok(web::Json(challenge))
+}
+
+pub(super) fn daily_error(err: DailyError) -> actix_web::Error {
+    match err {
+        DailyError::Anonymous => error::ErrorUnauthorized("Log in to play the daily challenge"),
+        DailyError::Token(err) => player_token_error(err),
+        DailyError::AlreadyPlayed => {
+            error::ErrorConflict("Today's challenge has already been played")
+        }
+        DailyError::NoFreeCode => no_free_code(),
+        DailyError::Storage(err) => error::ErrorInternalServerError(err),
+    }
+}
@@ -0,0 +1,43 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, parse_date, GetDailyLeaderboard};
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+#[derive(Deserialize, Debug)]
+struct DailyLeaderboardQuery {
+    /// Day of the challenge such as `2024-03-01`, today when left out
+    date: Option<String>,
+    limit: Option<usize>,
+    #[serde(default)]
+    include_flagged: bool,
+}
+
+#[get("daily/leaderboard")]
+async fn daily_leaderboard(
+    query: web::Query<DailyLeaderboardQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    let day = query
+        .date
+        .as_deref()
+        .map(|date| parse_date(date).ok_or_else(|| error::ErrorBadRequest("Invalid date")))
+        .transpose()?;
+
+    let leaderboard = game_handler
+        .send(GetDailyLeaderboard {
+            day,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+            include_flagged: query.include_flagged,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(leaderboard))
+}
@@ -0,0 +1,64 @@
+use actix::Addr;
+use actix_web::{error, get, web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, ExportGame, ExportGameError};
+use minesweeper_core::Board;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    #[default]
+    Json,
+    Mbf,
+    Rawvf,
+}
+
+#[derive(Deserialize, Debug)]
+struct ExportGameQuery {
+    code: String,
+    #[serde(default)]
+    format: FileFormat,
+}
+
+#[get("export-game")]
+async fn export_game(
+    query: web::Query<ExportGameQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let game_code = u16::from_str_radix(&query.code, 16)
+        .map_err(|_err| error::ErrorBadRequest("Invalid game code"))?;
+
+    let record = game_handler
+        .send(ExportGame { game_code })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| match err {
+            ExportGameError::GameNotFound => error::ErrorNotFound("Unable to find the game"),
+            ExportGameError::GameInProgress => error::ErrorConflict("Game is still in progress"),
+        })?;
+
+    let response = match query.format {
+        FileFormat::Json => HttpResponse::Ok().json(record),
+        FileFormat::Mbf | FileFormat::Rawvf => {
+            let board = Board::from_mines(record.board_size, &record.mines)
+                .map_err(|_| error::ErrorInternalServerError("Something went terribly wrong."))?;
+
+            if let FileFormat::Mbf = query.format {
+                let bytes = board
+                    .to_mbf()
+                    .map_err(|_| error::ErrorBadRequest("Board is too large for MBF"))?;
+
+                HttpResponse::Ok()
+                    .content_type("application/octet-stream")
+                    .body(bytes)
+            } else {
+                HttpResponse::Ok()
+                    .content_type("text/plain")
+                    .body(board.to_rawvf())
+            }
+        }
+    };
+
+    Ok(response)
+}
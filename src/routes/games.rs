@@ -0,0 +1,16 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+
+use crate::game_handler::{self, ListGames};
+
+#[get("games")]
+async fn games(
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let lobbies = game_handler
+        .send(ListGames)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(lobbies))
+}
@@ -0,0 +1,19 @@
+use actix::Addr;
+use actix_web::{error, post, web, Responder};
+
+use crate::game_handler::{self, CreateIdentity};
+
+/// Hands out a new anonymous identity. Clients keep the token and pass it to
+/// `join-game` so that their results follow them from game to game.
+#[post("identity")]
+async fn identity(
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let identity = game_handler
+        .send(CreateIdentity)
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(identity))
+}
@@ -0,0 +1,77 @@
+use actix::Addr;
+use actix_web::{error, post, web, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::game_handler::{self, GameLimits, GameRecord, ImportGame};
+use minesweeper_core::{Board, FormatError};
+
+use super::create_game::create_game_error;
+use super::export_game::FileFormat;
+
+#[derive(Deserialize, Debug)]
+struct ImportGameQuery {
+    #[serde(default)]
+    format: FileFormat,
+    player_limit: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct NewGameResponse {
+    code: String,
+}
+
+fn invalid_board(err: FormatError) -> actix_web::Error {
+    error::ErrorBadRequest(match err {
+        FormatError::UnexpectedEnd => "Board file is truncated",
+        FormatError::NotSquare => "Only square boards are supported",
+        FormatError::TooLarge => "Board is too large",
+        FormatError::MineOutOfBound => "Mine out of bounds",
+        FormatError::MineCountMismatch => "Mine count does not match the board",
+        FormatError::Malformed => "Malformed board file",
+        _ => "Invalid board file",
+    })
+}
+
+#[post("import-game")]
+async fn import_game(
+    query: web::Query<ImportGameQuery>,
+    body: web::Bytes,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+    limits: web::Data<GameLimits>,
+) -> actix_web::Result<impl Responder> {
+    let board = match query.format {
+        FileFormat::Json => {
+            let record: GameRecord = serde_json::from_slice(&body)
+                .map_err(|_| error::ErrorBadRequest("Invalid game record"))?;
+
+            // Checked before building the board, which allocates every cell up front
+            if record.board_size > limits.max_board_size {
+                return Err(error::ErrorBadRequest("Board is too large"));
+            }
+
+            record
+                .board()
+                .map_err(|_| error::ErrorBadRequest("Mine out of bounds"))?
+        }
+        FileFormat::Mbf => Board::from_mbf(&body).map_err(invalid_board)?,
+        FileFormat::Rawvf => {
+            let text = std::str::from_utf8(&body)
+                .map_err(|_| error::ErrorBadRequest("RAWVF board must be valid UTF-8"))?;
+
+            Board::from_rawvf(text).map_err(invalid_board)?
+        }
+    };
+
+    let code = game_handler
+        .send(ImportGame {
+            board,
+            player_limit: query.player_limit,
+        })
+        .await
+        .map_err(|_| error::ErrorInternalServerError("Something went terribly wrong."))?
+        .map_err(create_game_error)?;
+
+    Ok(web::Json(NewGameResponse {
+        code: format!("{:X}", code),
+    }))
+}
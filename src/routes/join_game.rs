@@ -1,15 +1,11 @@
+use crate::game_handler::{NameError, MAX_NAME_LENGTH};
+use crate::{game_handler, routes::ws::GameWebSocketActor};
 use actix::Addr;
 use actix_web::{error, get, web, HttpRequest, Responder};
 use actix_web_actors::ws;
-use serde::Deserialize;
-
-use crate::{game_handler, routes::ws::GameWebSocketActor};
+use minesweeper_core::protocol::JoinGameQuery;
 
-#[derive(Deserialize, Debug)]
-struct JoinGameQuery {
-    code: String,
-    player_name: String,
-}
+use super::session::{player_token_error, session_token};
 
 #[get("join-game")]
 async fn join_game(
@@ -21,15 +17,61 @@ async fn join_game(
     let game_code = u16::from_str_radix(&query.code, 16)
         .map_err(|_err| error::ErrorBadRequest("Invalid game code"))?;
 
+    let mut join_game = game_handler::JoinGame::new(game_code, query.player_name.to_owned());
+    if let Some(host_token) = &query.host_token {
+        join_game = join_game.with_host_token(host_token.to_owned());
+    }
+    if let Some(password) = query.password.clone() {
+        let password_hash = game_handler
+            .send(game_handler::GetPasswordHash { game_code })
+            .await
+            .map_err(error::ErrorInternalServerError)?;
+
+        // Hashing is slow on purpose, so it is kept off the game handler
+        if let Some(password_hash) = password_hash {
+            let verified = web::block(move || {
+                game_handler::verify_password(&password, &password_hash).then_some(password_hash)
+            })
+            .await?;
+
+            if let Some(password_hash) = verified {
+                join_game = join_game.with_verified_password(password_hash);
+            }
+        }
+    }
+    if let Some(identity_token) = &query.identity_token {
+        join_game = join_game.with_identity_token(identity_token.to_owned());
+    }
+    // Logged in players play under their username rather than `player_name`
+    if let Some(session_token) = session_token(&req) {
+        join_game = join_game.with_session_token(session_token);
+    }
+
     let player_code = game_handler
-        .send(game_handler::JoinGame::new(
-            game_code,
-            query.player_name.to_owned(),
-        ))
+        .send(join_game)
         .await
-        .map_err(|err| error::ErrorInternalServerError(err))?
+        .map_err(error::ErrorInternalServerError)?
         .map_err(|err| match err {
             game_handler::JoinGameError::GameFull => error::ErrorBadRequest("Game is full"),
+            game_handler::JoinGameError::InvalidName(err) => invalid_name(err),
+            game_handler::JoinGameError::Token(err) => player_token_error(err),
+            game_handler::JoinGameError::WrongPassword => {
+                error::ErrorUnauthorized("Wrong or missing password")
+            }
+            game_handler::JoinGameError::Locked => error::ErrorForbidden("Game is locked"),
+            game_handler::JoinGameError::InvalidHostToken => {
+                error::ErrorForbidden("Invalid host token")
+            }
+            game_handler::JoinGameError::NotInMatch => {
+                error::ErrorForbidden("Player is not part of this match")
+            }
+            game_handler::JoinGameError::AlreadyJoined => {
+                error::ErrorConflict("Already playing in this game")
+            }
+            game_handler::JoinGameError::DeadlinePassed => error::ErrorGone("Game has ended"),
+            game_handler::JoinGameError::AlreadyFinished => {
+                error::ErrorConflict("A player has already finished this game")
+            }
             game_handler::JoinGameError::GameNotFound => {
                 error::ErrorNotFound("Unable to find the game")
             }
@@ -45,3 +87,12 @@ async fn join_game(
         stream,
     ))
 }
+
+pub(super) fn invalid_name(err: NameError) -> actix_web::Error {
+    error::ErrorBadRequest(match err {
+        NameError::Empty => "Player name is empty".to_string(),
+        NameError::TooLong => format!("Player name is longer than {} characters", MAX_NAME_LENGTH),
+        NameError::ControlCharacter => "Player name contains control characters".to_string(),
+        NameError::Registered => "Player name belongs to an account, log in to use it".to_string(),
+    })
+}
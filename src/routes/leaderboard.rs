@@ -0,0 +1,43 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, Difficulty, GetLeaderboard, Period, PlayerId};
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 100;
+
+#[derive(Deserialize, Debug)]
+struct LeaderboardQuery {
+    difficulty: Difficulty,
+    #[serde(default)]
+    period: Period,
+    limit: Option<usize>,
+    player_name: Option<String>,
+    player_id: Option<PlayerId>,
+    #[serde(default)]
+    include_flagged: bool,
+}
+
+#[get("leaderboard")]
+async fn leaderboard(
+    query: web::Query<LeaderboardQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    let leaderboard = game_handler
+        .send(GetLeaderboard {
+            difficulty: query.difficulty,
+            period: query.period,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+            player_name: query.player_name,
+            player_id: query.player_id,
+            include_flagged: query.include_flagged,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(leaderboard))
+}
@@ -0,0 +1,48 @@
+use actix::Addr;
+use actix_web::{error, post, web, HttpResponse};
+
+use crate::game_handler::{
+    self, dummy_password_hash, verify_password, AccountError, GetAccountPasswordHash, Login,
+};
+
+use super::register::{account_error, Credentials};
+use super::session::session_cookie;
+
+#[post("login")]
+async fn login(
+    credentials: web::Json<Credentials>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<HttpResponse> {
+    let Credentials { username, password } = credentials.into_inner();
+
+    let password_hash = game_handler
+        .send(GetAccountPasswordHash {
+            username: username.clone(),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    // Unknown usernames take as long to turn away as wrong passwords
+    let password_hash = web::block(move || match password_hash {
+        Some(password_hash) => verify_password(&password, &password_hash).then_some(password_hash),
+        None => {
+            verify_password(&password, dummy_password_hash());
+            None
+        }
+    })
+    .await?
+    .ok_or_else(|| account_error(AccountError::InvalidCredentials))?;
+
+    let session = game_handler
+        .send(Login {
+            username,
+            password_hash,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(account_error)?;
+
+    Ok(HttpResponse::Ok()
+        .cookie(session_cookie(&session.token))
+        .json(session))
+}
@@ -0,0 +1,27 @@
+use actix::Addr;
+use actix_web::{error, post, web, HttpRequest, HttpResponse};
+
+use crate::game_handler::{self, Logout};
+
+use super::session::{session_cookie, session_token};
+
+#[post("logout")]
+async fn logout(
+    req: HttpRequest,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<HttpResponse> {
+    let token = session_token(&req).ok_or_else(|| error::ErrorUnauthorized("Not logged in"))?;
+
+    game_handler
+        .send(Logout {
+            token: token.clone(),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    let mut cookie = session_cookie(&token);
+    cookie.make_removal();
+
+    Ok(HttpResponse::NoContent().cookie(cookie).finish())
+}
@@ -0,0 +1,38 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetMatchHistory};
+
+use super::player_stats::PlayerQuery;
+
+const DEFAULT_PER_PAGE: usize = 20;
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Deserialize, Debug)]
+struct PageQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+#[get("match-history")]
+async fn match_history(
+    player: web::Query<PlayerQuery>,
+    page: web::Query<PageQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let history = game_handler
+        .send(GetMatchHistory {
+            player: player.into_inner().player()?,
+            page: page.page.unwrap_or(1).max(1),
+            per_page: page
+                .per_page
+                .unwrap_or(DEFAULT_PER_PAGE)
+                .clamp(1, MAX_PER_PAGE),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(history))
+}
@@ -0,0 +1,114 @@
+use actix::{
+    fut, Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, ContextFutureSpawner, Handler,
+    StreamHandler, WrapFuture,
+};
+use actix_web::{get, web, HttpRequest, Responder};
+use actix_web_actors::ws;
+use serde::Deserialize;
+
+use crate::game_handler::{self, Difficulty, EnterQueue, LeaveQueue, MatchFound};
+
+use super::session::session_token;
+
+#[derive(Deserialize, Debug)]
+struct MatchmakingQuery {
+    difficulty: Difficulty,
+    player_name: String,
+    identity_token: Option<String>,
+}
+
+/// Socket held open while a player waits in the queue. It sends the game to
+/// join once one is found and closes.
+struct MatchmakingSocket {
+    game_handler_addr: Addr<game_handler::GameHandler>,
+    difficulty: Difficulty,
+    player_name: String,
+    /// Tokens the player is rated by, if they have any
+    identity_token: Option<String>,
+    session_token: Option<String>,
+    ticket: Option<u64>,
+}
+
+impl Actor for MatchmakingSocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.game_handler_addr
+            .send(EnterQueue {
+                difficulty: self.difficulty,
+                player_name: self.player_name.clone(),
+                identity_token: self.identity_token.clone(),
+                session_token: self.session_token.clone(),
+                recipient: ctx.address().recipient(),
+            })
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(Ok(ticket)) => act.ticket = Some(ticket),
+                    Ok(Err(_)) => {
+                        ctx.close(Some(ws::CloseReason {
+                            code: ws::CloseCode::Policy,
+                            description: Some("Unknown identity or session".to_string()),
+                        }));
+                        ctx.stop();
+                    }
+                    Err(_) => ctx.stop(),
+                }
+
+                fut::ready(())
+            })
+            .wait(ctx);
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Some(ticket) = self.ticket {
+            self.game_handler_addr.do_send(LeaveQueue { ticket });
+        }
+    }
+}
+
+impl Handler<MatchFound> for MatchmakingSocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: MatchFound, ctx: &mut Self::Context) {
+        // The player is no longer queued once matched
+        self.ticket = None;
+
+        let json = serde_json::to_string(&msg).expect("serializes MatchFound");
+        ctx.text(json);
+        ctx.close(None);
+        ctx.stop();
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MatchmakingSocket {
+    fn handle(&mut self, item: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if let Ok(ws::Message::Close(reason)) = item {
+            ctx.close(reason);
+            ctx.stop();
+        }
+    }
+}
+
+#[get("matchmaking")]
+async fn matchmaking(
+    req: HttpRequest,
+    query: web::Query<MatchmakingQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+    stream: web::Payload,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    ws::start(
+        MatchmakingSocket {
+            game_handler_addr: game_handler.as_ref().clone(),
+            difficulty: query.difficulty,
+            player_name: query.player_name,
+            identity_token: query.identity_token,
+            session_token: session_token(&req),
+            ticket: None,
+        },
+        &req,
+        stream,
+    )
+}
@@ -1,31 +1,64 @@
+mod analysis;
 mod create_game;
+mod create_tournament;
+mod daily;
+mod daily_leaderboard;
+mod export_game;
+mod games;
+mod identity;
+mod import_game;
 mod join_game;
+mod leaderboard;
+mod login;
+mod logout;
+mod match_history;
+mod matchmaking;
+mod personal_bests;
+mod play_daily;
+mod play_solo;
+mod player_stats;
+mod ratings;
+mod register;
+mod results;
+mod session;
+mod spectate;
+mod tournament;
 mod ws;
 
-use std::path::PathBuf;
+use std::path::Path;
 
-use actix_files::{Files, NamedFile};
-use actix_web::{web, HttpRequest};
+use actix_files::Files;
+use actix_web::web;
 
-async fn index(req: HttpRequest) -> actix_web::Result<NamedFile> {
-    let static_dir: PathBuf = "./static/".parse().unwrap();
-    let filename: PathBuf = req.match_info().query("filename").parse().unwrap();
-
-    let path = static_dir.join(filename);
-
-    if path == static_dir {
-        Ok(NamedFile::open(static_dir.join("index.html"))?)
-    } else {
-        Ok(NamedFile::open(static_dir.join(path))?)
-    }
-}
-
-pub fn routes() -> actix_web::Scope {
+/// The API along with the frontend served from `static_dir`
+pub fn routes(static_dir: &Path) -> actix_web::Scope {
     let api_service = web::scope("/api")
+        .service(analysis::analysis)
         .service(create_game::create_game)
-        .service(join_game::join_game);
+        .service(create_tournament::create_tournament)
+        .service(daily::daily)
+        .service(daily_leaderboard::daily_leaderboard)
+        .service(export_game::export_game)
+        .service(games::games)
+        .service(identity::identity)
+        .service(import_game::import_game)
+        .service(join_game::join_game)
+        .service(leaderboard::leaderboard)
+        .service(login::login)
+        .service(logout::logout)
+        .service(match_history::match_history)
+        .service(matchmaking::matchmaking)
+        .service(personal_bests::personal_bests)
+        .service(play_daily::play_daily)
+        .service(play_solo::play_solo)
+        .service(player_stats::player_stats)
+        .service(ratings::ratings)
+        .service(register::register)
+        .service(results::results)
+        .service(spectate::spectate)
+        .service(tournament::tournament);
 
     web::scope("")
         .service(api_service)
-        .service(Files::new("/", "./static/").index_file("index.html"))
+        .service(Files::new("/", static_dir).index_file("index.html"))
 }
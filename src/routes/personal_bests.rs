@@ -0,0 +1,32 @@
+use actix::Addr;
+use actix_web::{error, get, web, HttpRequest, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetPersonalBests};
+
+use super::play_solo::solo_error;
+use super::session::session_token;
+
+#[derive(Deserialize, Debug)]
+struct PersonalBestsQuery {
+    /// Token from `identity`, for players who are not logged in
+    identity_token: Option<String>,
+}
+
+#[get("solo/bests")]
+async fn personal_bests(
+    req: HttpRequest,
+    query: web::Query<PersonalBestsQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let bests = game_handler
+        .send(GetPersonalBests {
+            identity_token: query.into_inner().identity_token,
+            session_token: session_token(&req),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(solo_error)?;
+
+    Ok(web::Json(bests))
+}
@@ -0,0 +1,33 @@
+use actix::Addr;
+use actix_web::{error, get, web, HttpRequest, Responder};
+use actix_web_actors::ws;
+
+use crate::{game_handler, routes::ws::GameWebSocketActor};
+
+use super::daily::daily_error;
+use super::session::session_token;
+
+#[get("daily/play")]
+async fn play_daily(
+    req: HttpRequest,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+    stream: web::Payload,
+) -> actix_web::Result<impl Responder> {
+    let attempt = game_handler
+        .send(game_handler::PlayDaily {
+            session_token: session_token(&req),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(daily_error)?;
+
+    ws::start(
+        GameWebSocketActor {
+            game_handler_addr: game_handler.as_ref().clone(),
+            game_code: attempt.game_code,
+            player_code: attempt.player_code,
+        },
+        &req,
+        stream,
+    )
+}
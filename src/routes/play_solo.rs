@@ -0,0 +1,67 @@
+use actix::Addr;
+use actix_web::{error, get, web, HttpRequest, Responder};
+use actix_web_actors::ws;
+use serde::Deserialize;
+
+use crate::game_handler::{self, SoloError};
+use crate::routes::ws::GameWebSocketActor;
+
+use super::create_game::no_free_code;
+use super::join_game::invalid_name;
+use super::session::{player_token_error, session_token};
+
+#[derive(Deserialize, Debug)]
+struct PlaySoloQuery {
+    board_size: usize,
+    /// Name to play under, logged in players play under their username instead
+    #[serde(default)]
+    player_name: String,
+    /// Token from `identity`, to count the game towards the player's personal bests
+    identity_token: Option<String>,
+}
+
+#[get("solo/play")]
+async fn play_solo(
+    req: HttpRequest,
+    query: web::Query<PlaySoloQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+    stream: web::Payload,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    let attempt = game_handler
+        .send(game_handler::PlaySolo {
+            board_size: query.board_size,
+            player_name: query.player_name,
+            identity_token: query.identity_token,
+            session_token: session_token(&req),
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(solo_error)?;
+
+    ws::start(
+        GameWebSocketActor {
+            game_handler_addr: game_handler.as_ref().clone(),
+            game_code: attempt.game_code,
+            player_code: attempt.player_code,
+        },
+        &req,
+        stream,
+    )
+}
+
+pub(super) fn solo_error(err: SoloError) -> actix_web::Error {
+    match err {
+        SoloError::InvalidBoardSize => {
+            error::ErrorBadRequest("Board size is outside the server's limits")
+        }
+        SoloError::Anonymous => {
+            error::ErrorUnauthorized("Log in or use an identity to keep personal bests")
+        }
+        SoloError::Token(err) => player_token_error(err),
+        SoloError::InvalidName(err) => invalid_name(err),
+        SoloError::NoFreeCode => no_free_code(),
+        SoloError::Storage(err) => error::ErrorInternalServerError(err),
+    }
+}
@@ -0,0 +1,40 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetPlayerStats, PlayerId, PlayerRef};
+
+/// Identifies a player by their public id, or by name for anonymous players
+#[derive(Deserialize, Debug)]
+pub(super) struct PlayerQuery {
+    player_id: Option<PlayerId>,
+    player_name: Option<String>,
+}
+
+impl PlayerQuery {
+    pub(super) fn player(self) -> actix_web::Result<PlayerRef> {
+        match (self.player_id, self.player_name) {
+            (Some(player_id), _) => Ok(PlayerRef::Id(player_id)),
+            (None, Some(player_name)) => Ok(PlayerRef::Name(player_name)),
+            (None, None) => Err(error::ErrorBadRequest(
+                "Either player_id or player_name is required",
+            )),
+        }
+    }
+}
+
+#[get("player-stats")]
+async fn player_stats(
+    query: web::Query<PlayerQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let stats = game_handler
+        .send(GetPlayerStats {
+            player: query.into_inner().player()?,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(stats))
+}
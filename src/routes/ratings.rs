@@ -0,0 +1,30 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, Difficulty, GetRatings, PlayerId};
+
+#[derive(Deserialize, Debug)]
+struct RatingsQuery {
+    player_id: PlayerId,
+    difficulty: Option<Difficulty>,
+}
+
+#[get("ratings")]
+async fn ratings(
+    query: web::Query<RatingsQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let query = query.into_inner();
+
+    let ratings = game_handler
+        .send(GetRatings {
+            player_id: query.player_id,
+            difficulty: query.difficulty,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(ratings))
+}
@@ -0,0 +1,53 @@
+use actix::Addr;
+use actix_web::{error, post, web, HttpResponse};
+use serde::Deserialize;
+
+use crate::game_handler::{self, hash_new_password, AccountError, Register, MIN_PASSWORD_LENGTH};
+
+use super::join_game::invalid_name;
+use super::session::session_cookie;
+
+/// Body of `register` and `login`
+#[derive(Deserialize)]
+pub(super) struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+pub(super) fn account_error(err: AccountError) -> actix_web::Error {
+    match err {
+        AccountError::InvalidName(err) => invalid_name(err),
+        AccountError::UsernameTaken => error::ErrorConflict("Username is already taken"),
+        AccountError::WeakPassword => error::ErrorBadRequest(format!(
+            "Password must be at least {} characters long",
+            MIN_PASSWORD_LENGTH
+        )),
+        AccountError::InvalidCredentials => error::ErrorUnauthorized("Wrong username or password"),
+        AccountError::Storage(err) => error::ErrorInternalServerError(err),
+    }
+}
+
+#[post("register")]
+async fn register(
+    credentials: web::Json<Credentials>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<HttpResponse> {
+    let Credentials { username, password } = credentials.into_inner();
+
+    let password_hash = web::block(move || hash_new_password(&password))
+        .await?
+        .map_err(account_error)?;
+
+    let session = game_handler
+        .send(Register {
+            username,
+            password_hash,
+        })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(account_error)?;
+
+    Ok(HttpResponse::Created()
+        .cookie(session_cookie(&session.token))
+        .json(session))
+}
@@ -0,0 +1,68 @@
+use std::time::UNIX_EPOCH;
+
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::game_handler::{self, GameStats, GetResults, PlayerId, Suspicion};
+
+#[derive(Deserialize, Debug)]
+struct ResultsQuery {
+    code: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ResultResponse {
+    code: String,
+    player_name: String,
+    player_id: Option<PlayerId>,
+    board_size: usize,
+    mines: usize,
+    won: bool,
+    finished_time: u64,
+    duration_ms: u64,
+    stats: GameStats,
+    suspicions: Vec<Suspicion>,
+}
+
+#[get("results")]
+async fn results(
+    query: web::Query<ResultsQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let game_code = query
+        .code
+        .as_deref()
+        .map(|code| u16::from_str_radix(code, 16))
+        .transpose()
+        .map_err(|_err| error::ErrorBadRequest("Invalid game code"))?;
+
+    let results = game_handler
+        .send(GetResults { game_code })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(
+        results
+            .into_iter()
+            .rev()
+            .map(|result| ResultResponse {
+                code: format!("{:X}", result.game_code),
+                player_name: result.player_name.clone(),
+                player_id: result.player_id.clone(),
+                board_size: result.board_size,
+                mines: result.mines,
+                won: result.won,
+                finished_time: result
+                    .finished_time
+                    .duration_since(UNIX_EPOCH)
+                    .expect("time went backwards")
+                    .as_secs(),
+                duration_ms: result.duration().as_millis() as u64,
+                stats: result.stats(),
+                suspicions: result.suspicions.clone(),
+            })
+            .collect::<Vec<ResultResponse>>(),
+    ))
+}
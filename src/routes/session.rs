@@ -0,0 +1,45 @@
+//! Passing login sessions between the client and the server.
+//!
+//! Clients may send the session token either as a bearer token or in the
+//! `session` cookie set on login, which is the only option for browsers
+//! opening a websocket.
+
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::http::header;
+use actix_web::{error, HttpRequest};
+
+use crate::game_handler::PlayerTokenError;
+
+pub const SESSION_COOKIE: &str = "session";
+
+/// The session token sent with a request, if any
+pub fn session_token(req: &HttpRequest) -> Option<String> {
+    let bearer = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    bearer.or_else(|| {
+        req.cookie(SESSION_COOKIE)
+            .map(|cookie| cookie.value().to_string())
+    })
+}
+
+pub fn session_cookie(token: &str) -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE, token.to_string())
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .finish()
+}
+
+pub(super) fn player_token_error(err: PlayerTokenError) -> actix_web::Error {
+    match err {
+        PlayerTokenError::UnknownIdentity => error::ErrorUnauthorized("Unknown identity token"),
+        PlayerTokenError::InvalidSession => {
+            error::ErrorUnauthorized("Session has expired, log in again")
+        }
+    }
+}
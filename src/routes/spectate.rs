@@ -0,0 +1,32 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetSpectatorState, GetSpectatorStateError};
+
+#[derive(Deserialize, Debug)]
+struct SpectateQuery {
+    code: String,
+}
+
+#[get("spectate")]
+async fn spectate(
+    query: web::Query<SpectateQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let game_code = u16::from_str_radix(&query.code, 16)
+        .map_err(|_err| error::ErrorBadRequest("Invalid game code"))?;
+
+    let state = game_handler
+        .send(GetSpectatorState { game_code })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| match err {
+            GetSpectatorStateError::GameNotFound => error::ErrorNotFound("Unable to find the game"),
+            GetSpectatorStateError::DailyInProgress => {
+                error::ErrorForbidden("Daily challenges can be watched once the day is over")
+            }
+        })?;
+
+    Ok(web::Json(state))
+}
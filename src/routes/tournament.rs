@@ -0,0 +1,31 @@
+use actix::Addr;
+use actix_web::{error, get, web, Responder};
+use serde::Deserialize;
+
+use crate::game_handler::{self, GetTournament, GetTournamentError};
+
+#[derive(Deserialize, Debug)]
+struct TournamentQuery {
+    code: String,
+}
+
+#[get("tournament")]
+async fn tournament(
+    query: web::Query<TournamentQuery>,
+    game_handler: web::Data<Addr<game_handler::GameHandler>>,
+) -> actix_web::Result<impl Responder> {
+    let tournament_code = u16::from_str_radix(&query.code, 16)
+        .map_err(|_err| error::ErrorBadRequest("Invalid tournament code"))?;
+
+    let state = game_handler
+        .send(GetTournament { tournament_code })
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .map_err(|err| match err {
+            GetTournamentError::TournamentNotFound => {
+                error::ErrorNotFound("Unable to find the tournament")
+            }
+        })?;
+
+    Ok(web::Json(state))
+}
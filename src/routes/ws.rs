@@ -1,10 +1,10 @@
-use crate::{game::ExternalCell, game_handler};
+use crate::game_handler;
 use actix::{
-    fut, Actor, ActorContext, ActorFuture, ActorFutureExt, Addr, ContextFutureSpawner,
-    StreamHandler, WrapFuture,
+    fut, Actor, ActorContext, ActorFuture, ActorFutureExt, Addr, AsyncContext,
+    ContextFutureSpawner, Handler, StreamHandler, WrapFuture,
 };
 use actix_web_actors::ws::{self, CloseReason};
-use serde::Serialize;
+use minesweeper_core::protocol::{ClientMessage, GameAction};
 
 pub struct GameWebSocketActor {
     pub game_handler_addr: Addr<game_handler::GameHandler>,
@@ -12,16 +12,25 @@ pub struct GameWebSocketActor {
     pub player_code: u16,
 }
 
-#[derive(Serialize, Debug)]
-struct GameStateUpdate {
-    board_state: Vec<ExternalCell>,
-    board_size: usize,
-    start_time: Option<u64>,
-    finished_time: Option<u64>,
-}
-
 impl Actor for GameWebSocketActor {
     type Context = ws::WebsocketContext<Self>;
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.game_handler_addr.do_send(game_handler::Unsubscribe {
+            game_code: self.game_code,
+            player_code: self.player_code,
+        });
+    }
+}
+
+impl Handler<game_handler::GameUpdated> for GameWebSocketActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: game_handler::GameUpdated, ctx: &mut Self::Context) {
+        self.send_game_state()
+            .then(|_, _, _| fut::ready(()))
+            .wait(ctx);
+    }
 }
 
 impl GameWebSocketActor {
@@ -51,9 +60,9 @@ impl GameWebSocketActor {
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for GameWebSocketActor {
     fn handle(&mut self, item: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         if let Ok(ws::Message::Text(message)) = item {
-            let action = serde_json::from_str(&message);
+            let action = serde_json::from_str::<ClientMessage>(&message);
 
-            if let Err(_) = action {
+            if action.is_err() {
                 ctx.close(Some(CloseReason {
                     code: ws::CloseCode::Invalid,
                     description: Some("Invalid message received over web socket.".to_string()),
@@ -62,22 +71,53 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for GameWebSocketActo
                 return;
             }
 
-            let action: game_handler::PlayerAction = action.unwrap();
-
-            self.game_handler_addr
-                .send(game_handler::PlayerMove {
-                    game_code: self.game_code,
-                    player_code: self.player_code,
-                    action,
-                })
-                .into_actor(self)
-                .then(|_res, act, _ctx| act.send_game_state())
-                .then(|_, _, _| fut::ready(()))
-                .wait(ctx);
+            match action.unwrap() {
+                ClientMessage::Move(action) => self
+                    .game_handler_addr
+                    .send(game_handler::PlayerMove {
+                        game_code: self.game_code,
+                        player_code: self.player_code,
+                        action,
+                    })
+                    .into_actor(self)
+                    .then(|_res, act, _ctx| act.send_game_state())
+                    .then(|_, _, _| fut::ready(()))
+                    .wait(ctx),
+
+                ClientMessage::Host(action) => self
+                    .game_handler_addr
+                    .send(game_handler::HostCommand {
+                        game_code: self.game_code,
+                        player_code: self.player_code,
+                        action,
+                    })
+                    .into_actor(self)
+                    .then(|_res, act, _ctx| act.send_game_state())
+                    .then(|_, _, _| fut::ready(()))
+                    .wait(ctx),
+
+                ClientMessage::Game(GameAction::Rematch { same_board }) => self
+                    .game_handler_addr
+                    .send(game_handler::RequestRematch {
+                        game_code: self.game_code,
+                        player_code: self.player_code,
+                        same_board,
+                    })
+                    .into_actor(self)
+                    .then(|_res, act, _ctx| act.send_game_state())
+                    .then(|_, _, _| fut::ready(()))
+                    .wait(ctx),
+            }
         }
     }
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        self.game_handler_addr.do_send(game_handler::Subscribe {
+            game_code: self.game_code,
+            player_code: self.player_code,
+            recipient: ctx.address().recipient(),
+        });
+
         self.send_game_state()
             .then(|_, _, _| fut::ready(()))
             .wait(ctx);
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::game_handler::{
+    Account, GameResult, Identity, PlayerId, Rating, RatingChange, Session, Tournament, WsGame,
+};
+
+use super::{Storage, StorageError};
+
+/// Storage that only lives as long as the process, used by default and in tests
+#[derive(Default)]
+pub struct InMemoryStorage {
+    games: HashMap<u16, WsGame>,
+    tournaments: HashMap<u16, Tournament>,
+    results: Vec<GameResult>,
+    identities: Vec<Identity>,
+    accounts: Vec<Account>,
+    sessions: HashMap<String, Session>,
+    ratings: Vec<Rating>,
+    rating_changes: Vec<RatingChange>,
+    daily_secret: Option<Vec<u8>>,
+}
+
+impl Storage for InMemoryStorage {
+    fn save_game(&mut self, code: u16, game: &WsGame) -> Result<(), StorageError> {
+        self.games.insert(code, game.clone());
+        Ok(())
+    }
+
+    fn load_games(&self) -> Result<HashMap<u16, WsGame>, StorageError> {
+        Ok(self.games.clone())
+    }
+
+    fn delete_game(&mut self, code: u16) -> Result<(), StorageError> {
+        self.games.remove(&code);
+        Ok(())
+    }
+
+    fn save_tournament(&mut self, code: u16, tournament: &Tournament) -> Result<(), StorageError> {
+        self.tournaments.insert(code, tournament.clone());
+        Ok(())
+    }
+
+    fn load_tournaments(&self) -> Result<HashMap<u16, Tournament>, StorageError> {
+        Ok(self.tournaments.clone())
+    }
+
+    fn save_result(&mut self, result: &GameResult) -> Result<(), StorageError> {
+        self.results.push(result.clone());
+        Ok(())
+    }
+
+    fn load_results(&self) -> Result<Vec<GameResult>, StorageError> {
+        Ok(self.results.clone())
+    }
+
+    fn save_identity(&mut self, identity: &Identity) -> Result<(), StorageError> {
+        self.identities.push(identity.clone());
+        Ok(())
+    }
+
+    fn load_identities(&self) -> Result<Vec<Identity>, StorageError> {
+        Ok(self.identities.clone())
+    }
+
+    fn save_account(&mut self, account: &Account) -> Result<(), StorageError> {
+        self.accounts.push(account.clone());
+        Ok(())
+    }
+
+    fn load_accounts(&self) -> Result<Vec<Account>, StorageError> {
+        Ok(self.accounts.clone())
+    }
+
+    fn save_session(&mut self, session: &Session) -> Result<(), StorageError> {
+        self.sessions.insert(session.token.clone(), session.clone());
+        Ok(())
+    }
+
+    fn delete_session(&mut self, token_hash: &str) -> Result<(), StorageError> {
+        self.sessions.remove(token_hash);
+        Ok(())
+    }
+
+    fn load_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        Ok(self.sessions.values().cloned().collect())
+    }
+
+    fn save_rating(&mut self, rating: &Rating, change: &RatingChange) -> Result<(), StorageError> {
+        self.ratings.retain(|saved| {
+            saved.player_id != rating.player_id || saved.difficulty != rating.difficulty
+        });
+        self.ratings.push(rating.clone());
+        self.rating_changes.push(change.clone());
+        Ok(())
+    }
+
+    fn load_ratings(&self) -> Result<Vec<Rating>, StorageError> {
+        Ok(self.ratings.clone())
+    }
+
+    fn load_rating_history(&self, player_id: &PlayerId) -> Result<Vec<RatingChange>, StorageError> {
+        Ok(self
+            .rating_changes
+            .iter()
+            .filter(|change| &change.player_id == player_id)
+            .cloned()
+            .collect())
+    }
+
+    fn save_daily_secret(&mut self, secret: &[u8]) -> Result<(), StorageError> {
+        self.daily_secret = Some(secret.to_vec());
+        Ok(())
+    }
+
+    fn load_daily_secret(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.daily_secret.clone())
+    }
+}
@@ -0,0 +1,96 @@
+//! Persistence for games, tournaments and their results.
+//!
+//! The [`GameHandler`](crate::game_handler::GameHandler) keeps every game in
+//! memory and writes through to a [`Storage`] after each change, so that games
+//! in progress can be restored when the server restarts. Games are deleted
+//! again some time after they finish, while their results are kept.
+
+mod memory;
+mod sqlite;
+
+pub use memory::InMemoryStorage;
+pub use sqlite::SqliteStorage;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::game_handler::{
+    Account, GameResult, Identity, PlayerId, Rating, RatingChange, Session, Tournament, WsGame,
+};
+
+#[derive(Debug)]
+pub enum StorageError {
+    Sqlite(rusqlite::Error),
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Sqlite(err) => write!(f, "database error: {}", err),
+            StorageError::Serialization(err) => write!(f, "serialization error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        StorageError::Sqlite(err)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(err: serde_json::Error) -> Self {
+        StorageError::Serialization(err)
+    }
+}
+
+pub trait Storage {
+    /// Inserts or replaces a game along with all of its players
+    fn save_game(&mut self, code: u16, game: &WsGame) -> Result<(), StorageError>;
+
+    fn load_games(&self) -> Result<HashMap<u16, WsGame>, StorageError>;
+
+    fn delete_game(&mut self, code: u16) -> Result<(), StorageError>;
+
+    fn save_tournament(&mut self, code: u16, tournament: &Tournament) -> Result<(), StorageError>;
+
+    fn load_tournaments(&self) -> Result<HashMap<u16, Tournament>, StorageError>;
+
+    fn save_result(&mut self, result: &GameResult) -> Result<(), StorageError>;
+
+    /// All recorded results, oldest first
+    fn load_results(&self) -> Result<Vec<GameResult>, StorageError>;
+
+    /// Identities and sessions are saved with their token already hashed
+    fn save_identity(&mut self, identity: &Identity) -> Result<(), StorageError>;
+
+    fn load_identities(&self) -> Result<Vec<Identity>, StorageError>;
+
+    fn save_account(&mut self, account: &Account) -> Result<(), StorageError>;
+
+    fn load_accounts(&self) -> Result<Vec<Account>, StorageError>;
+
+    fn save_session(&mut self, session: &Session) -> Result<(), StorageError>;
+
+    fn delete_session(&mut self, token_hash: &str) -> Result<(), StorageError>;
+
+    fn load_sessions(&self) -> Result<Vec<Session>, StorageError>;
+
+    /// Replaces a player's rating and appends the change that led to it to
+    /// their history
+    fn save_rating(&mut self, rating: &Rating, change: &RatingChange) -> Result<(), StorageError>;
+
+    fn load_ratings(&self) -> Result<Vec<Rating>, StorageError>;
+
+    /// Every change of a player's ratings, oldest first
+    fn load_rating_history(&self, player_id: &PlayerId) -> Result<Vec<RatingChange>, StorageError>;
+
+    /// Keeps the key the daily challenge is derived from, so that the day's
+    /// board survives a restart when none is configured
+    fn save_daily_secret(&mut self, secret: &[u8]) -> Result<(), StorageError>;
+
+    fn load_daily_secret(&self) -> Result<Option<Vec<u8>>, StorageError>;
+}
@@ -0,0 +1,709 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, Connection};
+
+use crate::game_handler::{
+    hash_token, Account, Difficulty, GameResult, Identity, PlayerId, Rating, RatingChange, Session,
+    Tournament, WsGame,
+};
+
+use super::{Storage, StorageError};
+
+/// Schema changes applied in order. The number of migrations already applied is
+/// tracked in SQLite's `user_version` so new entries must only ever be appended.
+/// Migrations may call `hash_token` to hash tokens the way the game handler does.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE IF NOT EXISTS games (
+        code INTEGER PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS results (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        game_code INTEGER NOT NULL,
+        player_name TEXT NOT NULL,
+        board_size INTEGER NOT NULL,
+        mines INTEGER NOT NULL,
+        won INTEGER NOT NULL,
+        start_time INTEGER NOT NULL,
+        finished_time INTEGER NOT NULL
+    );
+",
+    "
+    ALTER TABLE results ADD COLUMN bbbv INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN openings INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN islands INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE results ADD COLUMN clicks INTEGER NOT NULL DEFAULT 0;
+",
+    "
+    ALTER TABLE results ADD COLUMN suspicions TEXT NOT NULL DEFAULT '[]';
+",
+    "
+    CREATE TABLE IF NOT EXISTS tournaments (
+        code INTEGER PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+",
+    "
+    CREATE TABLE IF NOT EXISTS identities (
+        player_id TEXT PRIMARY KEY,
+        token TEXT NOT NULL UNIQUE,
+        created_time INTEGER NOT NULL
+    );
+
+    ALTER TABLE results ADD COLUMN player_id TEXT;
+",
+    "
+    CREATE TABLE IF NOT EXISTS accounts (
+        username TEXT PRIMARY KEY COLLATE NOCASE,
+        password_hash TEXT NOT NULL,
+        player_id TEXT NOT NULL UNIQUE,
+        created_time INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS sessions (
+        token TEXT PRIMARY KEY,
+        username TEXT NOT NULL REFERENCES accounts (username),
+        created_time INTEGER NOT NULL
+    );
+",
+    "
+    CREATE TABLE IF NOT EXISTS ratings (
+        player_id TEXT NOT NULL,
+        difficulty TEXT NOT NULL,
+        rating REAL NOT NULL,
+        games INTEGER NOT NULL,
+        PRIMARY KEY (player_id, difficulty)
+    );
+
+    CREATE TABLE IF NOT EXISTS rating_changes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        player_id TEXT NOT NULL,
+        difficulty TEXT NOT NULL,
+        game_code INTEGER NOT NULL,
+        rating_before REAL NOT NULL,
+        rating_after REAL NOT NULL,
+        time INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS rating_changes_by_player ON rating_changes (player_id);
+",
+    "
+    ALTER TABLE results ADD COLUMN daily INTEGER;
+",
+    "
+    ALTER TABLE results ADD COLUMN solo INTEGER NOT NULL DEFAULT 0;
+",
+    "
+    ALTER TABLE results ADD COLUMN unranked INTEGER NOT NULL DEFAULT 0;
+",
+    "
+    UPDATE identities SET token = hash_token(token);
+    ALTER TABLE identities RENAME COLUMN token TO token_hash;
+
+    UPDATE sessions SET token = hash_token(token);
+    ALTER TABLE sessions RENAME COLUMN token TO token_hash;
+",
+    "
+    CREATE TABLE IF NOT EXISTS daily_secret (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        secret BLOB NOT NULL
+    );
+",
+];
+
+/// Storage backed by an embedded SQLite database.
+///
+/// Games and tournaments are stored as JSON documents, one row each, while
+/// results get a proper table so they can be queried.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    /// Opens or creates the database at `path`. `":memory:"` opens a
+    /// temporary database that is discarded when dropped.
+    pub fn open(path: impl AsRef<Path>) -> Result<SqliteStorage, StorageError> {
+        let connection = Connection::open(path)?;
+        Self::migrate(&connection)?;
+
+        Ok(SqliteStorage { connection })
+    }
+
+    fn migrate(connection: &Connection) -> Result<(), StorageError> {
+        let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        connection.create_scalar_function(
+            "hash_token",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |context| Ok(hash_token(&context.get::<String>(0)?)),
+        )?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            connection.execute_batch(migration)?;
+            connection.pragma_update(None, "user_version", index + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn from_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis as u64)
+}
+
+/// Difficulties are stored by the same name they have in the API
+fn difficulty_name(difficulty: Difficulty) -> Result<String, StorageError> {
+    Ok(serde_json::to_value(difficulty)?
+        .as_str()
+        .expect("difficulties serialize to strings")
+        .to_string())
+}
+
+fn difficulty_from_name(name: String) -> Result<Difficulty, StorageError> {
+    Ok(serde_json::from_value(serde_json::Value::String(name))?)
+}
+
+impl Storage for SqliteStorage {
+    fn save_game(&mut self, code: u16, game: &WsGame) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO games (code, data) VALUES (?1, ?2)",
+            params![code, serde_json::to_string(game)?],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_games(&self) -> Result<HashMap<u16, WsGame>, StorageError> {
+        let mut statement = self.connection.prepare("SELECT code, data FROM games")?;
+
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, u16>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut games = HashMap::new();
+
+        for row in rows {
+            let (code, data) = row?;
+            games.insert(code, serde_json::from_str(&data)?);
+        }
+
+        Ok(games)
+    }
+
+    fn delete_game(&mut self, code: u16) -> Result<(), StorageError> {
+        self.connection
+            .execute("DELETE FROM games WHERE code = ?1", params![code])?;
+
+        Ok(())
+    }
+
+    fn save_tournament(&mut self, code: u16, tournament: &Tournament) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO tournaments (code, data) VALUES (?1, ?2)",
+            params![code, serde_json::to_string(tournament)?],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_tournaments(&self) -> Result<HashMap<u16, Tournament>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT code, data FROM tournaments")?;
+
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, u16>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut tournaments = HashMap::new();
+
+        for row in rows {
+            let (code, data) = row?;
+            tournaments.insert(code, serde_json::from_str(&data)?);
+        }
+
+        Ok(tournaments)
+    }
+
+    fn save_result(&mut self, result: &GameResult) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT INTO results
+                (game_code, player_name, board_size, mines, won, start_time, finished_time,
+                    bbbv, openings, islands, clicks, suspicions, player_id, daily, solo,
+                    unranked)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                result.game_code,
+                result.player_name,
+                result.board_size,
+                result.mines,
+                result.won,
+                to_millis(result.start_time),
+                to_millis(result.finished_time),
+                result.bbbv,
+                result.openings,
+                result.islands,
+                result.clicks,
+                serde_json::to_string(&result.suspicions)?,
+                result.player_id.as_ref().map(PlayerId::as_str),
+                result.daily.map(|day| day as i64),
+                result.solo,
+                result.unranked,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_results(&self) -> Result<Vec<GameResult>, StorageError> {
+        let mut statement = self.connection.prepare(
+            "SELECT game_code, player_name, board_size, mines, won, start_time, finished_time,
+                    bbbv, openings, islands, clicks, suspicions, player_id, daily, solo,
+                    unranked
+                FROM results ORDER BY id",
+        )?;
+
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(11)?,
+                GameResult {
+                    game_code: row.get(0)?,
+                    player_name: row.get(1)?,
+                    board_size: row.get(2)?,
+                    mines: row.get(3)?,
+                    won: row.get(4)?,
+                    start_time: from_millis(row.get(5)?),
+                    finished_time: from_millis(row.get(6)?),
+                    bbbv: row.get(7)?,
+                    openings: row.get(8)?,
+                    islands: row.get(9)?,
+                    clicks: row.get(10)?,
+                    suspicions: Vec::new(),
+                    player_id: row.get::<_, Option<String>>(12)?.map(PlayerId::new),
+                    daily: row.get::<_, Option<i64>>(13)?.map(|day| day as u64),
+                    solo: row.get(14)?,
+                    unranked: row.get(15)?,
+                },
+            ))
+        })?;
+
+        let mut results = Vec::new();
+
+        for row in rows {
+            let (suspicions, mut result) = row?;
+            result.suspicions = serde_json::from_str(&suspicions)?;
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    fn save_identity(&mut self, identity: &Identity) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT INTO identities (player_id, token_hash, created_time) VALUES (?1, ?2, ?3)",
+            params![
+                identity.player_id.as_str(),
+                identity.token,
+                to_millis(identity.created_time),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_identities(&self) -> Result<Vec<Identity>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT player_id, token_hash, created_time FROM identities")?;
+
+        let rows = statement.query_map([], |row| {
+            Ok(Identity {
+                player_id: PlayerId::new(row.get(0)?),
+                token: row.get(1)?,
+                created_time: from_millis(row.get(2)?),
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<Identity>, rusqlite::Error>>()?)
+    }
+
+    fn save_account(&mut self, account: &Account) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT INTO accounts (username, password_hash, player_id, created_time)
+                VALUES (?1, ?2, ?3, ?4)",
+            params![
+                account.username,
+                account.password_hash,
+                account.player_id.as_str(),
+                to_millis(account.created_time),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_accounts(&self) -> Result<Vec<Account>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT username, password_hash, player_id, created_time FROM accounts")?;
+
+        let rows = statement.query_map([], |row| {
+            Ok(Account {
+                username: row.get(0)?,
+                password_hash: row.get(1)?,
+                player_id: PlayerId::new(row.get(2)?),
+                created_time: from_millis(row.get(3)?),
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<Account>, rusqlite::Error>>()?)
+    }
+
+    fn save_session(&mut self, session: &Session) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT INTO sessions (token_hash, username, created_time) VALUES (?1, ?2, ?3)",
+            params![
+                session.token,
+                session.username,
+                to_millis(session.created_time)
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn delete_session(&mut self, token_hash: &str) -> Result<(), StorageError> {
+        self.connection.execute(
+            "DELETE FROM sessions WHERE token_hash = ?1",
+            params![token_hash],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        let mut statement = self.connection.prepare(
+            "SELECT sessions.token_hash, accounts.username, accounts.player_id, sessions.created_time
+                FROM sessions JOIN accounts ON accounts.username = sessions.username",
+        )?;
+
+        let rows = statement.query_map([], |row| {
+            Ok(Session {
+                token: row.get(0)?,
+                username: row.get(1)?,
+                player_id: PlayerId::new(row.get(2)?),
+                created_time: from_millis(row.get(3)?),
+            })
+        })?;
+
+        Ok(rows.collect::<Result<Vec<Session>, rusqlite::Error>>()?)
+    }
+
+    fn save_rating(&mut self, rating: &Rating, change: &RatingChange) -> Result<(), StorageError> {
+        let transaction = self.connection.transaction()?;
+
+        transaction.execute(
+            "INSERT OR REPLACE INTO ratings (player_id, difficulty, rating, games)
+                VALUES (?1, ?2, ?3, ?4)",
+            params![
+                rating.player_id.as_str(),
+                difficulty_name(rating.difficulty)?,
+                rating.rating,
+                rating.games as i64,
+            ],
+        )?;
+        transaction.execute(
+            "INSERT INTO rating_changes
+                (player_id, difficulty, game_code, rating_before, rating_after, time)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                change.player_id.as_str(),
+                difficulty_name(change.difficulty)?,
+                change.game_code,
+                change.rating_before,
+                change.rating_after,
+                to_millis(change.time),
+            ],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn load_ratings(&self) -> Result<Vec<Rating>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT player_id, difficulty, rating, games FROM ratings")?;
+
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(1)?,
+                Rating {
+                    player_id: PlayerId::new(row.get(0)?),
+                    difficulty: Difficulty::Beginner,
+                    rating: row.get(2)?,
+                    games: row.get::<_, i64>(3)? as usize,
+                },
+            ))
+        })?;
+
+        rows.map(|row| {
+            let (difficulty, mut rating) = row?;
+            rating.difficulty = difficulty_from_name(difficulty)?;
+            Ok(rating)
+        })
+        .collect()
+    }
+
+    fn load_rating_history(&self, player_id: &PlayerId) -> Result<Vec<RatingChange>, StorageError> {
+        let mut statement = self.connection.prepare(
+            "SELECT difficulty, game_code, rating_before, rating_after, time
+                FROM rating_changes WHERE player_id = ?1 ORDER BY id",
+        )?;
+
+        let rows = statement.query_map(params![player_id.as_str()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                RatingChange {
+                    player_id: player_id.clone(),
+                    difficulty: Difficulty::Beginner,
+                    game_code: row.get(1)?,
+                    rating_before: row.get(2)?,
+                    rating_after: row.get(3)?,
+                    time: from_millis(row.get(4)?),
+                },
+            ))
+        })?;
+
+        rows.map(|row| {
+            let (difficulty, mut change) = row?;
+            change.difficulty = difficulty_from_name(difficulty)?;
+            Ok(change)
+        })
+        .collect()
+    }
+
+    fn save_daily_secret(&mut self, secret: &[u8]) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO daily_secret (id, secret) VALUES (0, ?1)",
+            params![secret],
+        )?;
+
+        Ok(())
+    }
+
+    fn load_daily_secret(&self) -> Result<Option<Vec<u8>>, StorageError> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT secret FROM daily_secret WHERE id = 0")?;
+        let mut rows = statement.query_map([], |row| row.get(0))?;
+
+        Ok(rows.next().transpose()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_handler::{Clock, GameConfig, Suspicion, Visibility};
+    use minesweeper_core::Board;
+
+    #[test]
+    fn test_games_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+
+        let game = WsGame::with_board(
+            GameConfig {
+                board_size: 4,
+                player_limit: 2,
+                hint_limit: 0,
+                hint_penalty_secs: 0,
+                clock: Clock::Blitz { limit_secs: 30 },
+                visibility: Visibility::Public,
+            },
+            Board::from_mines(4, &[3, 7]).unwrap(),
+        );
+
+        storage.save_game(0xbeef, &game).unwrap();
+
+        let games = storage.load_games().unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&games[&0xbeef]).unwrap(),
+            serde_json::to_value(&game).unwrap()
+        );
+
+        storage.delete_game(0xbeef).unwrap();
+        assert!(storage.load_games().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_results_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+
+        let result = GameResult {
+            game_code: 1,
+            player_name: "alice".to_string(),
+            player_id: Some(PlayerId::new("00c0ffee".to_string())),
+            board_size: 8,
+            mines: 10,
+            won: true,
+            start_time: from_millis(1_000),
+            finished_time: from_millis(25_500),
+            bbbv: 12,
+            openings: 2,
+            islands: 1,
+            clicks: 15,
+            suspicions: vec![Suspicion::InhumanMoveRate],
+            daily: Some(20_000),
+            solo: true,
+            unranked: true,
+        };
+
+        storage.save_result(&result).unwrap();
+
+        assert_eq!(storage.load_results().unwrap(), vec![result]);
+    }
+
+    #[test]
+    fn test_identities_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+
+        let identity = Identity {
+            player_id: PlayerId::new("00c0ffee".to_string()),
+            token: "secret".to_string(),
+            created_time: from_millis(1_000),
+        };
+
+        storage.save_identity(&identity).unwrap();
+
+        assert_eq!(storage.load_identities().unwrap(), vec![identity]);
+    }
+
+    #[test]
+    fn test_accounts_and_sessions_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+
+        let account = Account {
+            username: "Alice".to_string(),
+            password_hash: "hash".to_string(),
+            player_id: PlayerId::new("00c0ffee".to_string()),
+            created_time: from_millis(1_000),
+        };
+        let session = Session {
+            token: "secret".to_string(),
+            username: "alice".to_string(),
+            player_id: account.player_id.clone(),
+            created_time: from_millis(2_000),
+        };
+
+        storage.save_account(&account).unwrap();
+        storage.save_session(&session).unwrap();
+
+        assert_eq!(storage.load_accounts().unwrap(), vec![account]);
+        assert_eq!(
+            storage.load_sessions().unwrap()[0].username,
+            "Alice",
+            "sessions take the username as registered"
+        );
+
+        storage.delete_session("secret").unwrap();
+        assert!(storage.load_sessions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migration_hashes_saved_tokens() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(&MIGRATIONS[..10].concat())
+            .unwrap();
+        connection.pragma_update(None, "user_version", 10).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO identities VALUES ('00c0ffee', 'secret', 0);
+                INSERT INTO accounts VALUES ('alice', 'hash', '00beef00', 0);
+                INSERT INTO sessions VALUES ('other secret', 'alice', 0);",
+            )
+            .unwrap();
+
+        SqliteStorage::migrate(&connection).unwrap();
+        let storage = SqliteStorage { connection };
+
+        assert_eq!(
+            storage.load_identities().unwrap()[0].token,
+            hash_token("secret")
+        );
+        assert_eq!(
+            storage.load_sessions().unwrap()[0].token,
+            hash_token("other secret")
+        );
+    }
+
+    #[test]
+    fn test_ratings_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+
+        let player_id = PlayerId::new("00c0ffee".to_string());
+        let change = |game_code, rating_before, rating_after| RatingChange {
+            player_id: player_id.clone(),
+            difficulty: Difficulty::Expert,
+            game_code,
+            rating_before,
+            rating_after,
+            time: from_millis(1_000),
+        };
+
+        for (games, change) in [change(1, 1500.0, 1520.0), change(2, 1520.0, 1505.5)]
+            .into_iter()
+            .enumerate()
+        {
+            let rating = Rating {
+                player_id: player_id.clone(),
+                difficulty: Difficulty::Expert,
+                rating: change.rating_after,
+                games: games + 1,
+            };
+            storage.save_rating(&rating, &change).unwrap();
+        }
+
+        assert_eq!(
+            storage.load_ratings().unwrap(),
+            vec![Rating {
+                player_id: player_id.clone(),
+                difficulty: Difficulty::Expert,
+                rating: 1505.5,
+                games: 2,
+            }]
+        );
+        assert_eq!(
+            storage.load_rating_history(&player_id).unwrap(),
+            vec![change(1, 1500.0, 1520.0), change(2, 1520.0, 1505.5)]
+        );
+    }
+
+    #[test]
+    fn test_daily_secret_round_trip() {
+        let mut storage = SqliteStorage::open(":memory:").unwrap();
+        assert_eq!(storage.load_daily_secret().unwrap(), None);
+
+        storage.save_daily_secret(b"first").unwrap();
+        storage.save_daily_secret(b"second").unwrap();
+        assert_eq!(
+            storage.load_daily_secret().unwrap(),
+            Some(b"second".to_vec())
+        );
+    }
+}